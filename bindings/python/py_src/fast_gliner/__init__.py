@@ -1,14 +1,22 @@
+import asyncio
+import functools
+import os
 from pathlib import Path
-from typing import List, Literal, Optional, Tuple, Union
+from typing import Callable, Dict, Iterable, Iterator, List, Optional, Tuple, Union
 from abc import ABC
 
 from huggingface_hub import snapshot_download
 
 from .fast_gliner import (
+    PyEntitySpan,
     PyFastGliNER,
     PyFastGliNER2,
     PyGLiNER2PipelineSchema,
+    PyRelationSchema,
     PyRelationSchemaEntry,
+    available_providers,
+    resolve_onnx_model_path,
+    validate_model_dir,
 )
 
 
@@ -23,13 +31,24 @@ class _FastGLiNERBase(ABC):
 
     _backend = None
 
+    # Runtime-specific keyword arguments forwarded by `from_pretrained` to the backend constructor
+    _options: Tuple[str, ...] = ()
+
     def __init__(
         self,
         model_path: str,
-        onnx_path: Optional[str] = "onnx/model.onnx",
-        execution_provider: Optional[Literal["cpu", "cuda"]] = None,
+        onnx_path: Optional[str] = None,
+        execution_provider: Optional[str] = None,
+        fallback_to_cpu: bool = False,
+        **options,
     ):
-        self.model = self._backend(model_path, onnx_path, execution_provider)
+        self.model = self._backend(
+            model_path,
+            onnx_path,
+            execution_provider,
+            fallback_to_cpu=fallback_to_cpu,
+            **options,
+        )
 
     @staticmethod
     def _normalize_input(input_text):
@@ -46,57 +65,282 @@ class _FastGLiNERBase(ABC):
         return input_text, False
 
     @staticmethod
-    def _normalize_relation_schema(schema: List[dict]) -> List[PyRelationSchemaEntry]:
+    def _normalize_relation_schema(
+        schema: Union[List[dict], str, os.PathLike, PyRelationSchema],
+    ) -> Union[List[PyRelationSchemaEntry], PyRelationSchema]:
+        if isinstance(schema, PyRelationSchema):
+            return schema
+        if isinstance(schema, (str, os.PathLike)):
+            return PyRelationSchemaEntry.from_json_file(os.fspath(schema))
         return [
             PyRelationSchemaEntry(
                 relation=entry["relation"],
                 subject_labels=entry["subject_labels"],
                 object_labels=entry["object_labels"],
+                symmetric=entry.get("symmetric", False),
+                reflexive=entry.get("reflexive", False),
             )
             for entry in schema
         ]
 
+    @classmethod
+    def prepare_relation_schema(
+        cls, schema: Union[List[dict], str, os.PathLike]
+    ) -> PyRelationSchema:
+        """
+        Parse and validate a relation schema once, to be given to `extract_relations`
+        or `extract_relations_from_entities` for many calls instead of the schema
+        itself (which is otherwise parsed and validated again at each call).
+
+        Parameters
+        ----------
+        schema : List[dict] or path
+            Relation definitions (see `extract_relations`).
+
+        Raises
+        ------
+        ValueError
+            If the schema file cannot be read or is invalid.
+        """
+        return PyRelationSchema(cls._normalize_relation_schema(schema))
+
     def _extract_relations_common(
         self,
         input_text: Union[str, List[str]],
         labels: List[str],
-        schema: List[dict],
+        schema: Union[List[dict], str, os.PathLike, PyRelationSchema],
+        relation_threshold: Optional[float] = None,
     ) -> Union[List[dict], List[List[dict]]]:
         texts, single = self._normalize_input(input_text)
         schema_entries = self._normalize_relation_schema(schema)
-        results = self.model.extract_relations(texts, labels, schema_entries)
+        results = self.model.extract_relations(
+            texts, labels, schema_entries, relation_threshold
+        )
         return results[0] if single else results
 
-    def predict_entities(
-        self, input_text: Union[str, List[str]], labels: List[str]
+    @staticmethod
+    def _entity_tuple(entity: Union[PyEntitySpan, dict]) -> Tuple[int, int, str]:
+        if isinstance(entity, dict):
+            return entity["start"], entity["end"], entity["label"]
+        return entity.start, entity.end, entity.label
+
+    def extract_relations_from_entities(
+        self,
+        input_text: Union[str, List[str]],
+        entities: Union[List[Union[PyEntitySpan, dict]], List[List[Union[PyEntitySpan, dict]]]],
+        schema: Union[List[dict], str, os.PathLike, PyRelationSchema],
+        relation_threshold: Optional[float] = None,
     ) -> Union[List[dict], List[List[dict]]]:
         """
-        Predict entities in the given text(s).
+        Extract relations between already known entities (e.g. curated ones), skipping
+        entity detection.
 
         Parameters
         ----------
         input_text : str or List[str]
             Input text or batch of texts.
-        labels : List[str]
-            Entity labels to detect.
+        entities : list of entities, or one list per text for a batch
+            Entities as returned by `predict_entities`, or dicts with `start`, `end` (byte
+            offsets in the UTF-8 encoded text, as in `PyEntitySpan.to_dict()`) and `label`.
+        schema : List[dict], path or PyRelationSchema
+            Relation definitions (see `extract_relations`).
+        relation_threshold : float, optional
+            Minimum probability in [0.0, 1.0] for a relation to be returned.
 
         Returns
         -------
         List[dict] or List[List[dict]]
-            Predicted entities.
+            Extracted relations.
+
+        Raises
+        ------
+        ValueError
+            If the offsets of an entity do not match its text, if the schema is invalid,
+            or if `relation_threshold` is outside [0.0, 1.0].
+        """
+        texts, single = self._normalize_input(input_text)
+        if single:
+            entities = [entities]
+        entity_tuples = [
+            [self._entity_tuple(entity) for entity in text_entities]
+            for text_entities in entities
+        ]
+        schema_entries = self._normalize_relation_schema(schema)
+        results = self.model.extract_relations_from_entities(
+            texts, entity_tuples, schema_entries, relation_threshold
+        )
+        return results[0] if single else results
+
+    def warmup(self) -> None:
+        """
+        Run a tiny dummy inference so that the runtime allocates its resources up front.
+
+        The first inference is otherwise noticeably slower than the following ones.
+        This does not alter the model or its parameters.
+        """
+        self.model.warmup()
+
+    def predict_entities(
+        self,
+        input_text: Union[str, List[str]],
+        labels: Union[List[str], List[List[str]]],
+        threshold: Optional[float] = None,
+        flat_ner: Optional[bool] = None,
+        dup_label: Optional[bool] = None,
+        multi_label: Optional[bool] = None,
+        progress: Optional[Callable[[int, int], None]] = None,
+        with_text: bool = False,
+        with_token_counts: bool = False,
+        force_flat: Optional[bool] = None,
+        label_descriptions: Optional[Dict[str, str]] = None,
+    ) -> Union[List[PyEntitySpan], List[List[PyEntitySpan]], dict, List[dict], tuple]:
+        """
+        Predict entities in the given text(s).
+
+        Parameters
+        ----------
+        input_text : str or List[str]
+            Input text or batch of texts.
+        labels : List[str] or List[List[str]]
+            Entity labels to detect, either shared by all texts or given as one list
+            per text (FastGLiNER only). Texts sharing the same labels are still
+            processed together.
+        threshold : float, optional
+            Probability threshold in [0.0, 1.0], applied to every text of the batch.
+            Defaults to the model's configured threshold.
+        flat_ner : bool, optional
+            If True (default), overlapping entities are not allowed. Set to False
+            to get nested or overlapping entities.
+        dup_label : bool, optional
+            If `flat_ner` is False, allow overlapping entities with the *same* label
+            (default: False).
+        multi_label : bool, optional
+            If `flat_ner` is False, allow overlapping entities with *different* labels
+            (default: False).
+        progress : Callable[[int, int], None], optional
+            Called as `progress(completed, total)` after each batch of texts (see the
+            `batch_size` option), e.g. to drive a progress bar (FastGLiNER only).
+        with_text : bool
+            If True, the entities of each text are returned as a dict
+            `{"index": ..., "text": ..., "entities": [...]}` holding the position and the
+            original text, so that results can be correlated with their source even after
+            being reordered or filtered (default: False).
+        with_token_counts : bool
+            If True, also return the number of tokens (words) processed for each text, as
+            an `(entities, token_counts)` tuple, e.g. to measure throughput in tokens per
+            second. Truncated words are not counted (FastGLiNER only, default: False).
+        force_flat : bool, optional
+            If True, no two returned entities overlap, whatever `flat_ner` and
+            `multi_label`: residual conflicts are resolved by keeping the most probable
+            entity, e.g. for annotation UIs requiring a flat annotation (default: False).
+        label_descriptions : Dict[str, str], optional
+            Descriptions of some labels (e.g. `{"ORG_TICKER": "stock ticker"}`), given
+            to the model instead of the labels themselves since descriptive labels work
+            better. Entities still carry the original labels (FastGLiNER only).
+
+        Returns
+        -------
+        List[PyEntitySpan] or List[List[PyEntitySpan]]
+            Predicted entities, exposing `text`, `label`, `score`, `start` and `end`
+            (byte offsets), as well as `char_start` and `char_end` (character offsets).
+            Use `to_dict()` on each entity to get a plain dict. With `with_text`, one
+            dict per text as described above. With `with_token_counts`, a tuple of these
+            results and of the token counts (a single count for a single text).
+
+        Raises
+        ------
+        ValueError
+            If `threshold` is outside [0.0, 1.0], or if no text or no label is given
+            (or a label or a label description is blank).
         """
 
         texts, single = self._normalize_input(input_text)
 
-        results = self.model.predict_entities(texts, labels)
+        results = self.model.predict_entities(
+            texts,
+            labels,
+            threshold,
+            flat_ner,
+            dup_label,
+            multi_label,
+            progress,
+            with_token_counts,
+            force_flat,
+            label_descriptions,
+        )
+        if with_token_counts:
+            results, token_counts = results
 
+        if with_text:
+            results = self._with_text(texts, results)
+
+        if with_token_counts:
+            return (results[0], token_counts[0]) if single else (results, token_counts)
         return results[0] if single else results
 
+    async def predict_entities_async(self, *args, **kwargs):
+        """
+        Same as `predict_entities` (taking the same arguments and returning the same
+        results, in the same order), but awaitable: the inference runs on a worker
+        thread of the event loop's default executor, so that the event loop is not
+        blocked meanwhile (e.g. when serving the model from an asyncio-based service).
+
+        Note that a `progress` callback is then called from the worker thread.
+        """
+        loop = asyncio.get_running_loop()
+        return await loop.run_in_executor(
+            None, functools.partial(self.predict_entities, *args, **kwargs)
+        )
+
+    def predict_entities_one(
+        self,
+        text: str,
+        labels: List[str],
+        threshold: Optional[float] = None,
+        flat_ner: Optional[bool] = None,
+        dup_label: Optional[bool] = None,
+        multi_label: Optional[bool] = None,
+        force_flat: Optional[bool] = None,
+        label_descriptions: Optional[Dict[str, str]] = None,
+    ) -> List[PyEntitySpan]:
+        """
+        Predict entities in a single text, returning the flat list of its entities
+        (same as `predict_entities` given a string, see there for the parameters).
+
+        Raises
+        ------
+        TypeError
+            If `text` is not a string.
+        """
+        if not isinstance(text, str):
+            raise TypeError(f"text must be a string, got {type(text).__name__}")
+        return self.predict_entities(
+            text,
+            labels,
+            threshold,
+            flat_ner,
+            dup_label,
+            multi_label,
+            force_flat=force_flat,
+            label_descriptions=label_descriptions,
+        )
+
+    @staticmethod
+    def _with_text(texts: List[str], results: list, offset: int = 0) -> List[dict]:
+        """
+        Attach the index (starting at `offset`) and original text to the results of each text.
+        """
+        return [
+            {"index": offset + i, "text": text, "entities": entities}
+            for i, (text, entities) in enumerate(zip(texts, results))
+        ]
+
     def extract_relations(
         self,
         input_text: Union[str, List[str]],
         labels: List[str],
-        schema: List[dict],
+        schema: Union[List[dict], str, os.PathLike, PyRelationSchema],
+        relation_threshold: Optional[float] = None,
     ):
         """
         Relation extraction is runtime-dependent.
@@ -109,8 +353,9 @@ class _FastGLiNERBase(ABC):
     def from_pretrained(
         cls,
         model_id: str,
-        onnx_path: Optional[str] = "onnx/model.onnx",
-        execution_provider: Optional[Literal["cpu", "cuda"]] = None,
+        onnx_path: Optional[str] = None,
+        execution_provider: Optional[str] = None,
+        fallback_to_cpu: bool = False,
         **kwargs,
     ):
         """
@@ -121,9 +366,19 @@ class _FastGLiNERBase(ABC):
         model_id : str
             Hugging Face repository ID or local directory path.
         onnx_path : str, optional
-            Path to the ONNX model inside the model directory.
-        execution_provider : {"cpu", "cuda"}, optional
-            ONNX Runtime execution provider.
+            Path to the ONNX model inside the model directory. If not given, the model is
+            searched in common locations (`onnx/model.onnx`, `model.onnx`,
+            `onnx/model_quantized.onnx`), or as the single ONNX file of the directory.
+        execution_provider : str, optional
+            ONNX Runtime execution provider: "cpu", "cuda", "tensorrt", "directml" (or
+            "dml"), "coreml" or "openvino". A device index can be given for CUDA, TensorRT
+            and DirectML, e.g. "cuda:1", and a device type for OpenVINO, e.g. "openvino:GPU".
+        fallback_to_cpu : bool, optional
+            If the execution provider fails to initialize (e.g. driver mismatch or out of
+            memory), load the model on the CPU with a `RuntimeWarning` instead of raising.
+        **kwargs
+            Runtime-specific options (see the runtime class documentation), any other
+            argument being forwarded to `huggingface_hub.snapshot_download`.
 
         Returns
         -------
@@ -136,32 +391,76 @@ class _FastGLiNERBase(ABC):
             If the ONNX model cannot be located.
         """
 
+        options = {name: kwargs.pop(name) for name in cls._options if name in kwargs}
+
         model_dir = Path(model_id)
 
         if not model_dir.exists():
             model_dir = Path(
                 snapshot_download(
                     repo_id=model_id,
-                    allow_patterns=["*.json", "*.model", "onnx/*.onnx"],
+                    allow_patterns=["*.json", "*.model", "*.onnx", "onnx/*.onnx"],
                     **kwargs,
                 )
             )
 
-        if not (model_dir / onnx_path).exists():
-            onnx_files = sorted(model_dir.rglob("*.onnx"))
-
-            if len(onnx_files) == 1:
-                onnx_path = onnx_files[0].relative_to(model_dir).as_posix()
-            else:
-                raise FileNotFoundError(f"Could not resolve ONNX model inside {model_dir}")
-
-        return cls(str(model_dir.resolve()), onnx_path, execution_provider)
+        try:
+            resolve_onnx_model_path(str(model_dir), onnx_path)
+        except FileNotFoundError:
+            onnx_files = [] if onnx_path is not None else sorted(model_dir.rglob("*.onnx"))
+            if len(onnx_files) != 1:
+                raise
+            onnx_path = onnx_files[0].relative_to(model_dir).as_posix()
+
+        return cls(
+            str(model_dir.resolve()),
+            onnx_path,
+            execution_provider,
+            fallback_to_cpu=fallback_to_cpu,
+            **options,
+        )
 
 
 class FastGLiNER(_FastGLiNERBase):
     """
     Python wrapper for the GLiNER runtime.
 
+    Options
+    -------
+    max_length : int, optional
+        Maximum number of words per text (default: `max_len` from `gliner_config.json`
+        if present, otherwise 512). Longer texts are truncated. The probability threshold
+        also defaults to the `threshold` of `gliner_config.json`, if any.
+    max_width : int, optional
+        Maximum span width, in words (default: read from `gliner_config.json`).
+        Must be lower than `max_length`.
+    engine_cache_path : str, optional
+        Directory where TensorRT engines are cached across runs (requires
+        `execution_provider="tensorrt"`).
+    batch_size : int, optional
+        Maximum number of texts per inference (default: all texts at once). Larger
+        inputs are split into sub-batches, which bounds memory usage.
+    intra_threads : int, optional
+        Number of threads used by ONNX Runtime within each operator (default: 4),
+        0 letting ONNX Runtime decide. Useful to avoid oversubscribing the cores when
+        running several models on the same host.
+    inter_threads : int, optional
+        Number of threads used by ONNX Runtime to run independent operators in
+        parallel (default: sequential execution of the graph).
+    gpu_mem_limit : int, optional
+        Maximum size, in bytes, of the GPU memory arena allocated by ONNX Runtime
+        (requires `execution_provider="cuda"`). Useful to share a GPU between several
+        models or processes.
+    optimization_level : str, optional
+        Graph optimization level of the ONNX Runtime session: "disable", "basic",
+        "extended" or "all" (default).
+    cpu_arena : bool, optional
+        Enables or disables the CPU memory arena of ONNX Runtime (default: enabled).
+        Disabling it lowers the memory footprint, at the cost of more allocations.
+    memory_pattern : bool, optional
+        Enables or disables the memory pattern optimization of ONNX Runtime (default:
+        enabled), which pre-allocates memory according to the previous input shapes.
+
     Example
     -------
     ```python
@@ -177,25 +476,203 @@ class FastGLiNER(_FastGLiNERBase):
     Output
     ------
     ```python
-    [
-        {
-            "text": "James Bond",
-            "label": "person",
-            "score": 0.90,
-            "start": 5,
-            "end": 15
-        }
-    ]
+    [PyEntitySpan(text="James Bond", label="person", score=0.90, start=5, end=15)]
     ```
     """
 
     _backend = PyFastGliNER
+    _options = (
+        "max_length",
+        "max_width",
+        "engine_cache_path",
+        "batch_size",
+        "intra_threads",
+        "inter_threads",
+        "gpu_mem_limit",
+        "optimization_level",
+        "cpu_arena",
+        "memory_pattern",
+    )
+
+    @classmethod
+    def from_bytes(
+        cls,
+        config_bytes: bytes,
+        tokenizer_bytes: bytes,
+        model_bytes: bytes,
+        execution_provider: Optional[str] = None,
+        **options,
+    ) -> "FastGLiNER":
+        """
+        Load a model from memory, without any file on disk.
+
+        Parameters
+        ----------
+        config_bytes : bytes
+            Contents of `gliner_config.json`.
+        tokenizer_bytes : bytes
+            Contents of `tokenizer.json`.
+        model_bytes : bytes
+            Contents of the ONNX model.
+        execution_provider : str, optional
+            ONNX Runtime execution provider, as for `from_pretrained`.
+        **options
+            Runtime options (see above).
+
+        Returns
+        -------
+        FastGLiNER
+            Loaded model instance.
+        """
+        instance = cls.__new__(cls)
+        instance.model = cls._backend.from_bytes(
+            config_bytes, tokenizer_bytes, model_bytes, execution_provider, **options
+        )
+        return instance
+
+    def predict_entities_timed(
+        self,
+        input_text: Union[str, List[str]],
+        labels: Union[List[str], List[List[str]]],
+        threshold: Optional[float] = None,
+        flat_ner: Optional[bool] = None,
+        dup_label: Optional[bool] = None,
+        multi_label: Optional[bool] = None,
+    ) -> tuple:
+        """
+        Same as `predict_entities`, also measuring the time spent in each phase of the
+        pipeline, e.g. to benchmark execution providers or batch sizes.
+
+        Returns
+        -------
+        tuple
+            The predicted entities (as returned by `predict_entities`), and a dict of
+            durations in seconds: `preprocessing` (splitting, prompting, tokenization and
+            input tensors), `session` (ONNX Runtime), `postprocessing` (decoding and
+            overlap resolution) and `total`, summed over all batches.
+        """
+        texts, single = self._normalize_input(input_text)
+        results, timings = self.model.predict_entities_timed(
+            texts, labels, threshold, flat_ner, dup_label, multi_label
+        )
+        return (results[0] if single else results), timings
+
+    def predict_entities_iter(
+        self,
+        texts: Iterable[str],
+        labels: List[str],
+        batch_size: int = 32,
+        threshold: Optional[float] = None,
+        flat_ner: Optional[bool] = None,
+        dup_label: Optional[bool] = None,
+        multi_label: Optional[bool] = None,
+        with_text: bool = False,
+        force_flat: Optional[bool] = None,
+        label_descriptions: Optional[Dict[str, str]] = None,
+    ) -> Iterator[Union[List[List[PyEntitySpan]], List[dict]]]:
+        """
+        Predict entities in a stream of texts, by chunks of `batch_size` texts.
+
+        Unlike `predict_entities`, results are yielded as soon as each chunk is
+        processed, so that large corpora (possibly given as a generator) never
+        have to be held in memory at once: the texts are pulled lazily by the
+        model, the next chunk only once the current one has been yielded.
+
+        Parameters
+        ----------
+        texts : Iterable[str]
+            Input texts.
+        labels : List[str]
+            Entity labels to detect.
+        batch_size : int
+            Number of texts per chunk (default: 32).
+        threshold, flat_ner, dup_label, multi_label, with_text, force_flat, label_descriptions
+            Same as for `predict_entities` (with `with_text`, the index is the position of
+            the text in the whole stream).
+
+        Yields
+        ------
+        List[List[PyEntitySpan]] or List[dict]
+            Predicted entities for each text of the chunk, in input order.
+        """
+        if batch_size < 1:
+            raise ValueError("batch_size must be a positive integer")
+
+        stream = self.model.predict_entities_stream(
+            texts,
+            labels,
+            batch_size,
+            threshold,
+            flat_ner,
+            dup_label,
+            multi_label,
+            force_flat,
+            label_descriptions,
+        )
+        offset = 0
+        for chunk, results in stream:
+            yield self._with_text(chunk, results, offset) if with_text else results
+            offset += len(chunk)
+
+    def close(self) -> None:
+        """
+        Release the underlying ONNX Runtime session (and the memory it holds, e.g. on GPU)
+        right away, instead of waiting for the object to be garbage-collected.
+
+        Any subsequent inference raises a `RuntimeError`.
+        """
+        self.model.close()
+
+    def __enter__(self) -> "FastGLiNER":
+        return self
+
+    def __exit__(self, exc_type, exc_value, traceback) -> None:
+        self.close()
+
+    def tokenize_preview(self, text: str) -> List[Tuple[str, int, int]]:
+        """
+        Split the given text into words the same way as before inference, which helps
+        diagnosing unexpected entity offsets (entities being made of whole words).
+
+        Returns
+        -------
+        List[Tuple[str, int, int]]
+            Each word with its start and end character offsets, e.g.
+            `text[start:end] == word`.
+        """
+        return self.model.tokenize_preview(text)
+
+    @property
+    def labels(self) -> Optional[List[str]]:
+        """
+        Entity labels declared in the model configuration (`labels` or `entity_labels`
+        in `gliner_config.json`), typically the ones the model was fine-tuned on, or
+        `None` if the configuration does not declare any.
+
+        Any label can still be used for inference, but these tend to work best.
+        """
+        return self.model.known_labels()
+
+    def describe(self) -> dict:
+        """
+        Describe the inputs and outputs of the loaded ONNX model, which helps
+        diagnosing model compatibility issues. The description is read once, when
+        loading the model (including with `from_bytes`).
+
+        Returns
+        -------
+        dict
+            `inputs` and `outputs`, as lists of dicts with `name`, `element_type`
+            and `shape` keys (dynamic dimensions being `-1`).
+        """
+        return self.model.describe()
 
     def extract_relations(
         self,
         input_text: Union[str, List[str]],
         labels: List[str],
-        schema: List[dict],
+        schema: Union[List[dict], str, os.PathLike, PyRelationSchema],
+        relation_threshold: Optional[float] = None,
     ) -> Union[List[dict], List[List[dict]]]:
         """
         Extract relations between entities based on a user-defined schema.
@@ -206,19 +683,37 @@ class FastGLiNER(_FastGLiNERBase):
             Input text or batch of texts.
         labels : List[str]
             Entity labels to detect.
-        schema : List[dict]
+        schema : List[dict] or path
             Relation definitions with:
             - relation
             - subject_labels
             - object_labels
+            - symmetric (optional): if True, the relation holds in both directions
+              and each pair of entities is reported once
+            - reflexive (optional): if True, the subject and the object may be the
+              same entity (otherwise such relations are rejected)
+            Or the path of a JSON file mapping each relation to its labels, e.g.
+            `{"founded": {"subject_labels": ["person"], "object_labels": ["company"]}}`.
+            Or a schema prepared once with `prepare_relation_schema`.
+        relation_threshold : float, optional
+            Minimum probability in [0.0, 1.0] for a relation to be returned, on top of
+            the entity threshold. Defaults to the entity threshold only.
 
         Returns
         -------
         List[dict] or List[List[dict]]
             Extracted relations.
+
+        Raises
+        ------
+        ValueError
+            If the schema file cannot be read or is invalid, or if `relation_threshold`
+            is outside [0.0, 1.0].
         """
 
-        return self._extract_relations_common(input_text, labels, schema)
+        return self._extract_relations_common(
+            input_text, labels, schema, relation_threshold
+        )
 
 
 class FastGLiNER2(_FastGLiNERBase):
@@ -227,6 +722,32 @@ class FastGLiNER2(_FastGLiNERBase):
 
     GLiNER2 supports NER, classification, structured extraction, and relation extraction.
 
+    Options
+    -------
+    engine_cache_path : str, optional
+        Directory where TensorRT engines are cached across runs (requires
+        `execution_provider="tensorrt"`).
+    intra_threads : int, optional
+        Number of threads used by ONNX Runtime within each operator (default: 4),
+        0 letting ONNX Runtime decide. Useful to avoid oversubscribing the cores when
+        running several models on the same host.
+    inter_threads : int, optional
+        Number of threads used by ONNX Runtime to run independent operators in
+        parallel (default: sequential execution of the graph).
+    gpu_mem_limit : int, optional
+        Maximum size, in bytes, of the GPU memory arena allocated by ONNX Runtime
+        (requires `execution_provider="cuda"`). Useful to share a GPU between several
+        models or processes.
+    optimization_level : str, optional
+        Graph optimization level of the ONNX Runtime session: "disable", "basic",
+        "extended" or "all" (default).
+    cpu_arena : bool, optional
+        Enables or disables the CPU memory arena of ONNX Runtime (default: enabled).
+        Disabling it lowers the memory footprint, at the cost of more allocations.
+    memory_pattern : bool, optional
+        Enables or disables the memory pattern optimization of ONNX Runtime (default:
+        enabled), which pre-allocates memory according to the previous input shapes.
+
     Example
     -------
     ```python
@@ -241,32 +762,63 @@ class FastGLiNER2(_FastGLiNERBase):
     """
 
     _backend = PyFastGliNER2
+    _options = (
+        "engine_cache_path",
+        "intra_threads",
+        "inter_threads",
+        "gpu_mem_limit",
+        "optimization_level",
+        "cpu_arena",
+        "memory_pattern",
+    )
 
     def predict_entities(
-        self, input_text: Union[str, List[str]], labels: List[str]
-    ) -> Union[List[dict], List[List[dict]]]:
+        self,
+        input_text: Union[str, List[str]],
+        labels: List[str],
+        threshold: Optional[float] = None,
+        flat_ner: Optional[bool] = None,
+        dup_label: Optional[bool] = None,
+        multi_label: Optional[bool] = None,
+        force_flat: Optional[bool] = None,
+        label_descriptions: Optional[Dict[str, str]] = None,
+    ) -> Union[List[PyEntitySpan], List[List[PyEntitySpan]]]:
         """
         Run NER inference using GLiNER2.
 
         Note
         ----
-        GLiNER2 currently does **not support batched inference**.
+        GLiNER2 currently does **not support batched inference**, nor label descriptions.
         """
 
+        if label_descriptions:
+            raise ValueError("GLiNER2 does not support label descriptions.")
+
         if isinstance(input_text, list) and len(input_text) > 1:
             raise ValueError(
                 "GLiNER2 currently does not support batched inference. Please pass a single input string."
             )
 
-        return super().predict_entities(input_text, labels)
+        return super().predict_entities(
+            input_text,
+            labels,
+            threshold,
+            flat_ner,
+            dup_label,
+            multi_label,
+            force_flat=force_flat,
+        )
 
     def extract_relations(
         self,
         input_text: Union[str, List[str]],
         labels: List[str],
-        schema: List[dict],
+        schema: Union[List[dict], str, os.PathLike, PyRelationSchema],
+        relation_threshold: Optional[float] = None,
     ) -> Union[List[dict], List[List[dict]]]:
-        return self._extract_relations_common(input_text, labels, schema)
+        return self._extract_relations_common(
+            input_text, labels, schema, relation_threshold
+        )
 
     def classify(self, text: str, labels: List[str]):
         return self.model.classify(text, labels)
@@ -340,4 +892,11 @@ class FastGLiNER2(_FastGLiNERBase):
 
 __version__ = "0.2.1"
 
-__all__ = ["FastGLiNER", "FastGLiNER2"]
+__all__ = [
+    "FastGLiNER",
+    "FastGLiNER2",
+    "PyEntitySpan",
+    "available_providers",
+    "resolve_onnx_model_path",
+    "validate_model_dir",
+]
@@ -2,6 +2,7 @@ mod output;
 mod pipeline;
 mod schema;
 
+use output::PyEntitySpan;
 use pipeline::*;
 use pyo3::prelude::*;
 use schema::*;
@@ -10,7 +11,14 @@ use schema::*;
 fn fast_gliner(_py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<PyFastGliNER>()?;
     m.add_class::<PyFastGliNER2>()?;
+    m.add_class::<PyEntitySpan>()?;
+    m.add_class::<PyEntityStream>()?;
+    m.add_class::<PyEntityStream>()?;
     m.add_class::<PyGLiNER2PipelineSchema>()?;
+    m.add_class::<PyRelationSchema>()?;
     m.add_class::<PyRelationSchemaEntry>()?;
+    m.add_function(wrap_pyfunction!(available_providers, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_model_dir, m)?)?;
+    m.add_function(wrap_pyfunction!(resolve_onnx_model_path, m)?)?;
     Ok(())
 }
@@ -1,4 +1,5 @@
 use gliner::model::gliner2::{ExtractedValue, ExtractionOutput, GLiNER2PipelineOutput};
+use gliner::model::info::{ModelDirReport, ModelInfo};
 use gliner::model::output::{decoded::SpanOutput, relation::Relation, relation::RelationOutput};
 use gliner::text::span::Span;
 use pyo3::prelude::*;
@@ -8,20 +9,101 @@ pub(crate) trait ToPy {
     fn to_py(&self, py: Python<'_>) -> PyResult<Py<PyAny>>;
 }
 
+/// Typed entity returned by `predict_entities`
+#[pyclass]
+#[derive(Clone)]
+pub struct PyEntitySpan {
+    #[pyo3(get)]
+    pub text: String,
+    #[pyo3(get)]
+    pub label: String,
+    #[pyo3(get)]
+    pub score: f32,
+    #[pyo3(get)]
+    pub start: usize,
+    #[pyo3(get)]
+    pub end: usize,
+    #[pyo3(get)]
+    pub char_start: usize,
+    #[pyo3(get)]
+    pub char_end: usize,
+}
+
+impl PyEntitySpan {
+    /// Builds the entity given the span and the text it was extracted from (to compute character offsets)
+    fn new(span: &Span, text: &str) -> PyResult<Self> {
+        let (start, end) = span.offsets();
+        let (char_start, char_end) = span.char_offsets(text).ok_or_else(|| {
+            pyo3::exceptions::PyRuntimeError::new_err(format!(
+                "entity offsets ({start}..{end}) do not match the input text"
+            ))
+        })?;
+        Ok(Self {
+            text: span.text().to_string(),
+            label: span.class().to_string(),
+            score: span.probability(),
+            start,
+            end,
+            char_start,
+            char_end,
+        })
+    }
+}
+
+#[pymethods]
+impl PyEntitySpan {
+    /// Returns the entity as a plain dict (same shape as in previous versions)
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let span_dict = PyDict::new_bound(py);
+        span_dict.set_item("text", &self.text)?;
+        span_dict.set_item("label", &self.label)?;
+        span_dict.set_item("score", self.score)?;
+        span_dict.set_item("start", self.start)?;
+        span_dict.set_item("end", self.end)?;
+        span_dict.set_item("char_start", self.char_start)?;
+        span_dict.set_item("char_end", self.char_end)?;
+        Ok(span_dict)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "PyEntitySpan(text={:?}, label={:?}, score={}, start={}, end={})",
+            self.text, self.label, self.score, self.start, self.end
+        )
+    }
+}
+
 impl ToPy for SpanOutput {
     fn to_py(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
-        let results = PyList::empty_bound(py);
+        let results = self
+            .spans
+            .iter()
+            .zip(&self.texts)
+            .map(|(spans, text)| {
+                spans
+                    .iter()
+                    .map(|span| PyEntitySpan::new(span, text))
+                    .collect::<PyResult<Vec<_>>>()
+            })
+            .collect::<PyResult<Vec<_>>>()?;
 
-        for spans in &self.spans {
-            let py_spans = PyList::empty_bound(py);
-            for span in spans {
-                let span_dict = pipeline_output_to_py(py, span)?;
-                py_spans.append(span_dict)?;
-            }
-            results.append(py_spans)?;
-        }
+        Ok(results.into_py(py))
+    }
+}
+
+impl ToPy for ModelInfo {
+    fn to_py(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        let value = serde_json::to_value(self)
+            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
+        json_value_to_py(py, &value)
+    }
+}
 
-        Ok(results.into())
+impl ToPy for ModelDirReport {
+    fn to_py(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        let value = serde_json::to_value(self)
+            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
+        json_value_to_py(py, &value)
     }
 }
 
@@ -148,10 +230,7 @@ fn json_value_to_py(py: Python<'_>, value: &serde_json::Value) -> PyResult<Py<Py
     }
 }
 
-fn pipeline_output_to_py<'py>(
-    py: Python<'py>,
-    span: &Span,
-) -> PyResult<Bound<'py, PyDict>> {
+fn pipeline_output_to_py<'py>(py: Python<'py>, span: &Span) -> PyResult<Bound<'py, PyDict>> {
     let span_dict = PyDict::new_bound(py);
     span_dict.set_item("text", span.text())?;
     span_dict.set_item("label", span.class())?;
@@ -1,30 +1,58 @@
 use crate::output::ToPy;
 use crate::schema::PyGLiNER2PipelineSchema;
-use composable::*;
 use gliner::model::gliner2::{ExtractionFieldSchema, ExtractionSchema, GLiNER2};
-use gliner::model::input::relation::schema::RelationSchema;
-use gliner::model::output::decoded::SpanOutput;
-use gliner::model::pipeline::{relation::RelationPipeline, token::TokenPipeline};
+use gliner::model::info::ModelInfo;
+use gliner::model::input::relation::schema::{RelationSchema, RelationSpec};
+use gliner::model::output::{decoded::SpanOutput, relation::RelationOutput};
 use gliner::model::runtime::InferenceMode;
-use gliner::model::{input::text::TextInput, params::Parameters, GLiNER};
+use gliner::model::session::{Model, OptimizationLevel, SessionParameters};
+use gliner::model::{input::text::TextInput, params::Parameters, stream_batches, GLiNER};
+use gliner::text::span::Span;
 use gliner::util::result::Result as GResult;
-use orp::model::Model;
+use gliner::util::timing::{StageTimings, Timings};
 use orp::params::RuntimeParameters;
-use orp::pipeline::*;
 use ort::execution_providers::{CPUExecutionProvider, ExecutionProviderDispatch};
 use pyo3::prelude::*;
-use pyo3::types::PyAny;
+use pyo3::types::{PyAny, PyIterator};
 use pyo3::{Py, Python};
-use std::collections::HashMap;
-use std::path::Path;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
 #[cfg(feature = "cuda")]
 use ort::execution_providers::CUDAExecutionProvider;
+#[cfg(feature = "coreml")]
+use ort::execution_providers::CoreMLExecutionProvider;
+#[cfg(feature = "directml")]
+use ort::execution_providers::DirectMLExecutionProvider;
+#[cfg(feature = "openvino")]
+use ort::execution_providers::OpenVINOExecutionProvider;
+#[cfg(feature = "tensorrt")]
+use ort::execution_providers::TensorRTExecutionProvider;
 
 #[pyclass]
 pub struct PyFastGliNER {
-    model: Box<dyn Inferencer + Send + Sync>,
-    tokenizer_path: String,
+    /// Inner model, dropped (releasing the ORT session) when closed and no stream uses it anymore
+    model: Option<Arc<dyn Inferencer + Send + Sync>>,
+    params: Parameters,
+}
+
+/// Entities of the texts pulled lazily from a Python iterator, by batches (see `predict_entities_stream`)
+#[pyclass]
+pub struct PyEntityStream {
+    spans: Box<dyn Iterator<Item = GResult<Vec<Span>>> + Send>,
+    texts: PyTexts,
+    labels: Vec<String>,
+    batch_size: usize,
+}
+
+/// Texts pulled from a Python iterator, kept until their entities are returned. Since the iterator is consumed
+/// by the model, the error it raises (or a text which is not a string) ends it, and is raised again afterwards.
+#[derive(Clone)]
+struct PyTexts {
+    iterator: Arc<Py<PyIterator>>,
+    pending: Arc<Mutex<VecDeque<String>>>,
+    error: Arc<Mutex<Option<PyErr>>>,
 }
 
 #[pyclass]
@@ -41,128 +69,669 @@ pub struct PyRelationSchemaEntry {
     pub subject_labels: Vec<String>,
     #[pyo3(get, set)]
     pub object_labels: Vec<String>,
+    #[pyo3(get, set)]
+    pub symmetric: bool,
+    #[pyo3(get, set)]
+    pub reflexive: bool,
+}
+
+/// Relation schema, prepared once to be reused across calls (see `PyRelationSchemaEntry` for its entries)
+#[pyclass]
+pub struct PyRelationSchema {
+    schema: Arc<RelationSchema>,
+}
+
+/// Relation schema given to `extract_relations`: either prepared, or given by its entries (validated for each call)
+#[derive(FromPyObject)]
+enum RelationSchemaArg {
+    Prepared(Py<PyRelationSchema>),
+    Entries(Vec<PyRelationSchemaEntry>),
+}
+
+impl RelationSchemaArg {
+    fn into_schema(self, py: Python<'_>) -> PyResult<Arc<RelationSchema>> {
+        match self {
+            RelationSchemaArg::Prepared(schema) => Ok(schema.borrow(py).schema.clone()),
+            RelationSchemaArg::Entries(entries) => {
+                relation_schema_from_entries(entries).map(Arc::new)
+            }
+        }
+    }
+}
+
+/// Entity labels given to `predict_entities`: either shared by all texts, or one list per text
+#[derive(FromPyObject)]
+enum EntityLabels {
+    Shared(Vec<String>),
+    PerText(Vec<Vec<String>>),
 }
 
 trait Inferencer: Send + Sync {
-    fn inference(&self, input: TextInput) -> GResult<SpanOutput>;
-    fn get_orp_model(&self) -> &Model;
+    fn inference_with_params(&self, input: TextInput, params: &Parameters) -> GResult<SpanOutput>;
+    fn inference_with_progress(
+        &self,
+        input: TextInput,
+        params: &Parameters,
+        progress: &mut (dyn FnMut(usize, usize) -> GResult<()> + Send),
+    ) -> GResult<SpanOutput>;
+    fn inference_with_entities_with_progress(
+        &self,
+        texts: Vec<String>,
+        entities: Vec<Vec<String>>,
+        params: &Parameters,
+        progress: &mut (dyn FnMut(usize, usize) -> GResult<()> + Send),
+    ) -> GResult<SpanOutput>;
+    fn extract_relations(
+        &self,
+        input: TextInput,
+        relation_schema: &RelationSchema,
+        params: &Parameters,
+    ) -> GResult<RelationOutput>;
+    fn extract_relations_from_entities(
+        &self,
+        entities: SpanOutput,
+        relation_schema: &RelationSchema,
+        params: &Parameters,
+    ) -> GResult<RelationOutput>;
+    fn tokenize_preview(&self, text: &str) -> GResult<Vec<(String, usize, usize)>>;
+    fn known_labels(&self) -> Option<Vec<String>>;
+    fn model_info(&self) -> &ModelInfo;
 }
 
 impl Inferencer for InferenceMode {
-    fn inference(&self, input: TextInput) -> GResult<SpanOutput> {
-        self.inference(input)
+    fn inference_with_params(&self, input: TextInput, params: &Parameters) -> GResult<SpanOutput> {
+        self.inference_with_params(input, params)
+    }
+
+    fn inference_with_progress(
+        &self,
+        input: TextInput,
+        params: &Parameters,
+        progress: &mut (dyn FnMut(usize, usize) -> GResult<()> + Send),
+    ) -> GResult<SpanOutput> {
+        self.inference_with_progress(input, params, progress)
+    }
+
+    fn inference_with_entities_with_progress(
+        &self,
+        texts: Vec<String>,
+        entities: Vec<Vec<String>>,
+        params: &Parameters,
+        progress: &mut (dyn FnMut(usize, usize) -> GResult<()> + Send),
+    ) -> GResult<SpanOutput> {
+        self.inference_with_entities_with_progress(texts, entities, params, progress)
+    }
+
+    fn extract_relations(
+        &self,
+        input: TextInput,
+        relation_schema: &RelationSchema,
+        params: &Parameters,
+    ) -> GResult<RelationOutput> {
+        self.extract_relations(input, relation_schema, params)
+    }
+
+    fn extract_relations_from_entities(
+        &self,
+        entities: SpanOutput,
+        relation_schema: &RelationSchema,
+        params: &Parameters,
+    ) -> GResult<RelationOutput> {
+        self.extract_relations_from_entities(entities, relation_schema, params)
+    }
+
+    fn tokenize_preview(&self, text: &str) -> GResult<Vec<(String, usize, usize)>> {
+        self.tokenize_preview(text)
+    }
+
+    fn known_labels(&self) -> Option<Vec<String>> {
+        self.known_labels().map(<[String]>::to_vec)
     }
 
-    fn get_orp_model(&self) -> &Model {
-        self.get_inner_model()
+    fn model_info(&self) -> &ModelInfo {
+        self.model_info()
     }
 }
 
 #[pymethods]
 impl PyRelationSchemaEntry {
     #[new]
-    fn new(relation: String, subject_labels: Vec<String>, object_labels: Vec<String>) -> Self {
+    #[pyo3(signature = (relation, subject_labels, object_labels, symmetric=false, reflexive=false))]
+    fn new(
+        relation: String,
+        subject_labels: Vec<String>,
+        object_labels: Vec<String>,
+        symmetric: bool,
+        reflexive: bool,
+    ) -> Self {
         PyRelationSchemaEntry {
             relation,
             subject_labels,
             object_labels,
+            symmetric,
+            reflexive,
         }
     }
+
+    /// Loads schema entries from a JSON file mapping each relation to its subject and object labels
+    #[staticmethod]
+    fn from_json_file(path: String) -> PyResult<Vec<PyRelationSchemaEntry>> {
+        RelationSchema::from_json_file(&path)
+            .map(|schema| relation_schema_entries(&schema))
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))
+    }
+
+    /// Same as `from_json_file`, given the JSON document itself
+    #[staticmethod]
+    fn from_json_str(json: String) -> PyResult<Vec<PyRelationSchemaEntry>> {
+        RelationSchema::from_json_str(&json)
+            .map(|schema| relation_schema_entries(&schema))
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))
+    }
+}
+
+#[pymethods]
+impl PyRelationSchema {
+    /// Builds and validates the schema from its entries, once for all the calls it is given to
+    #[new]
+    fn new(entries: Vec<PyRelationSchemaEntry>) -> PyResult<Self> {
+        Ok(PyRelationSchema {
+            schema: Arc::new(relation_schema_from_entries(entries)?),
+        })
+    }
+
+    /// Entries of the schema
+    fn entries(&self) -> Vec<PyRelationSchemaEntry> {
+        relation_schema_entries(&self.schema)
+    }
 }
 
 #[pymethods]
 impl PyFastGliNER {
     #[new]
+    #[pyo3(signature = (model_dir, filename=None, execution_provider=None, max_length=None, max_width=None, engine_cache_path=None, batch_size=None, intra_threads=None, inter_threads=None, gpu_mem_limit=None, optimization_level=None, cpu_arena=None, memory_pattern=None, fallback_to_cpu=false))]
+    #[allow(clippy::too_many_arguments)]
     fn new(
+        py: Python<'_>,
         model_dir: String,
         filename: Option<String>,
         execution_provider: Option<String>,
+        max_length: Option<usize>,
+        max_width: Option<usize>,
+        engine_cache_path: Option<String>,
+        batch_size: Option<usize>,
+        intra_threads: Option<usize>,
+        inter_threads: Option<usize>,
+        gpu_mem_limit: Option<usize>,
+        optimization_level: Option<String>,
+        cpu_arena: Option<bool>,
+        memory_pattern: Option<bool>,
+        fallback_to_cpu: bool,
     ) -> PyResult<Self> {
-        let base = Path::new(&model_dir);
-        let tokenizer_path = base.join("tokenizer.json");
-        let providers = execution_providers_from_arg(execution_provider)?;
-        let runtime_params = RuntimeParameters::default().with_execution_providers(providers);
-
-        let model = match filename.as_deref() {
-            Some(onnx_path) => GLiNER::from_dir_with(
-                &model_dir,
-                Parameters::default(),
-                runtime_params,
-                None,
-                Some(onnx_path),
-                None,
-            ),
-            None => GLiNER::from_dir(&model_dir, Parameters::default(), runtime_params),
-        }
+        let runtime_params = runtime_parameters_from_args(
+            execution_provider,
+            engine_cache_path,
+            intra_threads,
+            gpu_mem_limit,
+        )?;
+        let runtime_params = session_parameters_from_args(
+            runtime_params,
+            optimization_level,
+            cpu_arena,
+            memory_pattern,
+            inter_threads,
+            fallback_to_cpu,
+        )?;
+
+        resolve_onnx_model_path(PathBuf::from(&model_dir), filename.clone())?;
+
+        // a missing configuration is reported by `from_dir_with`
+        let params = match std::fs::read_to_string(Path::new(&model_dir).join("gliner_config.json"))
+        {
+            Ok(config) => parameters_from_config(&config)?,
+            Err(_) => Parameters::default(),
+        };
+
+        let model = GLiNER::from_dir_with(
+            &model_dir,
+            params,
+            runtime_params,
+            None,
+            filename.as_deref(),
+            None,
+        )
         .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("{:?}", e)))?;
+        warn_cpu_fallback(py, model.get_inner_model())?;
 
-        let model: Box<dyn Inferencer + Send + Sync> = Box::new(model);
+        Self::from_model(model, max_length, max_width, batch_size)
+    }
 
-        Ok(PyFastGliNER {
-            model,
-            tokenizer_path: tokenizer_path.to_string_lossy().to_string(),
-        })
+    /// Loads a model from memory, given the contents of `gliner_config.json`, `tokenizer.json` and the ONNX model
+    #[staticmethod]
+    #[pyo3(signature = (config_bytes, tokenizer_bytes, model_bytes, execution_provider=None, max_length=None, max_width=None, engine_cache_path=None, batch_size=None, intra_threads=None, inter_threads=None, gpu_mem_limit=None, optimization_level=None, cpu_arena=None, memory_pattern=None, fallback_to_cpu=false))]
+    #[allow(clippy::too_many_arguments)]
+    fn from_bytes(
+        py: Python<'_>,
+        config_bytes: &[u8],
+        tokenizer_bytes: &[u8],
+        model_bytes: &[u8],
+        execution_provider: Option<String>,
+        max_length: Option<usize>,
+        max_width: Option<usize>,
+        engine_cache_path: Option<String>,
+        batch_size: Option<usize>,
+        intra_threads: Option<usize>,
+        inter_threads: Option<usize>,
+        gpu_mem_limit: Option<usize>,
+        optimization_level: Option<String>,
+        cpu_arena: Option<bool>,
+        memory_pattern: Option<bool>,
+        fallback_to_cpu: bool,
+    ) -> PyResult<Self> {
+        let runtime_params = runtime_parameters_from_args(
+            execution_provider,
+            engine_cache_path,
+            intra_threads,
+            gpu_mem_limit,
+        )?;
+        let runtime_params = session_parameters_from_args(
+            runtime_params,
+            optimization_level,
+            cpu_arena,
+            memory_pattern,
+            inter_threads,
+            fallback_to_cpu,
+        )?;
+
+        let config = std::str::from_utf8(config_bytes)
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+
+        let model = GLiNER::from_bytes(
+            config_bytes,
+            tokenizer_bytes,
+            model_bytes,
+            parameters_from_config(config)?,
+            runtime_params,
+        )
+        .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("{:?}", e)))?;
+        warn_cpu_fallback(py, model.get_inner_model())?;
+
+        Self::from_model(model, max_length, max_width, batch_size)
     }
 
+    /// Returns the entities of each text, along with the number of tokens processed for each text if
+    /// `with_token_counts` is set (as a `(entities, token_counts)` tuple)
+    #[pyo3(signature = (texts, labels, threshold=None, flat_ner=None, dup_label=None, multi_label=None, progress=None, with_token_counts=false, force_flat=None, label_descriptions=None))]
+    #[allow(clippy::too_many_arguments)]
     fn predict_entities(
         &self,
         py: Python<'_>,
         texts: Vec<String>,
+        labels: EntityLabels,
+        threshold: Option<f32>,
+        flat_ner: Option<bool>,
+        dup_label: Option<bool>,
+        multi_label: Option<bool>,
+        progress: Option<Bound<'_, PyAny>>,
+        with_token_counts: bool,
+        force_flat: Option<bool>,
+        label_descriptions: Option<HashMap<String, String>>,
+    ) -> PyResult<Py<PyAny>> {
+        let params = parameters_with_threshold(&self.params, threshold)?;
+        let params = parameters_with_decoding(params, flat_ner, dup_label, multi_label, force_flat);
+        let params = parameters_with_label_descriptions(params, label_descriptions)?;
+
+        let progress = progress.map(Bound::unbind);
+        let output = self.predict(py, texts, labels, &params, progress.as_ref())?;
+
+        let results = output.to_py(py)?;
+        if with_token_counts {
+            Ok((results, output.token_counts).into_py(py))
+        } else {
+            Ok(results)
+        }
+    }
+
+    /// Returns the entities of each text along with the time spent in each phase of the pipeline, as an
+    /// `(entities, timings)` tuple, the timings being a dict of durations in seconds
+    #[pyo3(signature = (texts, labels, threshold=None, flat_ner=None, dup_label=None, multi_label=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn predict_entities_timed(
+        &self,
+        py: Python<'_>,
+        texts: Vec<String>,
+        labels: EntityLabels,
+        threshold: Option<f32>,
+        flat_ner: Option<bool>,
+        dup_label: Option<bool>,
+        multi_label: Option<bool>,
+    ) -> PyResult<Py<PyAny>> {
+        let timings = Arc::new(Timings::default());
+        let params = parameters_with_threshold(&self.params, threshold)?;
+        let params = parameters_with_decoding(params, flat_ner, dup_label, multi_label, None)
+            .with_timings(Some(timings.clone()));
+
+        let output = self.predict(py, texts, labels, &params, None)?;
+        let timings = StageTimings::from_timings(&timings);
+        let timings: HashMap<&str, f64> = [
+            ("preprocessing", timings.pre_processing.as_secs_f64()),
+            ("session", timings.session.as_secs_f64()),
+            ("postprocessing", timings.post_processing.as_secs_f64()),
+            ("total", timings.total().as_secs_f64()),
+        ]
+        .into();
+        Ok((output.to_py(py)?, timings).into_py(py))
+    }
+
+    /// Returns an iterator over the entities of the texts pulled lazily from the given iterator, by batches
+    /// of `batch_size` texts (see `gliner::model::stream_batches`). Each item is a `(texts, entities)` tuple
+    /// for a batch, the next batch being only pulled once the current one has been returned.
+    #[pyo3(signature = (texts, labels, batch_size, threshold=None, flat_ner=None, dup_label=None, multi_label=None, force_flat=None, label_descriptions=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn predict_entities_stream(
+        &self,
+        texts: &Bound<'_, PyAny>,
         labels: Vec<String>,
+        batch_size: usize,
+        threshold: Option<f32>,
+        flat_ner: Option<bool>,
+        dup_label: Option<bool>,
+        multi_label: Option<bool>,
+        force_flat: Option<bool>,
+        label_descriptions: Option<HashMap<String, String>>,
+    ) -> PyResult<PyEntityStream> {
+        let params = parameters_with_threshold(&self.params, threshold)?;
+        let params = parameters_with_decoding(params, flat_ner, dup_label, multi_label, force_flat);
+        let params = parameters_with_label_descriptions(params, label_descriptions)?;
+        // the labels are checked once, rather than for each batch
+        text_input_from_strings(&[String::new()], &labels)?;
+
+        let model = self.model_arc()?.clone();
+        let texts = PyTexts {
+            iterator: Arc::new(texts.iter()?.unbind()),
+            pending: Default::default(),
+            error: Default::default(),
+        };
+        let batch_labels = labels.clone();
+        let spans = stream_batches(texts.clone(), batch_size, move |batch| {
+            let input = TextInput::new(batch, batch_labels.clone())?;
+            Ok(model.inference_with_params(input, &params)?.spans)
+        })
+        .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+        Ok(PyEntityStream {
+            spans: Box::new(spans),
+            texts,
+            labels,
+            batch_size,
+        })
+    }
+
+    #[pyo3(signature = (texts, entity_labels, relation_schema, relation_threshold=None))]
+    fn extract_relations(
+        &self,
+        py: Python<'_>,
+        texts: Vec<String>,
+        entity_labels: Vec<String>,
+        relation_schema: RelationSchemaArg,
+        relation_threshold: Option<f32>,
     ) -> PyResult<Py<PyAny>> {
-        let input = text_input_from_strings(&texts, &labels)?;
+        let input = text_input_from_strings(&texts, &entity_labels)?;
+        let relation_schema = relation_schema.into_schema(py)?;
+        let params = parameters_with_relation_threshold(&self.params, relation_threshold)?;
 
+        let model = self.model()?;
         let output = py
-            .allow_threads(|| self.model.inference(input))
+            .allow_threads(|| model.extract_relations(input, &relation_schema, &params))
             .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("{:?}", e)))?;
 
         output.to_py(py)
     }
 
-    fn extract_relations(
+    /// Performs relation extraction only, given the entities of each text as `(start, end, label)` (byte offsets)
+    #[pyo3(signature = (texts, entities, relation_schema, relation_threshold=None))]
+    fn extract_relations_from_entities(
         &self,
         py: Python<'_>,
         texts: Vec<String>,
-        entity_labels: Vec<String>,
-        relation_schema_entries: Vec<PyRelationSchemaEntry>,
+        entities: Vec<Vec<(usize, usize, String)>>,
+        relation_schema: RelationSchemaArg,
+        relation_threshold: Option<f32>,
     ) -> PyResult<Py<PyAny>> {
-        let input = text_input_from_strings(&texts, &entity_labels)?;
-        let relation_schema = relation_schema_from_entries(relation_schema_entries);
+        let entities = span_output_from_entities(texts, entities)?;
+        let relation_schema = relation_schema.into_schema(py)?;
+        let params = parameters_with_relation_threshold(&self.params, relation_threshold)?;
+
+        let model = self.model()?;
+        let output = py
+            .allow_threads(|| {
+                model.extract_relations_from_entities(entities, &relation_schema, &params)
+            })
+            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("{:?}", e)))?;
 
-        let orp_model = self.model.get_orp_model();
+        output.to_py(py)
+    }
 
-        let token_pipeline = TokenPipeline::new(&self.tokenizer_path)
+    /// Runs a tiny dummy inference to force the runtime to allocate and warm up its session up front
+    fn warmup(&self, py: Python<'_>) -> PyResult<()> {
+        let input = warmup_input()?;
+        let model = self.model()?;
+        py.allow_threads(|| model.inference_with_params(input, &self.params))
             .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("{:?}", e)))?;
+        Ok(())
+    }
 
-        let relation_pipeline =
-            RelationPipeline::default(&self.tokenizer_path, &relation_schema)
-                .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("{:?}", e)))?;
+    /// Returns the words of the given text, with their character offsets, as split before inference
+    fn tokenize_preview(&self, text: &str) -> PyResult<Vec<(String, usize, usize)>> {
+        self.model()?
+            .tokenize_preview(text)
+            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("{:?}", e)))
+    }
 
-        let params = Parameters::default();
+    /// Returns the entity labels declared in the model configuration, if any
+    fn known_labels(&self) -> PyResult<Option<Vec<String>>> {
+        Ok(self.model()?.known_labels())
+    }
 
-        let pipeline = composed![
-            token_pipeline.to_composable(orp_model, &params),
-            relation_pipeline.to_composable(orp_model, &params)
-        ];
+    /// Returns the names, element types and shapes of the model inputs and outputs, as read when loading the model
+    fn describe(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        self.model()?.model_info().to_py(py)
+    }
 
-        let output = py
-            .allow_threads(|| pipeline.apply(input))
+    /// Drops the inner model, releasing the ORT session (and the memory it holds) right away, or once the
+    /// streams still using it are dropped (see `predict_entities_stream`).
+    /// Any subsequent inference raises an error.
+    fn close(&mut self) {
+        self.model = None;
+    }
+
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __exit__(
+        &mut self,
+        _exc_type: &Bound<'_, PyAny>,
+        _exc_value: &Bound<'_, PyAny>,
+        _traceback: &Bound<'_, PyAny>,
+    ) -> bool {
+        self.close();
+        false
+    }
+}
+
+impl PyFastGliNER {
+    /// Performs inference on all texts at once (the model then splitting them by batches of `batch_size` if set),
+    /// calling `progress(completed, total)` after each batch if given. The GIL is released while inferring, and
+    /// held while calling `progress`: an exception raised by `progress` stops the inference and is raised again.
+    fn predict(
+        &self,
+        py: Python<'_>,
+        texts: Vec<String>,
+        labels: EntityLabels,
+        params: &Parameters,
+        progress: Option<&Py<PyAny>>,
+    ) -> PyResult<SpanOutput> {
+        let model = self.model()?;
+        let mut progress_error = None;
+        let mut notify = |completed: usize, total: usize| -> GResult<()> {
+            let Some(progress) = progress else {
+                return Ok(());
+            };
+            Python::with_gil(|py| progress.call1(py, (completed, total)))
+                .map(drop)
+                .map_err(|e| {
+                    let message = e.to_string();
+                    progress_error = Some(e);
+                    message.into()
+                })
+        };
+        let output = match labels {
+            EntityLabels::Shared(labels) => {
+                let input = text_input_from_strings(&texts, &labels)?;
+                py.allow_threads(|| model.inference_with_progress(input, params, &mut notify))
+            }
+            EntityLabels::PerText(labels) => {
+                check_labels_per_text(&texts, &labels)?;
+                py.allow_threads(|| {
+                    model.inference_with_entities_with_progress(texts, labels, params, &mut notify)
+                })
+            }
+        };
+        if let Some(error) = progress_error {
+            return Err(error);
+        }
+        output.map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("{:?}", e)))
+    }
+
+    fn model(&self) -> PyResult<&(dyn Inferencer + Send + Sync)> {
+        self.model_arc().map(Arc::as_ref)
+    }
+
+    fn model_arc(&self) -> PyResult<&Arc<dyn Inferencer + Send + Sync>> {
+        self.model
+            .as_ref()
+            .ok_or_else(|| pyo3::exceptions::PyRuntimeError::new_err("the model has been closed"))
+    }
+
+    fn from_model(
+        model: InferenceMode,
+        max_length: Option<usize>,
+        max_width: Option<usize>,
+        batch_size: Option<usize>,
+    ) -> PyResult<Self> {
+        let params = parameters_with_lengths(model.get_parameters(), max_length, max_width)?;
+        let params = parameters_with_batch_size(params, batch_size)?;
+        let model: Arc<dyn Inferencer + Send + Sync> = Arc::new(model);
+
+        Ok(PyFastGliNER {
+            model: Some(model),
+            params,
+        })
+    }
+}
+
+#[pymethods]
+impl PyEntityStream {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    /// Returns the texts of the next batch along with their entities, as a `(texts, entities)` tuple.
+    /// The GIL is released while inferring (and held while pulling the texts).
+    fn __next__(&mut self, py: Python<'_>) -> PyResult<Option<Py<PyAny>>> {
+        let (spans, batch_size) = (&mut self.spans, self.batch_size);
+        let spans: Vec<GResult<Vec<Span>>> =
+            py.allow_threads(|| spans.by_ref().take(batch_size).collect());
+        if let Some(error) = self.texts.take_error() {
+            return Err(error);
+        }
+        if spans.is_empty() {
+            return Ok(None);
+        }
+        let texts = self.texts.take_pending(spans.len());
+        let spans = spans
+            .into_iter()
+            .collect::<GResult<Vec<_>>>()
             .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("{:?}", e)))?;
+        let output = SpanOutput::new(texts.clone(), self.labels.clone(), spans);
+        Ok(Some((texts, output.to_py(py)?).into_py(py)))
+    }
+}
 
-        output.to_py(py)
+impl PyTexts {
+    fn take_error(&self) -> Option<PyErr> {
+        self.error.lock().ok()?.take()
+    }
+
+    fn take_pending(&self, count: usize) -> Vec<String> {
+        match self.pending.lock() {
+            Ok(mut pending) => {
+                let count = count.min(pending.len());
+                pending.drain(..count).collect()
+            }
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+impl Iterator for PyTexts {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        let text = Python::with_gil(|py| {
+            let next = self.iterator.bind(py).clone().next()?;
+            Some(next.and_then(|text| text.extract::<String>()))
+        })?;
+        match text {
+            Ok(text) => {
+                if let Ok(mut pending) = self.pending.lock() {
+                    pending.push_back(text.clone());
+                }
+                Some(text)
+            }
+            Err(error) => {
+                if let Ok(mut slot) = self.error.lock() {
+                    *slot = Some(error);
+                }
+                None
+            }
+        }
     }
 }
 
 #[pymethods]
 impl PyFastGliNER2 {
     #[new]
+    #[pyo3(signature = (model_dir, filename=None, execution_provider=None, engine_cache_path=None, intra_threads=None, inter_threads=None, gpu_mem_limit=None, optimization_level=None, cpu_arena=None, memory_pattern=None, fallback_to_cpu=false))]
+    #[allow(clippy::too_many_arguments)]
     fn new(
+        py: Python<'_>,
         model_dir: String,
         filename: Option<String>,
         execution_provider: Option<String>,
+        engine_cache_path: Option<String>,
+        intra_threads: Option<usize>,
+        inter_threads: Option<usize>,
+        gpu_mem_limit: Option<usize>,
+        optimization_level: Option<String>,
+        cpu_arena: Option<bool>,
+        memory_pattern: Option<bool>,
+        fallback_to_cpu: bool,
     ) -> PyResult<Self> {
-        let providers = execution_providers_from_arg(execution_provider)?;
-        let runtime_params = RuntimeParameters::default().with_execution_providers(providers);
+        let runtime_params = runtime_parameters_from_args(
+            execution_provider,
+            engine_cache_path,
+            intra_threads,
+            gpu_mem_limit,
+        )?;
+        let runtime_params = session_parameters_from_args(
+            runtime_params,
+            optimization_level,
+            cpu_arena,
+            memory_pattern,
+            inter_threads,
+            fallback_to_cpu,
+        )?;
 
         if let Some(path) = filename.as_deref() {
             if path != "onnx/model.onnx" && path != "model.onnx" {
@@ -174,25 +743,53 @@ impl PyFastGliNER2 {
 
         let model = GLiNER2::from_dir(&model_dir, Parameters::default(), runtime_params)
             .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("{:?}", e)))?;
+        warn_cpu_fallback(py, model.get_inner_model())?;
 
         Ok(Self { model })
     }
 
+    /// Same signature as `PyFastGliNER::predict_entities`, but GLiNER2 neither reports progress nor token counts,
+    /// nor supports label descriptions
+    #[pyo3(signature = (texts, labels, threshold=None, flat_ner=None, dup_label=None, multi_label=None, progress=None, with_token_counts=false, force_flat=None, label_descriptions=None))]
+    #[allow(clippy::too_many_arguments)]
     fn predict_entities(
         &self,
         py: Python<'_>,
         texts: Vec<String>,
         labels: Vec<String>,
+        threshold: Option<f32>,
+        flat_ner: Option<bool>,
+        dup_label: Option<bool>,
+        multi_label: Option<bool>,
+        progress: Option<Bound<'_, PyAny>>,
+        with_token_counts: bool,
+        force_flat: Option<bool>,
+        label_descriptions: Option<HashMap<String, String>>,
     ) -> PyResult<Py<PyAny>> {
+        if progress.is_some() || with_token_counts || label_descriptions.is_some() {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "progress, with_token_counts and label_descriptions are not supported by GLiNER2",
+            ));
+        }
         let input = text_input_from_strings(&texts, &labels)?;
+        let params = parameters_with_threshold(self.model.get_parameters(), threshold)?;
+        let params = parameters_with_decoding(params, flat_ner, dup_label, multi_label, force_flat);
 
         let output = py
-            .allow_threads(|| self.model.inference(input))
+            .allow_threads(|| self.model.inference_with_params(input, &params))
             .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("{:?}", e)))?;
 
         output.to_py(py)
     }
 
+    /// Runs a tiny dummy inference to force the runtime to allocate and warm up its session up front
+    fn warmup(&self, py: Python<'_>) -> PyResult<()> {
+        let input = warmup_input()?;
+        py.allow_threads(|| self.model.inference(input))
+            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("{:?}", e)))?;
+        Ok(())
+    }
+
     fn classify(&self, text: String, labels: Vec<String>) -> PyResult<Vec<(String, f32)>> {
         let output = self
             .model
@@ -261,63 +858,542 @@ impl PyFastGliNER2 {
         output.to_py(py)
     }
 
+    #[pyo3(signature = (texts, entity_labels, relation_schema, relation_threshold=None))]
     fn extract_relations(
         &self,
         py: Python<'_>,
         texts: Vec<String>,
         entity_labels: Vec<String>,
-        relation_schema_entries: Vec<PyRelationSchemaEntry>,
+        relation_schema: RelationSchemaArg,
+        relation_threshold: Option<f32>,
     ) -> PyResult<Py<PyAny>> {
         let input = text_input_from_strings(&texts, &entity_labels)?;
-        let relation_schema = relation_schema_from_entries(relation_schema_entries);
+        let relation_schema = relation_schema.into_schema(py)?;
+        let params =
+            parameters_with_relation_threshold(self.model.get_parameters(), relation_threshold)?;
+
+        let output = py
+            .allow_threads(|| {
+                self.model
+                    .extract_relations_with_params(input, &relation_schema, &params)
+            })
+            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("{:?}", e)))?;
+
+        output.to_py(py)
+    }
+
+    /// Performs relation extraction only, given the entities of each text as `(start, end, label)` (byte offsets)
+    #[pyo3(signature = (texts, entities, relation_schema, relation_threshold=None))]
+    fn extract_relations_from_entities(
+        &self,
+        py: Python<'_>,
+        texts: Vec<String>,
+        entities: Vec<Vec<(usize, usize, String)>>,
+        relation_schema: RelationSchemaArg,
+        relation_threshold: Option<f32>,
+    ) -> PyResult<Py<PyAny>> {
+        let entities = span_output_from_entities(texts, entities)?;
+        let relation_schema = relation_schema.into_schema(py)?;
+        let params =
+            parameters_with_relation_threshold(self.model.get_parameters(), relation_threshold)?;
 
         let output = py
-            .allow_threads(|| self.model.extract_relations(input, &relation_schema))
+            .allow_threads(|| {
+                self.model
+                    .extract_relations_from_entities(entities, &relation_schema, &params)
+            })
             .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("{:?}", e)))?;
 
         output.to_py(py)
     }
 }
 
+/// Builds the runtime parameters, `intra_threads` defaulting to the `RuntimeParameters` default (0 letting ORT decide)
+fn runtime_parameters_from_args(
+    execution_provider: Option<String>,
+    engine_cache_path: Option<String>,
+    intra_threads: Option<usize>,
+    gpu_mem_limit: Option<usize>,
+) -> PyResult<RuntimeParameters> {
+    let providers =
+        execution_providers_from_arg(execution_provider, engine_cache_path, gpu_mem_limit)?;
+    let runtime_params = RuntimeParameters::default().with_execution_providers(providers);
+    Ok(match intra_threads {
+        Some(threads) => runtime_params.with_threads(threads),
+        None => runtime_params,
+    })
+}
+
+/// Adds the ONNX Runtime session options to the runtime parameters, `optimization_level` being one of
+/// `disable`, `basic`, `extended` or `all` (default), as named by ONNX Runtime
+fn session_parameters_from_args(
+    runtime_params: RuntimeParameters,
+    optimization_level: Option<String>,
+    cpu_arena: Option<bool>,
+    memory_pattern: Option<bool>,
+    inter_threads: Option<usize>,
+    fallback_to_cpu: bool,
+) -> PyResult<SessionParameters> {
+    let mut session_params =
+        SessionParameters::new(runtime_params).with_cpu_fallback(fallback_to_cpu);
+    if let Some(optimization_level) = optimization_level {
+        session_params = session_params.with_optimization_level(
+            match optimization_level.to_lowercase().as_str() {
+                "disable" => OptimizationLevel::Disable,
+                "basic" => OptimizationLevel::Level1,
+                "extended" => OptimizationLevel::Level2,
+                "all" => OptimizationLevel::Level3,
+                other => {
+                    return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                        "invalid optimization_level '{other}': expected 'disable', 'basic', 'extended' or 'all'"
+                    )))
+                }
+            },
+        );
+    }
+    if let Some(enable) = cpu_arena {
+        session_params = session_params.with_cpu_arena(enable);
+    }
+    if let Some(enable) = memory_pattern {
+        session_params = session_params.with_memory_pattern(enable);
+    }
+    if let Some(threads) = inter_threads {
+        session_params = session_params.with_inter_threads(threads);
+    }
+    Ok(session_params)
+}
+
+/// Raises a `RuntimeWarning` if the model was loaded on the CPU because the execution provider failed to
+/// register (see `fallback_to_cpu`)
+fn warn_cpu_fallback(py: Python<'_>, model: &Model) -> PyResult<()> {
+    match model.cpu_fallback() {
+        Some(error) => PyErr::warn_bound(
+            py,
+            py.get_type_bound::<pyo3::exceptions::PyRuntimeWarning>()
+                .as_any(),
+            &format!("Execution provider failed to initialize, falling back to CPU: {error}"),
+            1,
+        ),
+        None => Ok(()),
+    }
+}
+
+/// Returns the execution providers compiled in this build, i.e. the ones that can be selected
+/// with `execution_provider` (`cpu` being always available)
+#[pyfunction]
+pub fn available_providers() -> Vec<&'static str> {
+    let mut providers = vec!["cpu"];
+    if cfg!(feature = "cuda") {
+        providers.push("cuda");
+    }
+    if cfg!(feature = "tensorrt") {
+        providers.push("tensorrt");
+    }
+    if cfg!(feature = "directml") {
+        providers.push("directml");
+    }
+    if cfg!(feature = "coreml") {
+        providers.push("coreml");
+    }
+    if cfg!(feature = "openvino") {
+        providers.push("openvino");
+    }
+    providers
+}
+
+/// Checks that a model directory holds a valid `gliner_config.json`, tokenizer and ONNX model,
+/// without loading the model. Returns a dict with `valid` and, for each component, its `path`,
+/// `status` (`present`, `missing` or `invalid`) and `error`.
+#[pyfunction]
+#[pyo3(signature = (model_dir, tokenizer_path=None, onnx_model_path=None, config_path=None))]
+pub fn validate_model_dir(
+    py: Python<'_>,
+    model_dir: PathBuf,
+    tokenizer_path: Option<String>,
+    onnx_model_path: Option<String>,
+    config_path: Option<String>,
+) -> PyResult<Py<PyAny>> {
+    let report = py.allow_threads(|| {
+        GLiNER::validate_dir_with(
+            &model_dir,
+            tokenizer_path.as_deref(),
+            onnx_model_path.as_deref(),
+            config_path.as_deref(),
+        )
+    });
+    let result = report.to_py(py)?;
+    result.bind(py).set_item("valid", report.is_valid())?;
+    Ok(result)
+}
+
+/// Returns the path of the ONNX model within a model directory, given relatively to it or searched in the
+/// common locations if not given (see `GLiNER::resolve_onnx_model_path`). Raises `FileNotFoundError` if missing.
+#[pyfunction]
+#[pyo3(signature = (model_dir, onnx_model_path=None))]
+pub fn resolve_onnx_model_path(
+    model_dir: PathBuf,
+    onnx_model_path: Option<String>,
+) -> PyResult<PathBuf> {
+    GLiNER::resolve_onnx_model_path(&model_dir, onnx_model_path.as_deref())
+        .map_err(|e| pyo3::exceptions::PyFileNotFoundError::new_err(e.to_string()))
+}
+
+/// Builds the execution provider to register, failing on registration errors (`ort` ignores them by default)
+/// so that they are raised, or fall back to the CPU (see `session_parameters_from_args`)
 fn execution_providers_from_arg(
     execution_provider: Option<String>,
+    engine_cache_path: Option<String>,
+    gpu_mem_limit: Option<usize>,
 ) -> PyResult<Vec<ExecutionProviderDispatch>> {
-    match execution_provider.as_deref() {
-        Some("cuda") => {
+    let Some(execution_provider) = execution_provider.as_deref() else {
+        return match (engine_cache_path, gpu_mem_limit) {
+            (Some(_), _) => Err(engine_cache_path_error()),
+            (_, Some(_)) => Err(gpu_mem_limit_error()),
+            (None, None) => Ok(vec![]),
+        };
+    };
+
+    // OpenVINO takes a device type (e.g. 'openvino:GPU') rather than a device index
+    if let Some(device_type) = parse_openvino_device_type(execution_provider)? {
+        if engine_cache_path.is_some() {
+            return Err(engine_cache_path_error());
+        }
+        if gpu_mem_limit.is_some() {
+            return Err(gpu_mem_limit_error());
+        }
+        return openvino_execution_provider(device_type);
+    }
+
+    let (provider, device_id) = parse_execution_provider(execution_provider)?;
+    if engine_cache_path.is_some() && provider != "tensorrt" {
+        return Err(engine_cache_path_error());
+    }
+    if gpu_mem_limit.is_some() && provider != "cuda" {
+        return Err(gpu_mem_limit_error());
+    }
+
+    match provider {
+        "cuda" => {
             #[cfg(feature = "cuda")]
             {
-                Ok(vec![CUDAExecutionProvider::default().build()])
+                let cuda = CUDAExecutionProvider::default();
+                let cuda = match device_id {
+                    Some(device_id) => cuda.with_device_id(device_id),
+                    None => cuda,
+                };
+                let cuda = match gpu_mem_limit {
+                    Some(limit) => cuda.with_memory_limit(limit),
+                    None => cuda,
+                };
+                Ok(vec![cuda.build().error_on_failure()])
             }
             #[cfg(not(feature = "cuda"))]
             {
+                let _ = (device_id, gpu_mem_limit);
                 Err(pyo3::exceptions::PyRuntimeError::new_err(
                     "CUDA execution provider requested but 'cuda' feature is not enabled",
                 ))
             }
         }
-        Some("cpu") => Ok(vec![CPUExecutionProvider::default().build()]),
-        None => Ok(vec![]),
-        Some(other) => Err(pyo3::exceptions::PyValueError::new_err(format!(
-            "Unsupported execution provider: '{}'. Use 'cpu' or 'cuda'.",
+        "tensorrt" => {
+            #[cfg(feature = "tensorrt")]
+            {
+                let tensorrt = TensorRTExecutionProvider::default();
+                let tensorrt = match device_id {
+                    Some(device_id) => tensorrt.with_device_id(device_id),
+                    None => tensorrt,
+                };
+                let tensorrt = match engine_cache_path {
+                    Some(path) => tensorrt
+                        .with_engine_cache(true)
+                        .with_engine_cache_path(path),
+                    None => tensorrt,
+                };
+                Ok(vec![tensorrt.build().error_on_failure()])
+            }
+            #[cfg(not(feature = "tensorrt"))]
+            {
+                let _ = (device_id, engine_cache_path);
+                Err(pyo3::exceptions::PyRuntimeError::new_err(
+                    "TensorRT execution provider requested but 'tensorrt' feature is not enabled",
+                ))
+            }
+        }
+        "directml" | "dml" => {
+            #[cfg(feature = "directml")]
+            {
+                let directml = DirectMLExecutionProvider::default();
+                let directml = match device_id {
+                    Some(device_id) => directml.with_device_id(device_id),
+                    None => directml,
+                };
+                Ok(vec![directml.build().error_on_failure()])
+            }
+            #[cfg(not(feature = "directml"))]
+            {
+                let _ = device_id;
+                Err(pyo3::exceptions::PyRuntimeError::new_err(
+                    "DirectML execution provider requested but 'directml' feature is not enabled",
+                ))
+            }
+        }
+        "coreml" if device_id.is_some() => Err(pyo3::exceptions::PyValueError::new_err(
+            "The 'coreml' execution provider does not accept a device index.",
+        )),
+        "coreml" => {
+            #[cfg(feature = "coreml")]
+            {
+                Ok(vec![CoreMLExecutionProvider::default().build().error_on_failure()])
+            }
+            #[cfg(not(feature = "coreml"))]
+            {
+                Err(pyo3::exceptions::PyRuntimeError::new_err(
+                    "CoreML execution provider requested but 'coreml' feature is not enabled",
+                ))
+            }
+        }
+        "cpu" if device_id.is_some() => Err(pyo3::exceptions::PyValueError::new_err(
+            "The 'cpu' execution provider does not accept a device index.",
+        )),
+        "cpu" => Ok(vec![CPUExecutionProvider::default().build().error_on_failure()]),
+        other => Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Unsupported execution provider: '{}'. Use 'cpu', 'cuda', 'tensorrt', 'directml' (or 'dml'), 'coreml' or 'openvino' (optionally '<provider>:<device_id>' for CUDA, TensorRT and DirectML, or 'openvino:<device_type>', e.g. 'openvino:GPU').",
             other
         ))),
     }
 }
 
+/// Returns `Some(device_type)` if OpenVINO is requested (`None` standing for the default device)
+fn parse_openvino_device_type(execution_provider: &str) -> PyResult<Option<Option<&str>>> {
+    match execution_provider.split_once(':') {
+        None if execution_provider == "openvino" => Ok(Some(None)),
+        Some(("openvino", "")) => Err(pyo3::exceptions::PyValueError::new_err(
+            "Missing device type in execution provider 'openvino:', e.g. 'openvino:CPU' or 'openvino:GPU'.",
+        )),
+        Some(("openvino", device_type)) => Ok(Some(Some(device_type))),
+        _ => Ok(None),
+    }
+}
+
+fn openvino_execution_provider(
+    device_type: Option<&str>,
+) -> PyResult<Vec<ExecutionProviderDispatch>> {
+    #[cfg(feature = "openvino")]
+    {
+        let openvino = OpenVINOExecutionProvider::default();
+        let openvino = match device_type {
+            Some(device_type) => openvino.with_device_type(device_type),
+            None => openvino,
+        };
+        Ok(vec![openvino.build().error_on_failure()])
+    }
+    #[cfg(not(feature = "openvino"))]
+    {
+        let _ = device_type;
+        Err(pyo3::exceptions::PyRuntimeError::new_err(
+            "OpenVINO execution provider requested but 'openvino' feature is not enabled",
+        ))
+    }
+}
+
+fn engine_cache_path_error() -> PyErr {
+    pyo3::exceptions::PyValueError::new_err(
+        "engine_cache_path is only supported with the 'tensorrt' execution provider",
+    )
+}
+
+fn gpu_mem_limit_error() -> PyErr {
+    pyo3::exceptions::PyValueError::new_err(
+        "gpu_mem_limit is only supported with the 'cuda' execution provider",
+    )
+}
+
+/// Splits an execution provider argument such as `cuda:1` into its name and optional device index
+fn parse_execution_provider(execution_provider: &str) -> PyResult<(&str, Option<i32>)> {
+    match execution_provider.split_once(':') {
+        None => Ok((execution_provider, None)),
+        Some((provider, device_id)) => match device_id.parse::<i32>() {
+            Ok(device_id) if device_id >= 0 => Ok((provider, Some(device_id))),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Invalid device index '{}' in execution provider '{}'. Expected a non-negative integer, e.g. 'cuda:0'.",
+                device_id, execution_provider
+            ))),
+        },
+    }
+}
+
+fn parameters_with_lengths(
+    params: &Parameters,
+    max_length: Option<usize>,
+    max_width: Option<usize>,
+) -> PyResult<Parameters> {
+    let mut params = params.clone();
+    if let Some(max_length) = max_length {
+        params = params.with_max_length(Some(max_length));
+    }
+    if let Some(max_width) = max_width {
+        params = params.with_max_width(max_width);
+    }
+    params
+        .validate()
+        .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+    Ok(params)
+}
+
+fn parameters_with_batch_size(
+    params: Parameters,
+    batch_size: Option<usize>,
+) -> PyResult<Parameters> {
+    match batch_size {
+        Some(0) => Err(pyo3::exceptions::PyValueError::new_err(
+            "batch_size must be a positive integer",
+        )),
+        Some(batch_size) => Ok(params.with_batch_size(Some(batch_size))),
+        None => Ok(params),
+    }
+}
+
+fn parameters_with_threshold(params: &Parameters, threshold: Option<f32>) -> PyResult<Parameters> {
+    match threshold {
+        Some(threshold) if !(0.0..=1.0).contains(&threshold) => {
+            Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "threshold must be within [0.0, 1.0], got {}",
+                threshold
+            )))
+        }
+        Some(threshold) => Ok(params.clone().with_threshold(threshold)),
+        None => Ok(params.clone()),
+    }
+}
+
+fn parameters_with_relation_threshold(
+    params: &Parameters,
+    relation_threshold: Option<f32>,
+) -> PyResult<Parameters> {
+    match relation_threshold {
+        Some(threshold) if !(0.0..=1.0).contains(&threshold) => {
+            Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "relation_threshold must be within [0.0, 1.0], got {}",
+                threshold
+            )))
+        }
+        Some(threshold) => Ok(params.clone().with_relation_threshold(Some(threshold))),
+        None => Ok(params.clone()),
+    }
+}
+
+fn parameters_with_decoding(
+    mut params: Parameters,
+    flat_ner: Option<bool>,
+    dup_label: Option<bool>,
+    multi_label: Option<bool>,
+    force_flat: Option<bool>,
+) -> Parameters {
+    if let Some(flat_ner) = flat_ner {
+        params = params.with_flat_ner(flat_ner);
+    }
+    if let Some(dup_label) = dup_label {
+        params = params.with_dup_label(dup_label);
+    }
+    if let Some(multi_label) = multi_label {
+        params = params.with_multi_label(multi_label);
+    }
+    if let Some(force_flat) = force_flat {
+        params = params.with_force_flat(force_flat);
+    }
+    params
+}
+
+fn parameters_with_label_descriptions(
+    params: Parameters,
+    label_descriptions: Option<HashMap<String, String>>,
+) -> PyResult<Parameters> {
+    let Some(label_descriptions) = label_descriptions else {
+        return Ok(params);
+    };
+    let params = params.with_label_descriptions(Some(label_descriptions));
+    params
+        .validate()
+        .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+    Ok(params)
+}
+
 fn text_input_from_strings(texts: &[String], labels: &[String]) -> PyResult<TextInput> {
     let texts_ref: Vec<&str> = texts.iter().map(|s| s.as_str()).collect();
     let labels_ref: Vec<&str> = labels.iter().map(|s| s.as_str()).collect();
 
     TextInput::from_str(&texts_ref, &labels_ref)
-        .map_err(|e| pyo3::exceptions::PyValueError::new_err(format!("{:?}", e)))
+        .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))
+}
+
+/// Default parameters honoring the hyperparameters recommended by the model configuration
+fn parameters_from_config(config: &str) -> PyResult<Parameters> {
+    Parameters::from_config_json(config)
+        .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))
+}
+
+fn span_output_from_entities(
+    texts: Vec<String>,
+    entities: Vec<Vec<(usize, usize, String)>>,
+) -> PyResult<SpanOutput> {
+    SpanOutput::from_entities(texts, entities)
+        .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))
+}
+
+fn check_labels_per_text(texts: &[String], labels: &[Vec<String>]) -> PyResult<()> {
+    if texts.is_empty() || texts.len() != labels.len() {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "labels must contain one list per text (got {} texts and {} lists)",
+            texts.len(),
+            labels.len()
+        )));
+    }
+    if labels.iter().any(|labels| labels.is_empty()) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "labels must not contain any empty list",
+        ));
+    }
+    Ok(())
+}
+
+fn warmup_input() -> PyResult<TextInput> {
+    text_input_from_strings(&["Warm up".to_string()], &["entity".to_string()])
+}
+
+fn relation_schema_entries(schema: &RelationSchema) -> Vec<PyRelationSchemaEntry> {
+    let sorted_labels = |labels: Option<&std::collections::HashSet<String>>| {
+        let mut labels: Vec<String> = labels.into_iter().flatten().cloned().collect();
+        labels.sort();
+        labels
+    };
+    let mut entries: Vec<PyRelationSchemaEntry> = schema
+        .iter()
+        .map(|(relation, spec)| PyRelationSchemaEntry {
+            relation: relation.to_string(),
+            subject_labels: sorted_labels(spec.allowed_subjects()),
+            object_labels: sorted_labels(spec.allowed_objects()),
+            symmetric: spec.is_symmetric(),
+            reflexive: spec.is_reflexive(),
+        })
+        .collect();
+    entries.sort_by(|a, b| a.relation.cmp(&b.relation));
+    entries
 }
 
-fn relation_schema_from_entries(entries: Vec<PyRelationSchemaEntry>) -> RelationSchema {
+fn relation_schema_from_entries(entries: Vec<PyRelationSchemaEntry>) -> PyResult<RelationSchema> {
     let mut relation_schema = RelationSchema::new();
     for entry in entries {
         let subj: Vec<&str> = entry.subject_labels.iter().map(|s| s.as_str()).collect();
         let obj: Vec<&str> = entry.object_labels.iter().map(|s| s.as_str()).collect();
-        relation_schema.push_with_allowed_labels(&entry.relation, &subj, &obj);
+        relation_schema.push_with_spec(
+            &entry.relation,
+            RelationSpec::new(&subj, &obj)
+                .with_symmetric(entry.symmetric)
+                .with_reflexive(entry.reflexive),
+        );
     }
     relation_schema
+        .validate()
+        .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+    Ok(relation_schema)
 }
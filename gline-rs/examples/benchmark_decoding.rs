@@ -0,0 +1,64 @@
+//! Measures span decoding time (span mode) on a synthetic batch, without requiring any model.
+//!
+//! Run with and without the `rayon` feature to compare sequential and parallel decoding:
+//! ```text
+//! $ cargo run --release --example benchmark-decoding
+//! $ cargo run --release --example benchmark-decoding --features=rayon
+//! ```
+
+use gliner::model::output::decoded::span::TensorsToDecoded;
+use gliner::model::pipeline::context::EntityContext;
+use gliner::text::token::Token;
+use gliner::util::result::Result;
+
+fn main() -> Result<()> {
+    const BATCH_SIZE: usize = 64;
+    const NUM_WORDS: usize = 384;
+    const MAX_WIDTH: usize = 12;
+    const NUM_CLASSES: usize = 8;
+    const ITERATIONS: usize = 20;
+
+    println!("Preparing synthetic data...");
+    let text = (0..NUM_WORDS)
+        .map(|i| format!("w{i:03}"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let tokens: Vec<Token> = (0..NUM_WORDS)
+        .map(|i| Token::new(i * 5, i * 5 + 4, &text[i * 5..i * 5 + 4]))
+        .collect();
+    let context = EntityContext {
+        texts: vec![text.clone(); BATCH_SIZE],
+        tokens: vec![tokens; BATCH_SIZE],
+        entities: (0..NUM_CLASSES).map(|i| format!("class{i}")).collect(),
+        sequence_ids: (0..BATCH_SIZE).collect(),
+        num_words: NUM_WORDS,
+    };
+    // pseudo-random logits, mostly below threshold as with real outputs
+    let logits = ndarray::Array4::from_shape_fn(
+        (BATCH_SIZE, NUM_WORDS, MAX_WIDTH, NUM_CLASSES),
+        |(s, w, m, c)| {
+            ((s * 7919 + w * 104_729 + m * 1_299_709 + c * 15_485_863) % 1000) as f32 / 100.0 - 9.9
+        },
+    );
+
+    let decoder = TensorsToDecoded::new(0.5, MAX_WIDTH);
+
+    println!("Decoding...");
+    let start = std::time::Instant::now();
+    let mut num_spans = 0;
+    for _ in 0..ITERATIONS {
+        let spans = decoder.decode_logits(logits.view(), &context)?;
+        num_spans = spans.iter().map(Vec::len).sum();
+    }
+    let elapsed = start.elapsed();
+
+    println!(
+        "Decoding {} sequences took {:.2} ms on average ({} spans above threshold, parallel: {})",
+        BATCH_SIZE,
+        elapsed.as_secs_f64() * 1000.0 / ITERATIONS as f64,
+        num_spans,
+        cfg!(feature = "rayon"),
+    );
+
+    Ok(())
+}
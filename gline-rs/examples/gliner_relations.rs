@@ -2,10 +2,9 @@ use composable::*;
 use gliner::model::input::{relation::schema::RelationSchema, text::TextInput};
 use gliner::model::params::Parameters;
 use gliner::model::pipeline::{relation::RelationPipeline, token::TokenPipeline};
+use gliner::model::session::Model;
 use gliner::util::result::Result;
-use orp::model::Model;
 use orp::params::RuntimeParameters;
-use orp::pipeline::*;
 
 /// Sample usage of the public API for Relation Extraction
 ///
@@ -45,12 +44,15 @@ fn main() -> Result<()> {
     let model = Model::new(MODEL_PATH, runtime_params)?;
 
     // Relation Extraction needs Named Entity Recognition to be applied first.
-    // Here we combine the two pipelines: one for NER, and one for RE.
+    // Here we combine the two pipelines: one for NER, and one for RE (which borrows the NER one,
+    // so that the tokenizer is only loaded once).
     // For testing purposes we also insert printing functions.
+    let entity_pipeline = TokenPipeline::new(TOKENIZER_PATH)?;
+    let relation_pipeline = RelationPipeline::borrowed(&entity_pipeline, &relation_schema);
     let pipeline = composed![
-        TokenPipeline::new(TOKENIZER_PATH)?.to_composable(&model, &params),
+        model.to_composable(&entity_pipeline, &params),
         Print::new(Some("Entities:\n"), None),
-        RelationPipeline::default(TOKENIZER_PATH, &relation_schema)?.to_composable(&model, &params),
+        model.to_composable(&relation_pipeline, &params),
         Print::new(Some("Relations:\n"), None)
     ];
 
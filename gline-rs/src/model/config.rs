@@ -3,13 +3,17 @@ use std::path::Path;
 
 use serde::Deserialize;
 
+use crate::model::input::prompt::PromptConfig;
+use crate::model::input::tensors::InputNames;
+use crate::model::params::Parameters;
 use crate::util::result::Result;
 
 const DEFAULT_MAX_WIDTH: usize = 12;
 
-#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum ConfigMode {
+    #[default]
     Span,
     Token,
 }
@@ -20,12 +24,49 @@ pub struct ModelConfig {
     pub mode: ConfigMode,
     #[serde(default = "default_max_width")]
     pub max_width: usize,
+    /// Recommended maximum sequence length, in words
+    #[serde(default, alias = "max_length")]
+    pub max_len: Option<usize>,
+    /// Recommended probability threshold
+    #[serde(default)]
+    pub threshold: Option<f32>,
+    /// Size of the model vocabulary (`-1` if unknown)
+    #[serde(default)]
+    vocab_size: Option<i64>,
+    #[serde(default)]
+    encoder_config: Option<EncoderConfig>,
+    /// Token preceding each entity label in the prompts, if the model does not use the usual one
+    #[serde(default)]
+    pub ent_token: Option<String>,
+    /// Token separating the entity labels from the text in the prompts, if the model does not use the usual one
+    #[serde(default)]
+    pub sep_token: Option<String>,
+    /// Entity labels declared by the model (typically the ones it was fine-tuned on), if any
+    #[serde(default, alias = "entity_labels")]
+    pub labels: Option<Vec<String>>,
+    /// Names of the input tensors, for models whose export does not use the usual ones (see `input_names`)
+    #[serde(default)]
+    pub input_names: InputNamesConfig,
 }
 
-impl Default for ConfigMode {
-    fn default() -> Self {
-        Self::Span
-    }
+/// Overridden names of the input tensors, missing ones keeping their usual value (see `InputNames`)
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct InputNamesConfig {
+    #[serde(default)]
+    pub input_ids: Option<String>,
+    #[serde(default)]
+    pub attention_mask: Option<String>,
+    #[serde(default)]
+    pub words_mask: Option<String>,
+    #[serde(default)]
+    pub text_lengths: Option<String>,
+}
+
+/// Subset of the underlying encoder configuration
+#[derive(Debug, Clone, Default, Deserialize)]
+struct EncoderConfig {
+    #[serde(default)]
+    vocab_size: Option<i64>,
 }
 
 impl Default for ModelConfig {
@@ -33,6 +74,14 @@ impl Default for ModelConfig {
         Self {
             mode: ConfigMode::default(),
             max_width: default_max_width(),
+            max_len: None,
+            threshold: None,
+            vocab_size: None,
+            encoder_config: None,
+            ent_token: None,
+            sep_token: None,
+            labels: None,
+            input_names: InputNamesConfig::default(),
         }
     }
 }
@@ -43,8 +92,175 @@ impl ModelConfig {
         let config = serde_json::from_str(&raw)?;
         Ok(config)
     }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let config = serde_json::from_slice(bytes)?;
+        Ok(config)
+    }
+
+    /// Adapts the given parameters to the model: sets the maximum span width, and the special tokens of
+    /// the prompts if the configuration defines them (see `prompt_config`).
+    pub fn apply(&self, parameters: Parameters) -> Result<Parameters> {
+        let mut parameters = parameters.with_max_width(self.max_width);
+        if self.ent_token.is_some() || self.sep_token.is_some() {
+            parameters = parameters.with_prompt(self.prompt_config()?);
+        }
+        Ok(parameters)
+    }
+
+    /// Special tokens of the prompts: the ones of the configuration (`ent_token` and `sep_token`) if present,
+    /// the usual ones otherwise
+    pub fn prompt_config(&self) -> Result<PromptConfig> {
+        let mut config = PromptConfig::default();
+        for (token, value) in [
+            (&mut config.entity_token, &self.ent_token),
+            (&mut config.sep_token, &self.sep_token),
+        ] {
+            match value {
+                Some(value) if value.trim().is_empty() => {
+                    return Err("invalid model configuration: empty special token".into())
+                }
+                Some(value) => value.clone_into(token),
+                None => {}
+            }
+        }
+        Ok(config)
+    }
+
+    /// Names of the input tensors: the ones of the configuration (`input_names`) if present, the usual ones otherwise
+    pub fn input_names(&self) -> Result<InputNames> {
+        let mut names = InputNames::default();
+        for (name, value) in [
+            (&mut names.input_ids, &self.input_names.input_ids),
+            (&mut names.attention_mask, &self.input_names.attention_mask),
+            (&mut names.words_mask, &self.input_names.words_mask),
+            (&mut names.text_lengths, &self.input_names.text_lengths),
+        ] {
+            match value {
+                Some(value) if value.trim().is_empty() => {
+                    return Err("invalid model configuration: empty input tensor name".into())
+                }
+                Some(value) => *name = value.as_str().into(),
+                _ => {}
+            }
+        }
+        Ok(names)
+    }
+
+    /// Default parameters honoring the hyperparameters recommended by the model (`max_width`, `max_len` and
+    /// `threshold`, when present), other parameters keeping their usual defaults.
+    pub fn recommended_parameters(&self) -> Result<Parameters> {
+        let mut parameters = Parameters::default().with_max_width(self.max_width);
+        if let Some(max_len) = self.max_len {
+            parameters = parameters.with_max_length(Some(max_len));
+        }
+        match self.threshold {
+            Some(threshold) if !(0.0..=1.0).contains(&threshold) => Err(format!(
+                "invalid model configuration: threshold must be within [0.0, 1.0], got {threshold}"
+            )
+            .into()),
+            Some(threshold) => Ok(parameters.with_threshold(threshold)),
+            None => Ok(parameters),
+        }
+    }
+
+    /// Size of the model vocabulary (i.e. number of rows of the embeddings), if known
+    pub fn vocab_size(&self) -> Option<usize> {
+        let encoder_vocab_size = self.encoder_config.as_ref().and_then(|c| c.vocab_size);
+        [self.vocab_size, encoder_vocab_size]
+            .into_iter()
+            .flatten()
+            .find(|size| *size > 0)
+            .map(|size| size as usize)
+    }
+
+    /// Checks that the given tokenizer vocabulary fits into the model embeddings.
+    /// Otherwise, inference would fail with an opaque error as soon as an out-of-range token shows up.
+    pub fn check_vocab_size(&self, tokenizer_vocab_size: usize) -> Result<()> {
+        match self.vocab_size() {
+            Some(model_vocab_size) if tokenizer_vocab_size > model_vocab_size => Err(format!(
+                "tokenizer and model mismatch: the tokenizer vocabulary has {tokenizer_vocab_size} tokens, \
+                but the model only knows {model_vocab_size} (make sure that `tokenizer.json` comes with the model)"
+            )
+            .into()),
+            _ => Ok(()),
+        }
+    }
 }
 
 fn default_max_width() -> usize {
     DEFAULT_MAX_WIDTH
 }
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vocab_size() -> Result<()> {
+        let config = ModelConfig::from_bytes(
+            br#"{"vocab_size": -1, "encoder_config": {"vocab_size": 100}}"#,
+        )?;
+        assert_eq!(config.vocab_size(), Some(100));
+        assert!(config.check_vocab_size(100).is_ok());
+        assert!(config.check_vocab_size(101).is_err());
+        let config = ModelConfig::from_bytes(b"{}")?;
+        assert_eq!(config.vocab_size(), None);
+        assert!(config.check_vocab_size(101).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_recommended_parameters() -> Result<()> {
+        let params = Parameters::from_config_json(
+            r#"{"span_mode": "markerV0", "max_width": 8, "max_len": 384, "threshold": 0.4}"#,
+        )?;
+        assert_eq!(params.max_width, 8);
+        assert_eq!(params.max_length, Some(384));
+        assert_eq!(params.threshold, 0.4);
+        let params = Parameters::from_config_json(r#"{"max_length": 256}"#)?;
+        assert_eq!(params.max_length, Some(256));
+        assert_eq!(params.max_width, DEFAULT_MAX_WIDTH);
+        assert_eq!(params.threshold, Parameters::default().threshold);
+        assert!(Parameters::from_config_json(r#"{"threshold": 2.0}"#).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_prompt_config() -> Result<()> {
+        let config = ModelConfig::from_bytes(br#"{"ent_token": "[E]", "sep_token": "[SEP]"}"#)?;
+        let params = config.apply(Parameters::default())?;
+        assert_eq!(params.prompt, PromptConfig::new("[E]", "[SEP]"));
+        let params =
+            ModelConfig::from_bytes(br#"{"sep_token": "[SEP]"}"#)?.apply(Parameters::default())?;
+        assert_eq!(params.prompt, PromptConfig::new("<<ENT>>", "[SEP]"));
+        let config = ModelConfig::from_bytes(br#"{"ent_token": ""}"#)?;
+        assert!(config.apply(Parameters::default()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_labels() -> Result<()> {
+        let config = ModelConfig::from_bytes(br#"{"labels": ["person", "location"]}"#)?;
+        assert_eq!(
+            config.labels,
+            Some(vec!["person".into(), "location".into()])
+        );
+        let config = ModelConfig::from_bytes(br#"{"entity_labels": ["person"]}"#)?;
+        assert_eq!(config.labels, Some(vec!["person".into()]));
+        assert_eq!(ModelConfig::from_bytes(b"{}")?.labels, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_input_names() -> Result<()> {
+        let config = ModelConfig::from_bytes(br#"{"input_names": {"words_mask": "word_mask"}}"#)?;
+        let names = config.input_names()?;
+        assert_eq!(&*names.words_mask, "word_mask");
+        assert_eq!(names.input_ids, InputNames::default().input_ids);
+        let config = ModelConfig::from_bytes(br#"{"input_names": {"input_ids": " "}}"#)?;
+        assert!(config.input_names().is_err());
+        Ok(())
+    }
+}
@@ -94,7 +94,13 @@ impl OutputsToSpans {
             }
         }
 
-        let output = SpanOutput::new(vec![context.text], context.labels, vec![spans]);
+        let words = context
+            .tokens
+            .iter()
+            .map(|token| (token.start(), token.end()))
+            .collect();
+        let output = SpanOutput::new(vec![context.text], context.labels, vec![spans])
+            .with_word_offsets(vec![words]);
         let output = SpanSort::default().apply(output)?;
         GreedySearch::new(self.flat_ner, self.dup_label, self.multi_label).apply(output)
     }
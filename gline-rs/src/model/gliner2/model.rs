@@ -3,18 +3,17 @@ use std::path::{Path, PathBuf};
 
 use composable::*;
 use ndarray::{Array1, Array2, Array3};
-use orp::model::Model;
-use orp::params::RuntimeParameters;
 use orp::pipeline::Pipeline;
 use ort::session::SessionInputs;
 
 use crate::model::input::relation::schema::RelationSchema;
 use crate::model::input::relation::RelationInput;
 use crate::model::input::text::TextInput;
-use crate::model::output::decoded::SpanOutput;
+use crate::model::output::decoded::{flat::ForceFlat, SpanOutput};
 use crate::model::output::relation::RelationOutput;
 use crate::model::params::Parameters;
 use crate::model::pipeline::context::RelationContext;
+use crate::model::session::{Model, SessionParameters};
 use crate::text::splitter::{RegexSplitter, Splitter};
 use crate::text::token::Token;
 use crate::util::result::Result;
@@ -50,7 +49,7 @@ impl GLiNER2 {
     pub fn from_dir<P: AsRef<Path>>(
         model_dir: P,
         parameters: Parameters,
-        runtime_parameters: RuntimeParameters,
+        runtime_parameters: impl Into<SessionParameters>,
     ) -> Result<Self> {
         let model_dir = model_dir.as_ref();
         let tokenizer_path = model_dir.join("tokenizer.json");
@@ -80,13 +79,33 @@ impl GLiNER2 {
         &self.model
     }
 
+    pub fn get_parameters(&self) -> &Parameters {
+        &self.params
+    }
+
     pub fn inference(&self, input: TextInput) -> Result<SpanOutput> {
-        let TextInput { texts, entities } = input;
+        self.inference_with_params(input, &self.params)
+    }
+
+    /// Same as `inference`, but overrides the parameters given at construction time for this call only
+    pub fn inference_with_params(
+        &self,
+        input: TextInput,
+        params: &Parameters,
+    ) -> Result<SpanOutput> {
+        if input.is_pretokenized() {
+            return Err("pre-tokenized input is not supported by GLiNER2".into());
+        }
+        let TextInput {
+            texts, entities, ..
+        } = input;
         let mut spans = Vec::with_capacity(texts.len());
+        let mut word_offsets = Vec::with_capacity(texts.len());
 
         for (sequence_index, text) in texts.iter().enumerate() {
             if text.trim().is_empty() {
                 spans.push(Vec::new());
+                word_offsets.push(Vec::new());
                 continue;
             }
 
@@ -98,13 +117,14 @@ impl GLiNER2 {
                     task: SequenceTask::Entities,
                 },
                 &self.ner_pipeline,
-                &self.params,
+                params,
             )?;
 
             spans.push(output.spans.into_iter().next().unwrap_or_default());
+            word_offsets.push(output.word_offsets.into_iter().next().unwrap_or_default());
         }
 
-        Ok(SpanOutput::new(texts, entities, spans))
+        Ok(SpanOutput::new(texts, entities, spans).with_word_offsets(word_offsets))
     }
 
     /// Runs schema-driven GLiNER2 classification using the monolithic `span_scores` export.
@@ -170,24 +190,54 @@ impl GLiNER2 {
         input: TextInput,
         schema: &RelationSchema,
     ) -> Result<RelationOutput> {
-        let entity_spans = self.inference(input)?;
+        self.extract_relations_with_params(input, schema, &self.params)
+    }
+
+    /// Same as `extract_relations`, but overrides the parameters given at construction time for this call only
+    pub fn extract_relations_with_params(
+        &self,
+        input: TextInput,
+        schema: &RelationSchema,
+        params: &Parameters,
+    ) -> Result<RelationOutput> {
+        let entity_spans = self.inference_with_params(input, params)?;
+        self.extract_relations_from_entities(entity_spans, schema, params)
+    }
+
+    /// Performs relation extraction only, given already known entities (see `SpanOutput::from_entities`)
+    pub fn extract_relations_from_entities(
+        &self,
+        entity_spans: SpanOutput,
+        schema: &RelationSchema,
+        params: &Parameters,
+    ) -> Result<RelationOutput> {
         let relation_input = RelationInput::from_spans(entity_spans, schema);
         let RelationInput {
             prompts,
             labels,
             entity_labels,
             entity_offsets,
+            entity_probabilities,
+            entity_spans,
+            text_offset,
         } = relation_input;
 
-        let relation_spans = self.inference(TextInput::new(prompts, labels)?)?;
-
-        OutputsToRelations::new(schema).apply((
-            relation_spans,
-            RelationContext {
-                entity_labels,
-                entity_offsets,
-            },
-        ))
+        let relation_spans =
+            self.inference_with_params(TextInput::new(prompts, labels)?, params)?;
+
+        OutputsToRelations::new(schema)
+            .with_threshold(params.relation_threshold)
+            .with_rejected(params.keep_rejected_relations)
+            .apply((
+                relation_spans,
+                RelationContext {
+                    entity_labels,
+                    entity_offsets,
+                    entity_probabilities,
+                    entity_spans,
+                    text_offset,
+                },
+            ))
     }
 
     pub fn create_schema(&self) -> GLiNER2PipelineSchema {
@@ -273,13 +323,16 @@ impl<'a> Pipeline<'a> for GLiNER2NerPipeline {
         &self,
         params: &Self::Parameters,
     ) -> impl orp::pipeline::PostProcessor<'a, Self::Output, Self::Context> {
-        OutputsToSpans::new(
-            params.threshold,
-            params.max_width,
-            params.flat_ner,
-            params.dup_label,
-            params.multi_label,
-        )
+        composed![
+            OutputsToSpans::new(
+                params.threshold,
+                params.max_width,
+                params.flat_ner,
+                params.dup_label,
+                params.multi_label,
+            ),
+            ForceFlat::new(params.force_flat)
+        ]
     }
 
     fn expected_inputs(&self) -> Option<&HashSet<&str>> {
@@ -625,6 +625,7 @@ fn structure_field_name(structure_name: &str, field_name: &str) -> String {
 fn build_relation_context(entities: &[Span]) -> RelationContext {
     let mut entity_labels = HashMap::<String, HashSet<String>>::new();
     let mut entity_offsets = HashMap::<String, (usize, usize)>::new();
+    let mut entity_probabilities = HashMap::<String, f32>::new();
 
     for entity in entities {
         entity_labels
@@ -634,11 +635,18 @@ fn build_relation_context(entities: &[Span]) -> RelationContext {
         entity_offsets
             .entry(entity.text().to_string())
             .or_insert_with(|| entity.offsets());
+        entity_probabilities
+            .entry(entity.text().to_string())
+            .or_insert_with(|| entity.probability());
     }
 
     RelationContext {
         entity_labels,
         entity_offsets,
+        entity_probabilities,
+        // the candidates are built from the text itself (see `build_relation_candidates`)
+        entity_spans: vec![entities.to_vec()],
+        text_offset: 0,
     }
 }
 
@@ -665,7 +673,7 @@ fn build_relation_candidates(
             continue;
         };
 
-        if subject.same_offsets(&object) {
+        if subject.same_offsets(object) {
             continue;
         }
 
@@ -8,16 +8,35 @@ use crate::util::result::Result;
 
 pub struct OutputsToRelations<'a> {
     schema: &'a RelationSchema,
+    threshold: Option<f32>,
+    keep_rejected: bool,
 }
 
 impl<'a> OutputsToRelations<'a> {
     pub fn new(schema: &'a RelationSchema) -> Self {
-        Self { schema }
+        Self {
+            schema,
+            threshold: None,
+            keep_rejected: false,
+        }
+    }
+
+    pub fn with_threshold(mut self, threshold: Option<f32>) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    pub fn with_rejected(mut self, keep_rejected: bool) -> Self {
+        self.keep_rejected = keep_rejected;
+        self
     }
 }
 
 impl Composable<(SpanOutput, RelationContext), RelationOutput> for OutputsToRelations<'_> {
     fn apply(&self, input: (SpanOutput, RelationContext)) -> Result<RelationOutput> {
-        SpanOutputToRelationOutput::new(self.schema).apply(input)
+        SpanOutputToRelationOutput::new(self.schema)
+            .with_threshold(self.threshold)
+            .with_rejected(self.keep_rejected)
+            .apply(input)
     }
 }
@@ -0,0 +1,654 @@
+//! Metadata about ONNX models (versions, input and output tensors), for diagnostic purposes
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufReader, Cursor, ErrorKind, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use ort::session::builder::GraphOptimizationLevel;
+use ort::session::Session;
+use ort::value::ValueType;
+use serde::Serialize;
+
+use crate::util::result::Result;
+
+/// Name, element type and shape of a model input or output
+#[derive(Debug, Clone, Serialize)]
+pub struct TensorInfo {
+    pub name: String,
+    /// Element type (e.g. `f32` or `i64`), or the full value type for non-tensor values
+    pub element_type: String,
+    /// Dimensions, `-1` denoting a dynamic one (empty for non-tensor values)
+    pub shape: Vec<i64>,
+}
+
+/// Describes the actual inputs and outputs of an ONNX model
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelInfo {
+    pub inputs: Vec<TensorInfo>,
+    pub outputs: Vec<TensorInfo>,
+    /// Whether the model is quantized, as detected from the name of its file (e.g. `model_quantized.onnx`)
+    /// or, when loaded from bytes, from the quantization operators of its graph
+    pub quantized: bool,
+}
+
+/// Markers of quantized models, in file names
+const QUANTIZED_FILE_MARKERS: [&str; 3] = ["quantized", "int8", "uint8"];
+
+/// Markers of quantized models, in the operator types of the graph (`QuantizeLinear`, `DynamicQuantizeLinear`,
+/// `MatMulInteger`, `QLinearMatMul`, ...)
+const QUANTIZED_OP_MARKERS: [&[u8]; 4] = [
+    b"QuantizeLinear",
+    b"MatMulInteger",
+    b"ConvInteger",
+    b"QLinear",
+];
+
+impl ModelInfo {
+    /// Reads the metadata of the given ONNX model.
+    ///
+    /// The model is loaded in a dedicated (non-optimized) session, so this is
+    /// meant for diagnostics rather than being called on a hot path.
+    pub fn from_file<P: AsRef<Path>>(model_path: P) -> Result<Self> {
+        let session = Self::builder()?.commit_from_file(&model_path)?;
+        Ok(Self::from_session(&session).with_file_name(model_path.as_ref()))
+    }
+
+    /// Same as `from_file`, given the contents of the ONNX model
+    pub fn from_bytes(model_bytes: &[u8]) -> Result<Self> {
+        let session = Self::builder()?.commit_from_memory(model_bytes)?;
+        Ok(Self::from_session(&session).with_graph(model_bytes))
+    }
+
+    fn builder() -> Result<ort::session::builder::SessionBuilder> {
+        Ok(Session::builder()?
+            .with_intra_threads(1)?
+            .with_optimization_level(GraphOptimizationLevel::Disable)?)
+    }
+
+    /// Reads the metadata of a loaded session (see `session::Model::info`). Since the session does
+    /// not tell whether the model is quantized, see `with_file_name` or `with_graph` for that.
+    pub fn from_session(session: &Session) -> Self {
+        Self {
+            inputs: session
+                .inputs
+                .iter()
+                .map(|input| TensorInfo::new(&input.name, &input.input_type))
+                .collect(),
+            outputs: session
+                .outputs
+                .iter()
+                .map(|output| TensorInfo::new(&output.name, &output.output_type))
+                .collect(),
+            quantized: false,
+        }
+    }
+
+    /// Detects a quantized model from the name of its file
+    pub fn with_file_name(mut self, model_path: &Path) -> Self {
+        let file_name = model_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        self.quantized = QUANTIZED_FILE_MARKERS
+            .iter()
+            .any(|marker| file_name.contains(marker));
+        self
+    }
+
+    /// Detects a quantized model from the operators of its graph, given the contents of the ONNX model
+    pub fn with_graph(mut self, model_bytes: &[u8]) -> Self {
+        self.quantized = QUANTIZED_OP_MARKERS.iter().any(|marker| {
+            model_bytes
+                .windows(marker.len())
+                .any(|window| window == *marker)
+        });
+        self
+    }
+}
+
+impl ModelInfo {
+    /// Reads the names of the inputs and outputs from the graph of the given ONNX model.
+    ///
+    /// Like `OnnxVersions`, this does not load the model (the weights are skipped), so the element
+    /// types and shapes are left empty.
+    pub fn from_graph_file<P: AsRef<Path>>(model_path: P) -> Result<Self> {
+        Ok(Self::read_graph(BufReader::new(File::open(&model_path)?))?
+            .with_file_name(model_path.as_ref()))
+    }
+
+    /// Same as `from_graph_file`, given the contents of the ONNX model
+    pub fn from_graph_bytes(model_bytes: &[u8]) -> Result<Self> {
+        Ok(Self::read_graph(Cursor::new(model_bytes))?.with_graph(model_bytes))
+    }
+
+    /// Checks the tensors of the model against the expected ones: inputs must be exactly the
+    /// expected ones, while outputs must include the expected ones (like `orp` does). The error
+    /// lists the actual tensors of the model.
+    pub fn check(
+        &self,
+        expected_inputs: Option<&HashSet<&str>>,
+        expected_outputs: Option<&HashSet<&str>>,
+    ) -> Result<()> {
+        if let Some(expected) = expected_inputs {
+            let actual: HashSet<&str> = self.input_names().collect();
+            if !actual.eq(expected) {
+                return Self::mismatch("input", expected, &actual);
+            }
+        }
+        if let Some(expected) = expected_outputs {
+            let actual: HashSet<&str> = self.output_names().collect();
+            if !actual.is_superset(expected) {
+                return Self::mismatch("output", expected, &actual);
+            }
+        }
+        Ok(())
+    }
+
+    fn mismatch(kind: &str, expected: &HashSet<&str>, actual: &HashSet<&str>) -> Result<()> {
+        let mut missing: Vec<&str> = expected.difference(actual).copied().collect();
+        let mut actual: Vec<&str> = actual.iter().copied().collect();
+        missing.sort_unstable();
+        actual.sort_unstable();
+        let message = match missing.is_empty() {
+            true => {
+                let mut expected: Vec<&str> = expected.iter().copied().collect();
+                expected.sort_unstable();
+                format!("expected {kind} tensors {expected:?}")
+            }
+            false => format!("missing {kind} tensor(s) {missing:?}"),
+        };
+        Err(format!("unexpected ONNX model: {message} (the model has {actual:?})").into())
+    }
+
+    fn read_graph<R: Read + Seek>(mut reader: R) -> Result<Self> {
+        // see `ModelProto` and `GraphProto` in https://github.com/onnx/onnx/blob/main/onnx/onnx.proto
+        const GRAPH: u64 = 7;
+        const INPUT: u64 = 11;
+        const OUTPUT: u64 = 12;
+
+        while let Some(key) = read_key(&mut reader)? {
+            match (key >> 3, key & 7) {
+                (GRAPH, 2) => {
+                    let end = read_varint(&mut reader)? + reader.stream_position()?;
+                    let mut result = Self {
+                        inputs: Vec::new(),
+                        outputs: Vec::new(),
+                        quantized: false,
+                    };
+                    while reader.stream_position()? < end {
+                        let key = read_varint(&mut reader)?;
+                        match (key >> 3, key & 7) {
+                            (INPUT, 2) => result.inputs.push(TensorInfo::named(&mut reader)?),
+                            (OUTPUT, 2) => result.outputs.push(TensorInfo::named(&mut reader)?),
+                            (_, wire_type) => skip_field(&mut reader, wire_type)?,
+                        }
+                    }
+                    return Ok(result);
+                }
+                (_, wire_type) => skip_field(&mut reader, wire_type)?,
+            }
+        }
+        Err("invalid ONNX model: missing graph".into())
+    }
+
+    /// Names of the inputs of the model
+    pub fn input_names(&self) -> impl Iterator<Item = &str> {
+        self.inputs.iter().map(|input| input.name.as_str())
+    }
+
+    /// Names of the outputs of the model
+    pub fn output_names(&self) -> impl Iterator<Item = &str> {
+        self.outputs.iter().map(|output| output.name.as_str())
+    }
+}
+
+impl TensorInfo {
+    fn new(name: &str, value_type: &ValueType) -> Self {
+        let (element_type, shape) = match value_type {
+            ValueType::Tensor { ty, dimensions, .. } => (ty.to_string(), dimensions.clone()),
+            other => (other.to_string(), Vec::new()),
+        };
+        Self {
+            name: name.to_string(),
+            element_type,
+            shape,
+        }
+    }
+
+    /// Reads the name of a (length-delimited) `ValueInfoProto`, leaving the type empty
+    fn named<R: Read>(reader: &mut R) -> Result<Self> {
+        let mut reader = Cursor::new(read_bytes(reader)?);
+        let mut name = String::new();
+        while let Some(key) = read_key(&mut reader)? {
+            match (key >> 3, key & 7) {
+                (1, 2) => name = read_string(&mut reader)?,
+                (_, wire_type) => skip_field(&mut reader, wire_type)?,
+            }
+        }
+        Ok(Self {
+            name,
+            element_type: String::new(),
+            shape: Vec::new(),
+        })
+    }
+}
+
+impl std::fmt::Display for ModelInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (kind, tensors) in [("input", &self.inputs), ("output", &self.outputs)] {
+            for tensor in tensors {
+                writeln!(
+                    f,
+                    "{:6} | {:20} | {:6} | {:?}",
+                    kind, tensor.name, tensor.element_type, tensor.shape
+                )?;
+            }
+        }
+        if self.quantized {
+            writeln!(f, "(quantized)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Outcome of the validation of a model component (see `ModelDirReport`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ComponentStatus {
+    /// The file exists and could be parsed
+    Present,
+    /// The file does not exist
+    Missing,
+    /// The file exists but could not be parsed, or is inconsistent with the other components
+    Invalid,
+}
+
+/// Validation report of a single model component
+#[derive(Debug, Clone, Serialize)]
+pub struct ComponentReport {
+    /// Name of the component (`config`, `tokenizer` or `onnx_model`)
+    pub component: &'static str,
+    /// Path of the file (the first searched location if it is missing)
+    pub path: PathBuf,
+    pub status: ComponentStatus,
+    /// Reason why the component is invalid (or missing)
+    pub error: Option<String>,
+}
+
+/// Validation report of a model directory, telling which components are present, missing or invalid
+/// (see `GLiNER::validate_dir`)
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelDirReport {
+    pub components: Vec<ComponentReport>,
+}
+
+impl ComponentReport {
+    pub fn new(component: &'static str, path: PathBuf, result: Result<()>) -> Self {
+        let (status, error) = match result {
+            Ok(()) => (ComponentStatus::Present, None),
+            Err(error) => (ComponentStatus::Invalid, Some(error.to_string())),
+        };
+        Self {
+            component,
+            path,
+            status,
+            error,
+        }
+    }
+
+    pub fn missing(component: &'static str, path: PathBuf, error: String) -> Self {
+        Self {
+            component,
+            path,
+            status: ComponentStatus::Missing,
+            error: Some(error),
+        }
+    }
+
+    pub fn is_present(&self) -> bool {
+        self.status == ComponentStatus::Present
+    }
+}
+
+impl ModelDirReport {
+    /// Returns `true` if all components are present and valid
+    pub fn is_valid(&self) -> bool {
+        self.components.iter().all(ComponentReport::is_present)
+    }
+
+    /// Returns the report of the given component, if checked
+    pub fn component(&self, component: &str) -> Option<&ComponentReport> {
+        self.components.iter().find(|c| c.component == component)
+    }
+
+    /// Returns an error listing the problems if some component is missing or invalid
+    pub fn check(&self) -> Result<()> {
+        if self.is_valid() {
+            return Ok(());
+        }
+        let problems: Vec<String> = self
+            .components
+            .iter()
+            .filter(|c| !c.is_present())
+            .map(|c| {
+                format!(
+                    "{}: {}",
+                    c.component,
+                    c.error.as_deref().unwrap_or_default()
+                )
+            })
+            .collect();
+        Err(format!("invalid model directory ({})", problems.join("; ")).into())
+    }
+}
+
+impl std::fmt::Display for ModelDirReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for component in &self.components {
+            write!(
+                f,
+                "{:10} | {:7} | {}",
+                component.component,
+                format!("{:?}", component.status).to_lowercase(),
+                component.path.display()
+            )?;
+            match &component.error {
+                Some(error) => writeln!(f, " ({error})")?,
+                None => writeln!(f)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Oldest opset used by GLiNER ONNX exports
+const MIN_OPSET_VERSION: i64 = 14;
+/// Latest opset known to be supported by the ONNX Runtime version bundled with `ort`
+const MAX_OPSET_VERSION: i64 = 21;
+/// Latest IR version known to be supported by the ONNX Runtime version bundled with `ort`
+const MAX_IR_VERSION: i64 = 10;
+
+/// IR and (default domain) opset versions of an ONNX model
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OnnxVersions {
+    pub ir_version: i64,
+    pub opset_version: Option<i64>,
+}
+
+impl OnnxVersions {
+    /// Reads the versions from the header of the given ONNX model.
+    ///
+    /// Unlike `ModelInfo`, this does not load the model: only the top-level fields of the
+    /// protobuf message are read, the graph itself being skipped.
+    pub fn from_file<P: AsRef<Path>>(model_path: P) -> Result<Self> {
+        Self::read(BufReader::new(File::open(model_path)?))
+    }
+
+    /// Same as `from_file`, given the contents of the ONNX model
+    pub fn from_bytes(model_bytes: &[u8]) -> Result<Self> {
+        Self::read(Cursor::new(model_bytes))
+    }
+
+    /// Returns an error naming the found and required versions if the opset is too old for GLiNER graphs.
+    ///
+    /// Versions more recent than the ones supported by the bundled ONNX Runtime only raise a warning, since
+    /// they may be supported by the ONNX Runtime library actually loaded (see the `load-dynamic` feature).
+    pub fn check(&self) -> Result<()> {
+        if self.ir_version > MAX_IR_VERSION {
+            log::warn!(
+                "ONNX model IR version is {}, but the bundled ONNX Runtime supports at most {MAX_IR_VERSION}",
+                self.ir_version
+            );
+        }
+        match self.opset_version {
+            Some(opset) if opset < MIN_OPSET_VERSION => Err(format!(
+                "unsupported ONNX model: opset version is {opset}, but at least {MIN_OPSET_VERSION} is required (try exporting the model with another opset)"
+            )
+            .into()),
+            Some(opset) if opset > MAX_OPSET_VERSION => {
+                log::warn!(
+                    "ONNX model opset version is {opset}, but the bundled ONNX Runtime supports at most {MAX_OPSET_VERSION}"
+                );
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn read<R: Read + Seek>(mut reader: R) -> Result<Self> {
+        // see `ModelProto` in https://github.com/onnx/onnx/blob/main/onnx/onnx.proto
+        const IR_VERSION: u64 = 1;
+        const OPSET_IMPORT: u64 = 8;
+
+        let mut ir_version = None;
+        let mut opset_version = None;
+        while let Some(key) = read_key(&mut reader)? {
+            match (key >> 3, key & 7) {
+                (IR_VERSION, 0) => ir_version = Some(read_varint(&mut reader)? as i64),
+                (OPSET_IMPORT, 2) => {
+                    let (domain, version) = read_operator_set(&read_bytes(&mut reader)?)?;
+                    if domain.is_empty() || domain == "ai.onnx" {
+                        opset_version = version;
+                    }
+                }
+                (_, wire_type) => skip_field(&mut reader, wire_type)?,
+            }
+        }
+
+        Ok(Self {
+            ir_version: ir_version.ok_or("invalid ONNX model: missing IR version")?,
+            opset_version,
+        })
+    }
+}
+
+/// Reads the domain and version of an `OperatorSetIdProto`
+fn read_operator_set(message: &[u8]) -> Result<(String, Option<i64>)> {
+    let mut reader = Cursor::new(message);
+    let mut domain = String::new();
+    let mut version = None;
+    while let Some(key) = read_key(&mut reader)? {
+        match (key >> 3, key & 7) {
+            (1, 2) => domain = read_string(&mut reader)?,
+            (2, 0) => version = Some(read_varint(&mut reader)? as i64),
+            (_, wire_type) => skip_field(&mut reader, wire_type)?,
+        }
+    }
+    Ok((domain, version))
+}
+
+/// Reads a field key, or returns `None` at the end of the message
+fn read_key<R: Read>(reader: &mut R) -> Result<Option<u64>> {
+    let mut byte = [0u8; 1];
+    match reader.read_exact(&mut byte) {
+        Ok(()) => Ok(Some(read_varint_from(reader, byte[0])?)),
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn read_string<R: Read>(reader: &mut R) -> Result<String> {
+    Ok(String::from_utf8(read_bytes(reader)?)?)
+}
+
+/// Reads a length-delimited field. The buffer grows as the bytes are actually read, so that a corrupt
+/// length cannot trigger a huge allocation.
+fn read_bytes<R: Read>(reader: &mut R) -> Result<Vec<u8>> {
+    let length = read_varint(reader)?;
+    let mut bytes = Vec::new();
+    reader.take(length).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 != length {
+        return Err("invalid ONNX model: truncated field".into());
+    }
+    Ok(bytes)
+}
+
+fn read_varint<R: Read>(reader: &mut R) -> Result<u64> {
+    let mut byte = [0u8; 1];
+    reader.read_exact(&mut byte)?;
+    read_varint_from(reader, byte[0])
+}
+
+fn read_varint_from<R: Read>(reader: &mut R, first: u8) -> Result<u64> {
+    let mut value = (first & 0x7f) as u64;
+    let mut byte = [first];
+    let mut shift = 7;
+    while byte[0] & 0x80 != 0 {
+        if shift >= 64 {
+            return Err("invalid ONNX model: malformed varint".into());
+        }
+        reader.read_exact(&mut byte)?;
+        value |= ((byte[0] & 0x7f) as u64) << shift;
+        shift += 7;
+    }
+    Ok(value)
+}
+
+fn skip_field<R: Read + Seek>(reader: &mut R, wire_type: u64) -> Result<()> {
+    let length = match wire_type {
+        0 => return read_varint(reader).map(|_| ()),
+        1 => 8,
+        2 => read_varint(reader)? as i64,
+        5 => 4,
+        _ => return Err(format!("invalid ONNX model: unexpected wire type {wire_type}").into()),
+    };
+    reader.seek(SeekFrom::Current(length))?;
+    Ok(())
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quantized() {
+        let info = || ModelInfo {
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            quantized: false,
+        };
+        assert!(
+            info()
+                .with_file_name(Path::new("onnx/model_quantized.onnx"))
+                .quantized
+        );
+        assert!(
+            info()
+                .with_file_name(Path::new("model_INT8.onnx"))
+                .quantized
+        );
+        assert!(
+            !info()
+                .with_file_name(Path::new("onnx/model.onnx"))
+                .quantized
+        );
+        assert!(
+            info()
+                .with_graph(b"\x08\x07:\x12DynamicQuantizeLinear")
+                .quantized
+        );
+        assert!(!info().with_graph(b"\x08\x07:\x06MatMul").quantized);
+    }
+
+    #[test]
+    fn test_versions() -> Result<()> {
+        let model = [
+            0x08, 0x08, // ir_version = 8
+            0x3a, 0x02, 0xff, 0xff, // graph (skipped)
+            0x42, 0x06, 0x0a, 0x00, 0x10, 0x11, 0x18,
+            0x01, // opset_import { domain = "", version = 17 }
+            0x42, 0x07, 0x0a, 0x03, b'f', b'o', b'o', 0x10,
+            0x01, // opset_import { domain = "foo", version = 1 }
+        ];
+        let versions = OnnxVersions::from_bytes(&model)?;
+        assert_eq!(versions.ir_version, 8);
+        assert_eq!(versions.opset_version, Some(17));
+        assert!(versions.check().is_ok());
+        // newer versions may be supported by the ONNX Runtime library actually loaded
+        let versions = OnnxVersions {
+            ir_version: 11,
+            opset_version: Some(22),
+        };
+        assert!(versions.check().is_ok());
+        let versions = OnnxVersions {
+            ir_version: 8,
+            opset_version: Some(13),
+        };
+        assert!(versions.check().is_err());
+        // a corrupt length fails without allocating it
+        let model = [0x08, 0x08, 0x42, 0xff, 0xff, 0xff, 0xff, 0x0f, 0x0a];
+        assert!(OnnxVersions::from_bytes(&model).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_tensor_names() -> Result<()> {
+        let model = [
+            0x08, 0x08, // ir_version = 8
+            0x3a, 0x15, // graph {
+            0x2a, 0x02, 0xff, 0xff, // initializer (skipped)
+            0x5a, 0x05, 0x0a, 0x03, b'i', b'd', b's', // input { name = "ids" }
+            0x5a, 0x03, 0x0a, 0x01, b'x', // input { name = "x" }
+            0x62, 0x03, 0x0a, 0x01, b'y', // output { name = "y" } }
+        ];
+        let info = ModelInfo::from_graph_bytes(&model)?;
+        assert_eq!(info.input_names().collect::<Vec<_>>(), vec!["ids", "x"]);
+        assert_eq!(info.output_names().collect::<Vec<_>>(), vec!["y"]);
+        let error = |inputs: &[&str], outputs: &[&str]| {
+            let inputs: HashSet<&str> = inputs.iter().copied().collect();
+            let outputs: HashSet<&str> = outputs.iter().copied().collect();
+            info.check(Some(&inputs), Some(&outputs))
+                .err()
+                .map(|e| e.to_string())
+        };
+        assert_eq!(error(&["ids", "x"], &[]), None);
+        assert_eq!(
+            error(&["ids", "mask"], &["y"]).as_deref(),
+            Some("unexpected ONNX model: missing input tensor(s) [\"mask\"] (the model has [\"ids\", \"x\"])")
+        );
+        // inputs must match exactly, like `orp` checks them
+        assert_eq!(
+            error(&["ids"], &["y"]).as_deref(),
+            Some("unexpected ONNX model: expected input tensors [\"ids\"] (the model has [\"ids\", \"x\"])")
+        );
+        assert!(error(&["ids", "x"], &["y", "z"]).is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_model_dir_report() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("gliner-validate-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let report = crate::model::GLiNER::validate_dir(&dir);
+        let statuses: Vec<_> = report.components.iter().map(|c| c.status).collect();
+        assert_eq!(statuses, vec![ComponentStatus::Missing; 3]);
+        // malformed config, and a model lacking the expected tensors
+        std::fs::write(dir.join("gliner_config.json"), "{")?;
+        std::fs::write(
+            dir.join("model.onnx"),
+            [0x08, 0x08, 0x3a, 0x05, 0x5a, 0x03, 0x0a, 0x01, b'x'],
+        )?;
+        let report = crate::model::GLiNER::validate_dir(&dir);
+        assert_eq!(
+            report.component("config").map(|c| c.status),
+            Some(ComponentStatus::Invalid)
+        );
+        assert!(report
+            .component("onnx_model")
+            .is_some_and(ComponentReport::is_present));
+        std::fs::write(dir.join("gliner_config.json"), "{}")?;
+        let report = crate::model::GLiNER::validate_dir(&dir);
+        std::fs::remove_dir_all(&dir)?;
+        assert!(report
+            .component("config")
+            .is_some_and(ComponentReport::is_present));
+        assert_eq!(
+            report.component("onnx_model").map(|c| c.status),
+            Some(ComponentStatus::Invalid)
+        );
+        assert!(report.check().is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,112 @@
+//! Optional casing normalization of the words, before their sub-word tokenization
+
+use super::tokenized::TokenizedInput;
+use crate::text::token::Token;
+use crate::util::result::Result;
+use composable::Composable;
+
+/// Minimal ratio of upper case letters for a text to be considered as written in capital letters
+const ALL_CAPS_RATIO: f32 = 0.8;
+
+/// Casing normalization of the words given to the model
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Casing {
+    /// Every word is lowercased
+    Lowercase,
+    /// Texts written in capital letters (typically headlines, at least 80% of their letters being upper case) have
+    /// their words converted to title case (e.g. "ACME HIRES BOB" is seen as "Acme Hires Bob"), which suits cased
+    /// models better. Other texts are left untouched.
+    AllCapsToTitle,
+}
+
+impl Casing {
+    /// Returns the given word in normalized casing
+    pub fn apply(&self, word: &str) -> String {
+        match self {
+            Self::Lowercase => word.to_lowercase(),
+            Self::AllCapsToTitle => {
+                let mut chars = word.chars();
+                match chars.next() {
+                    Some(first) => first
+                        .to_uppercase()
+                        .chain(chars.flat_map(char::to_lowercase))
+                        .collect(),
+                    None => String::new(),
+                }
+            }
+        }
+    }
+
+    /// Returns `true` if the words of the given text are to be normalized
+    pub fn applies_to(&self, text: &str) -> bool {
+        match self {
+            Self::Lowercase => true,
+            Self::AllCapsToTitle => {
+                let (upper, cased) = text
+                    .chars()
+                    .filter(|c| c.is_uppercase() || c.is_lowercase())
+                    .fold((0usize, 0usize), |(upper, cased), c| {
+                        (upper + usize::from(c.is_uppercase()), cased + 1)
+                    });
+                cased > 0 && upper as f32 >= ALL_CAPS_RATIO * cased as f32
+            }
+        }
+    }
+}
+
+/// Composable: Tokenized => Tokenized
+///
+/// Normalizes the casing of the words, which is what the model will see. Word offsets are left untouched, so
+/// that entities still refer to the original texts: their `text()` keeps the original casing. This step does
+/// nothing if no casing is set, so that it can always be part of a pipeline.
+#[derive(Default)]
+pub struct TokenizedToCased {
+    casing: Option<Casing>,
+}
+
+impl TokenizedToCased {
+    pub fn new(casing: Option<Casing>) -> Self {
+        Self { casing }
+    }
+}
+
+impl Composable<TokenizedInput, TokenizedInput> for TokenizedToCased {
+    fn apply(&self, mut input: TokenizedInput) -> Result<TokenizedInput> {
+        let Some(casing) = self.casing else {
+            return Ok(input);
+        };
+        let applies: Vec<bool> = input.texts.iter().map(|t| casing.applies_to(t)).collect();
+        for (tokens, sequence_id) in input.tokens.iter_mut().zip(&input.sequence_ids) {
+            if applies.get(*sequence_id).copied().unwrap_or_default() {
+                for token in tokens.iter_mut() {
+                    *token = Token::new(token.start(), token.end(), &casing.apply(token.text()));
+                }
+            }
+        }
+        Ok(input)
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::input::text::TextInput;
+    use crate::text::splitter::RegexSplitter;
+
+    #[test]
+    fn test_casing() -> Result<()> {
+        let input =
+            TextInput::from_str(&["ACME HIRES BOB IN N.Y.", "Acme hires Bob"], &["person"])?;
+        let tokenized = TokenizedInput::from(input, &RegexSplitter::default(), None)?;
+        let cased = TokenizedToCased::new(Some(Casing::AllCapsToTitle)).apply(tokenized)?;
+        let words = |i: usize| -> Vec<&str> { cased.tokens[i].iter().map(Token::text).collect() };
+        assert_eq!(words(0)[..3], ["Acme", "Hires", "Bob"]);
+        assert_eq!(words(1), ["Acme", "hires", "Bob"]);
+        // offsets still refer to the original text
+        let bob = &cased.tokens[0][2];
+        assert_eq!(&cased.texts[0][bob.start()..bob.end()], "BOB");
+        assert_eq!(Casing::Lowercase.apply("ÉCOLE"), "école");
+        Ok(())
+    }
+}
@@ -3,12 +3,15 @@ use crate::text::{token::Token, tokenizer::Tokenizer};
 use crate::util::result::Result;
 use composable::Composable;
 use ndarray::{Array, Array2, ArrayView};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 
 /// Represents encoded prompts (after sub-word tokenization)
 pub struct EncodedInput {
     pub texts: Vec<String>,
     pub tokens: Vec<Vec<Token>>,
     pub entities: Vec<String>,
+    pub sequence_ids: Vec<usize>,
     pub num_words: usize,
     pub num_tokens: usize,
     pub input_ids: Array2<i64>,
@@ -30,28 +33,46 @@ impl EncodedInput {
     // a Vec<u32> (sub-word tokenization). So for each prompt we get a Vec<Vec<u32>> (which is
     // stored in the 'encoding' field).
     pub fn from(input: PromptInput, tokenizer: &impl Tokenizer) -> Result<Self> {
+        Self::from_with_cache(input, tokenizer, None)
+    }
+
+    /// Same as `from`, but reuses the encoding of the entities part of the prompts from the
+    /// given cache (if any) when the entity labels are the same as in the previous call.
+    pub fn from_with_cache(
+        input: PromptInput,
+        tokenizer: &impl Tokenizer,
+        cache: Option<&LabelPromptCache>,
+    ) -> Result<Self> {
+        // the entities part is the same for every prompt, so it is encoded only once
+        let entities_encoding = match input.prompts.first() {
+            Some(prompt) => {
+                let entities_prompt = &prompt.tokens()[..prompt.entities_len()];
+                match cache {
+                    Some(cache) => cache.get_or_encode(entities_prompt, tokenizer)?,
+                    None => Arc::new(encode_words(entities_prompt, tokenizer)?),
+                }
+            }
+            None => Arc::default(),
+        };
+        // number of sub-word tokens for the entities part only (before the actual text)
+        let total_entity_tokens: usize = entities_encoding.iter().map(Vec::len).sum();
         // prepare the result vector
         let mut encodings: Vec<EncodedPrompt> = Vec::with_capacity(input.prompts.len());
         // maximum number of sub-word tokens found in one prompt (will be the width of the input tensor)
         let mut max_tokens: usize = 0;
         // process each prompt
         for prompt in &input.prompts {
-            // resulting sequence of encodings for each word of the current prompt
+            // resulting sequence of encodings for each word of the current prompt, starting with the entities part
             let mut prompt_tokens: Vec<Vec<u32>> = Vec::with_capacity(prompt.tokens().len());
+            prompt_tokens.extend(entities_encoding.iter().cloned());
             // total number of sub-word tokens for the current prompt (adding 2 for initial and terminal tokens)
-            let mut total_tokens: usize = 2;
-            // number of sub-word tokens for the entities part only (before the actual text)
-            let mut total_entity_tokens = 0;
-            // encode each token of the current prompt
-            for (pos, word) in prompt.tokens().iter().enumerate() {
+            let mut total_tokens: usize = 2 + total_entity_tokens;
+            // encode each token of the text part of the current prompt
+            for word in &prompt.tokens()[prompt.entities_len()..] {
                 // actually encode the word
                 let encoding = tokenizer.encode(word)?;
                 // increment the number of sub-word tokens accordingly
                 total_tokens += encoding.len();
-                // increment the number of sub-word tokens in the entity part (will be used to start the word masks at the right place)
-                if pos < prompt.entities_len() {
-                    total_entity_tokens += encoding.len();
-                }
                 prompt_tokens.push(encoding);
             }
 
@@ -126,6 +147,7 @@ impl EncodedInput {
             texts: input.texts,
             tokens: input.tokens,
             entities: input.entities,
+            sequence_ids: input.sequence_ids,
             num_words: input.num_words,
             num_tokens: max_tokens,
             input_ids,
@@ -136,20 +158,115 @@ impl EncodedInput {
     }
 }
 
+/// Encodings of a sequence of words (one `Vec<u32>` per word)
+type WordEncodings = Vec<Vec<u32>>;
+
+/// Encodes each word one by one
+fn encode_words(words: &[String], tokenizer: &impl Tokenizer) -> Result<WordEncodings> {
+    words.iter().map(|word| tokenizer.encode(word)).collect()
+}
+
+/// Cache of the encoded entities part of the prompts, to be reused across calls using the same
+/// entity labels (which is the common case). It holds the encodings of up to `capacity` label sets
+/// (so that callers alternating between a few label sets also benefit from it), the least recently
+/// used one being evicted first. A cache must not be shared between different tokenizers.
+#[derive(Debug)]
+pub struct LabelPromptCache {
+    entries: Mutex<LabelPromptEntries>,
+    capacity: usize,
+}
+
+/// Encodings of the label sets, along with the time they were last used
+#[derive(Debug, Default)]
+struct LabelPromptEntries {
+    encodings: HashMap<Vec<String>, (Arc<WordEncodings>, u64)>,
+    clock: u64,
+}
+
+impl LabelPromptCache {
+    /// Default number of label sets kept by the cache
+    pub const DEFAULT_CAPACITY: usize = 8;
+
+    /// Cache holding up to `capacity` label sets (at least one)
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Default::default(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Returns the encoding of the given entities part of a prompt, from the cache if these labels
+    /// were encoded recently
+    pub fn get_or_encode(
+        &self,
+        entities_prompt: &[String],
+        tokenizer: &impl Tokenizer,
+    ) -> Result<Arc<WordEncodings>> {
+        let mut entries = self
+            .entries
+            .lock()
+            .map_err(|_| "label prompt cache is poisoned")?;
+        entries.clock += 1;
+        let clock = entries.clock;
+        if let Some((encoding, last_used)) = entries.encodings.get_mut(entities_prompt) {
+            *last_used = clock;
+            return Ok(encoding.clone());
+        }
+        if entries.encodings.len() >= self.capacity {
+            let oldest = entries
+                .encodings
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(labels, _)| labels.clone());
+            if let Some(oldest) = oldest {
+                entries.encodings.remove(&oldest);
+            }
+        }
+        let encoding = Arc::new(encode_words(entities_prompt, tokenizer)?);
+        entries
+            .encodings
+            .insert(entities_prompt.to_vec(), (encoding.clone(), clock));
+        Ok(encoding)
+    }
+
+    /// Drops the cached entries, if any
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.encodings.clear();
+        }
+    }
+}
+
+impl Default for LabelPromptCache {
+    fn default() -> Self {
+        Self::with_capacity(Self::DEFAULT_CAPACITY)
+    }
+}
+
 /// Composable: Prompts => Encoded
 pub struct PromptsToEncoded<'a, T> {
     tokenizer: &'a T,
+    cache: Option<&'a LabelPromptCache>,
 }
 
 impl<'a, T> PromptsToEncoded<'a, T> {
     pub fn new(tokenizer: &'a T) -> Self {
-        Self { tokenizer }
+        Self {
+            tokenizer,
+            cache: None,
+        }
+    }
+
+    /// Reuses the encoding of the entity labels across calls (see `LabelPromptCache`)
+    pub fn with_cache(mut self, cache: Option<&'a LabelPromptCache>) -> Self {
+        self.cache = cache;
+        self
     }
 }
 
 impl<T: Tokenizer> Composable<PromptInput, EncodedInput> for PromptsToEncoded<'_, T> {
     fn apply(&self, input: PromptInput) -> Result<EncodedInput> {
-        EncodedInput::from(input, self.tokenizer)
+        EncodedInput::from_with_cache(input, self.tokenizer, self.cache)
     }
 }
 
@@ -343,4 +460,73 @@ mod tests {
 
         Ok(())
     }
+
+    /// Tokenizer encoding each character as a token, and counting the encoded words
+    #[derive(Default)]
+    struct CountingTokenizer {
+        calls: std::cell::Cell<usize>,
+    }
+
+    impl Tokenizer for CountingTokenizer {
+        fn encode(&self, input: &str) -> Result<Vec<u32>> {
+            self.calls.set(self.calls.get() + 1);
+            Ok(input.chars().map(|c| c as u32).collect())
+        }
+    }
+
+    #[test]
+    fn test_label_prompt_cache() -> Result<()> {
+        let splitter = crate::text::splitter::RegexSplitter::default();
+        let tokenizer = CountingTokenizer::default();
+        let cache = LabelPromptCache::default();
+        let encode = |entities: &[&str]| -> Result<EncodedInput> {
+            let input = super::super::text::TextInput::from_str(&["My name is Bond"], entities)?;
+            let tokenized = super::super::tokenized::TokenizedInput::from(input, &splitter, None)?;
+            EncodedInput::from_with_cache(PromptInput::from(tokenized), &tokenizer, Some(&cache))
+        };
+        // first call: 4 words for the text, 5 for the entities part
+        let first = encode(&["person", "place"])?;
+        assert_eq!(tokenizer.calls.get(), 9);
+        // same labels: only the text is encoded
+        let second = encode(&["person", "place"])?;
+        assert_eq!(tokenizer.calls.get(), 13);
+        assert_eq!(first.input_ids, second.input_ids);
+        assert_eq!(first.word_masks, second.word_masks);
+        // other labels: they are encoded as well
+        let third = encode(&["person"])?;
+        assert_eq!(tokenizer.calls.get(), 20);
+        assert_ne!(first.input_ids.shape(), third.input_ids.shape());
+        Ok(())
+    }
+
+    #[test]
+    fn test_label_prompt_cache_alternating() -> Result<()> {
+        let tokenizer = CountingTokenizer::default();
+        let labels = |labels: &[&str]| -> Vec<String> {
+            labels.iter().map(|label| label.to_string()).collect()
+        };
+        let (people, places) = (labels(&["person"]), labels(&["city", "country"]));
+        // both label sets are kept: each one is encoded once
+        let cache = LabelPromptCache::with_capacity(2);
+        for _ in 0..3 {
+            cache.get_or_encode(&people, &tokenizer)?;
+            cache.get_or_encode(&places, &tokenizer)?;
+        }
+        assert_eq!(tokenizer.calls.get(), 3);
+        // a third label set evicts the least recently used one (people)
+        cache.get_or_encode(&labels(&["company"]), &tokenizer)?;
+        cache.get_or_encode(&places, &tokenizer)?;
+        assert_eq!(tokenizer.calls.get(), 4);
+        cache.get_or_encode(&people, &tokenizer)?;
+        assert_eq!(tokenizer.calls.get(), 5);
+        // a single entry is replaced at each change
+        let cache = LabelPromptCache::with_capacity(1);
+        tokenizer.calls.set(0);
+        for _ in 0..3 {
+            cache.get_or_encode(&people, &tokenizer)?;
+            cache.get_or_encode(&places, &tokenizer)?;
+        }
+        assert_eq!(tokenizer.calls.get(), 9);
+        Ok(())
+    }
 }
@@ -2,7 +2,7 @@
 //!
 //! For NER, they will normally be applied in that order:
 //! - Text input (raw entities and texts)
-//! - Tokenized input (unchanged entities with tokenized texts)
+//! - Tokenized input (unchanged entities with tokenized texts), possibly with normalized casing
 //! - Prompts with entities tokens + text word-level tokens
 //! - Encoded prompts applying sub-word tokenization to text tokens
 //! - Ready for inference tensors
@@ -11,9 +11,12 @@
 //! - Input for relation extraction
 //! - ...
 
+pub mod casing;
 pub mod encoded;
+pub mod normalizer;
 pub mod prompt;
 pub mod relation;
+pub mod stream;
 pub mod tensors;
 pub mod text;
 pub mod tokenized;
@@ -0,0 +1,93 @@
+//! Unicode normalization of the texts before splitting them into words
+
+use crate::text::splitter::Splitter;
+use crate::text::token::Token;
+use crate::util::result::Result;
+use tokenizers::normalizer::Range;
+use tokenizers::NormalizedString;
+
+/// Unicode normalization form
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Normalization {
+    /// Canonical composition (e.g. combining accents are merged with their base letter)
+    Nfc,
+    /// Compatibility composition (additionally, e.g. full-width forms become their ASCII equivalent)
+    Nfkc,
+}
+
+/// Splitter normalizing the texts before splitting them with an inner splitter.
+///
+/// Words are given in normalized form (which is what the model will see), while their offsets
+/// refer to the original text, so that entity offsets still index the un-normalized text.
+pub struct NormalizingSplitter<'a, S> {
+    splitter: &'a S,
+    normalization: Normalization,
+}
+
+impl<'a, S> NormalizingSplitter<'a, S> {
+    pub fn new(splitter: &'a S, normalization: Normalization) -> Self {
+        Self {
+            splitter,
+            normalization,
+        }
+    }
+}
+
+impl<S: Splitter> Splitter for NormalizingSplitter<'_, S> {
+    fn split(&self, input: &str, limit: Option<usize>) -> Result<Vec<Token>> {
+        let mut normalized = NormalizedString::from(input);
+        match self.normalization {
+            Normalization::Nfc => normalized.nfc(),
+            Normalization::Nfkc => normalized.nfkc(),
+        };
+        self.splitter
+            .split(normalized.get(), limit)?
+            .into_iter()
+            .map(|token| {
+                let original = normalized
+                    .convert_offsets(Range::Normalized(token.start()..token.end()))
+                    .ok_or_else(|| {
+                        format!(
+                            "normalization error: cannot map offsets ({}, {}) to the original text",
+                            token.start(),
+                            token.end()
+                        )
+                    })?;
+                Ok(Token::new(original.start, original.end, token.text()))
+            })
+            .collect()
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::splitter::RegexSplitter;
+
+    #[test]
+    fn test_nfkc() -> Result<()> {
+        let splitter = RegexSplitter::default();
+        // full-width hyphen
+        let text = "oh\u{ff0d}yeah test";
+        assert_eq!(splitter.split(text, None)?.len(), 4);
+        let tokens = NormalizingSplitter::new(&splitter, Normalization::Nfkc).split(text, None)?;
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[0].text(), "oh-yeah");
+        assert_eq!(&text[tokens[0].start()..tokens[0].end()], "oh\u{ff0d}yeah");
+        assert_eq!(&text[tokens[1].start()..tokens[1].end()], "test");
+        Ok(())
+    }
+
+    #[test]
+    fn test_nfc() -> Result<()> {
+        let splitter = RegexSplitter::default();
+        // decomposed accent
+        let text = "une e\u{301}cole";
+        let tokens = NormalizingSplitter::new(&splitter, Normalization::Nfc).split(text, None)?;
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[1].text(), "\u{e9}cole");
+        assert_eq!(&text[tokens[1].start()..tokens[1].end()], "e\u{301}cole");
+        Ok(())
+    }
+}
@@ -2,6 +2,7 @@ use super::tokenized::TokenizedInput;
 use crate::text::{prompt::Prompt, token::Token};
 use crate::util::result::Result;
 use composable::Composable;
+use std::collections::HashMap;
 
 /// Prepared prompts, appending entity and text tokens.
 ///
@@ -16,6 +17,8 @@ pub struct PromptInput {
     pub tokens: Vec<Vec<Token>>,
     /// Entities (moved from input)
     pub entities: Vec<String>,
+    /// Original sequence of each prompt (moved from input)
+    pub sequence_ids: Vec<usize>,
     /// Number of tokens of the text part for each prompt
     pub text_lengths: Vec<usize>,
     /// Maximum number of words in a prompt excluding entities (number of tokens in the largest sequence in the batch)
@@ -24,10 +27,43 @@ pub struct PromptInput {
     pub prompts: Vec<Prompt>,
 }
 
+/// Special tokens used to build the prompts (see `PromptInput`), which some checkpoints define differently
+/// (the defaults being the ones of the original GLiNER models)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptConfig {
+    /// Token preceding each entity label
+    pub entity_token: String,
+    /// Token separating the entity labels from the text
+    pub sep_token: String,
+}
+
+impl Default for PromptConfig {
+    fn default() -> Self {
+        Self {
+            entity_token: "<<ENT>>".to_string(),
+            sep_token: "<<SEP>>".to_string(),
+        }
+    }
+}
+
+impl PromptConfig {
+    pub fn new(entity_token: &str, sep_token: &str) -> Self {
+        Self {
+            entity_token: entity_token.to_string(),
+            sep_token: sep_token.to_string(),
+        }
+    }
+}
+
 impl PromptInput {
     pub fn from(input: TokenizedInput) -> Self {
+        Self::from_with_config(input, &PromptConfig::default())
+    }
+
+    /// Same as `from`, using the given special tokens
+    pub fn from_with_config(input: TokenizedInput, config: &PromptConfig) -> Self {
         // prepare the entities part of the prompt (will be copied into each actual prompt)
-        let entities_prompt = Self::entities_prompt(&input.entities);
+        let entities_prompt = Self::entities_prompt(&input.entities, config);
         // the text lengths for each sequence (number of actual tokens beside the entities part)
         let mut text_lengths = Vec::<usize>::new();
         // the maximum number of words in a prompt excluding entities (number of tokens in the largest sequence in the batch)
@@ -54,6 +90,7 @@ impl PromptInput {
             texts: input.texts,
             tokens: input.tokens,
             entities: input.entities,
+            sequence_ids: input.sequence_ids,
             text_lengths,
             num_words,
             prompts,
@@ -61,28 +98,124 @@ impl PromptInput {
     }
 
     /// Create the entities part of the prompt.
-    fn entities_prompt(entities: &Vec<String>) -> Vec<String> {
-        const ENTITY_TOKEN: &str = "<<ENT>>";
-        const SEP_TOKEN: &str = "<<SEP>>";
-
+    fn entities_prompt(entities: &Vec<String>, config: &PromptConfig) -> Vec<String> {
         let mut result = Vec::with_capacity(entities.len() * 2 + 1);
         for entity in entities {
-            result.push(ENTITY_TOKEN.to_string());
+            result.push(config.entity_token.clone());
             result.push(entity.clone());
         }
 
-        result.push(SEP_TOKEN.to_string());
+        result.push(config.sep_token.clone());
         result
     }
 }
 
+/// Normalized form of an entity label, as used in the prompts if label normalization is enabled (trimmed and lowercased)
+pub fn normalize_label(label: &str) -> String {
+    label.trim().to_lowercase()
+}
+
 /// Composable: Tokenized => Prompt
 #[derive(Default)]
-pub struct TokenizedToPrompt {}
+pub struct TokenizedToPrompt {
+    config: PromptConfig,
+    normalize_labels: bool,
+    label_descriptions: Option<HashMap<String, String>>,
+    labels_warning: Option<usize>,
+    max_labels: Option<usize>,
+}
+
+impl TokenizedToPrompt {
+    pub fn new(config: PromptConfig) -> Self {
+        Self {
+            config,
+            normalize_labels: false,
+            label_descriptions: None,
+            labels_warning: None,
+            max_labels: None,
+        }
+    }
+
+    /// If `true`, the entity labels are normalized in the prompts (see `normalize_label`), the output still
+    /// referring to the labels as given (default: false)
+    pub fn with_label_normalization(mut self, normalize_labels: bool) -> Self {
+        self.normalize_labels = normalize_labels;
+        self
+    }
+
+    /// Descriptions of the entity labels (e.g. "stock ticker of a company" for "ORG_TICKER"), which are used
+    /// in the prompts instead of the labels, the output still referring to the labels (default: None)
+    pub fn with_label_descriptions(
+        mut self,
+        label_descriptions: Option<HashMap<String, String>>,
+    ) -> Self {
+        self.label_descriptions = label_descriptions;
+        self
+    }
+
+    /// Label as written in the prompts: its description if any, normalized if enabled
+    fn prompt_label(&self, label: &str) -> String {
+        let label = self
+            .label_descriptions
+            .as_ref()
+            .and_then(|descriptions| descriptions.get(label))
+            .map_or(label, String::as_str);
+        if self.normalize_labels {
+            normalize_label(label)
+        } else {
+            label.to_string()
+        }
+    }
+
+    /// Guards against too many entity labels (each of them widening the output tensor): a warning is logged above
+    /// `labels_warning` labels, and an error is returned above `max_labels` labels (default: None for both)
+    pub fn with_label_limits(
+        mut self,
+        labels_warning: Option<usize>,
+        max_labels: Option<usize>,
+    ) -> Self {
+        self.labels_warning = labels_warning;
+        self.max_labels = max_labels;
+        self
+    }
+
+    fn check_label_count(&self, num_labels: usize) -> Result<()> {
+        if let Some(max_labels) = self.max_labels {
+            if num_labels > max_labels {
+                return Err(format!(
+                    "too many entity labels: got {num_labels}, the maximum being {max_labels} (see the max_labels parameter)"
+                )
+                .into());
+            }
+        }
+        if let Some(labels_warning) = self.labels_warning {
+            if num_labels > labels_warning {
+                log::warn!(
+                    "{num_labels} entity labels requested (more than {labels_warning}), which may use a lot of memory and slow down inference"
+                );
+            }
+        }
+        Ok(())
+    }
+}
 
 impl Composable<TokenizedInput, PromptInput> for TokenizedToPrompt {
-    fn apply(&self, input: TokenizedInput) -> Result<PromptInput> {
-        Ok(PromptInput::from(input))
+    fn apply(&self, mut input: TokenizedInput) -> Result<PromptInput> {
+        self.check_label_count(input.entities.len())?;
+        if !self.normalize_labels && self.label_descriptions.is_none() {
+            return Ok(PromptInput::from_with_config(input, &self.config));
+        }
+        let prompt_labels = input
+            .entities
+            .iter()
+            .map(|e| self.prompt_label(e))
+            .collect();
+        let entities = std::mem::replace(&mut input.entities, prompt_labels);
+        // the prompts use the described or normalized labels, while the spans will use the original ones
+        Ok(PromptInput {
+            entities,
+            ..PromptInput::from_with_config(input, &self.config)
+        })
     }
 }
 
@@ -123,4 +256,60 @@ mod tests {
         // Everything rules
         Ok(())
     }
+
+    #[test]
+    fn test_label_normalization() -> Result<()> {
+        let splitter = crate::text::splitter::RegexSplitter::default();
+        let input = super::super::text::TextInput::from_str(&["Hello"], &[" Person", "place"])?;
+        let tokenized = super::super::tokenized::TokenizedInput::from(input, &splitter, None)?;
+        let prepared = TokenizedToPrompt::default()
+            .with_label_normalization(true)
+            .apply(tokenized)?;
+        assert_eq!(
+            prepared.prompts[0].tokens(),
+            &["<<ENT>>", "person", "<<ENT>>", "place", "<<SEP>>", "Hello"]
+        );
+        assert_eq!(prepared.entities, vec![" Person", "place"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_max_labels() -> Result<()> {
+        let splitter = crate::text::splitter::RegexSplitter::default();
+        let tokenized = || {
+            let input =
+                super::super::text::TextInput::from_str(&["Hello"], &["person", "place", "date"])?;
+            super::super::tokenized::TokenizedInput::from(input, &splitter, None)
+        };
+        let step = |max_labels| TokenizedToPrompt::default().with_label_limits(Some(1), max_labels);
+        assert!(step(Some(2)).apply(tokenized()?).is_err());
+        assert!(step(Some(3)).apply(tokenized()?).is_ok());
+        assert!(step(None).apply(tokenized()?).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_label_descriptions() -> Result<()> {
+        let splitter = crate::text::splitter::RegexSplitter::default();
+        let input = super::super::text::TextInput::from_str(&["Hello"], &["ORG_TICKER", "PER"])?;
+        let tokenized = super::super::tokenized::TokenizedInput::from(input, &splitter, None)?;
+        let descriptions = [("ORG_TICKER".to_string(), "Stock ticker".to_string())];
+        let prepared = TokenizedToPrompt::default()
+            .with_label_descriptions(Some(descriptions.into_iter().collect()))
+            .with_label_normalization(true)
+            .apply(tokenized)?;
+        assert_eq!(
+            prepared.prompts[0].tokens(),
+            &[
+                "<<ENT>>",
+                "stock ticker",
+                "<<ENT>>",
+                "per",
+                "<<SEP>>",
+                "Hello"
+            ]
+        );
+        assert_eq!(prepared.entities, vec!["ORG_TICKER", "PER"]);
+        Ok(())
+    }
 }
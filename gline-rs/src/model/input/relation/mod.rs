@@ -2,6 +2,7 @@ pub mod schema;
 
 use crate::model::output::decoded::SpanOutput;
 use crate::model::pipeline::context::RelationContext;
+use crate::text::span::Span;
 use crate::util::result::Result;
 use composable::*;
 use schema::RelationSchema;
@@ -15,21 +16,36 @@ pub struct RelationInput {
     pub labels: Vec<String>,
     pub entity_labels: HashMap<String, HashSet<String>>,
     pub entity_offsets: HashMap<String, (usize, usize)>, // new
+    pub entity_probabilities: HashMap<String, f32>,
+    /// Entities of each sequence (see `RelationContext::entity_spans`)
+    pub entity_spans: Vec<Vec<Span>>,
+    /// Offset of the text within the prompts
+    pub text_offset: usize,
 }
 
 impl RelationInput {
     /// Builds a relation input from a span output and a relation schema
     pub fn from_spans(spans: SpanOutput, schema: &RelationSchema) -> Self {
+        Self::from_spans_with(spans, schema, false)
+    }
+
+    /// Same as `from_spans`, but if `all_pairs` is set, the labels are not restricted to the subjects allowed by the schema
+    pub fn from_spans_with(spans: SpanOutput, schema: &RelationSchema, all_pairs: bool) -> Self {
         let prompts = Self::make_prompts(&spans, PROMPT_PREFIX);
-        let labels = Self::make_labels(&spans, schema);
+        let labels = Self::make_labels(&spans, schema, all_pairs);
         let entity_labels = Self::make_entity_labels(&spans);
         let entity_offsets = Self::make_entity_offsets(&spans); // new line
+        let entity_probabilities = Self::make_entity_probabilities(&spans);
 
         Self {
             prompts,
             labels,
             entity_labels,
             entity_offsets,
+            entity_probabilities,
+            entity_spans: spans.spans,
+            // see `make_prompts`
+            text_offset: PROMPT_PREFIX.len() + 1,
         }
     }
 
@@ -43,7 +59,7 @@ impl RelationInput {
     }
 
     /// Prepare the labels basing on extracted entities and the provided schema
-    fn make_labels(spans: &SpanOutput, schema: &RelationSchema) -> Vec<String> {
+    fn make_labels(spans: &SpanOutput, schema: &RelationSchema, all_pairs: bool) -> Vec<String> {
         // List unique (entity, class) entries found in all spans for all sequences.
         // This is sub-optimal because one huge label list will be made for all sequences,
         // but this is how GLiNER multitask works...
@@ -60,15 +76,15 @@ impl RelationInput {
 
         // Actually create the labels. Labels for not allowed entity classes for the subject (according
         // to the schema) will not be included. The check on the object class has to be made when
-        // decoding the result.
+        // decoding the result. With `all_pairs`, every entity is a candidate subject.
         let mut relations = schema.relations().iter().collect::<Vec<_>>();
-        relations.sort_unstable_by(|(left, _), (right, _)| left.cmp(right));
+        relations.sort_unstable_by_key(|(relation, _)| *relation);
 
         let mut result = Vec::new();
         for (relation, spec) in relations {
             unique_entities
                 .iter()
-                .filter(|(_, class)| spec.allows_subject(class))
+                .filter(|(_, class)| all_pairs || spec.allows_subject_in_any_direction(class))
                 .map(|(text, _)| format!("{} <> {}", text, relation))
                 .for_each(|l| result.push(l));
         }
@@ -106,21 +122,48 @@ impl RelationInput {
         }
         entity_offsets
     }
+
+    /// Build entity-text -> entity-probability map (for the same occurrence as `make_entity_offsets`)
+    fn make_entity_probabilities(spans: &SpanOutput) -> HashMap<String, f32> {
+        let mut entity_probabilities = HashMap::<String, f32>::new();
+        for seq in &spans.spans {
+            for span in seq {
+                entity_probabilities
+                    .entry(span.text().to_string())
+                    .or_insert_with(|| span.probability());
+            }
+        }
+        entity_probabilities
+    }
 }
 
 pub struct SpanOutputToRelationInput<'a> {
     schema: &'a RelationSchema,
+    all_pairs: bool,
 }
 
 impl<'a> SpanOutputToRelationInput<'a> {
     pub fn new(schema: &'a RelationSchema) -> Self {
-        Self { schema }
+        Self {
+            schema,
+            all_pairs: false,
+        }
+    }
+
+    /// Makes labels for all the entities, whatever the subjects allowed by the schema (see `RelationInput::from_spans_with`)
+    pub fn with_all_pairs(mut self, all_pairs: bool) -> Self {
+        self.all_pairs = all_pairs;
+        self
     }
 }
 
 impl Composable<SpanOutput, RelationInput> for SpanOutputToRelationInput<'_> {
     fn apply(&self, input: SpanOutput) -> Result<RelationInput> {
-        Ok(RelationInput::from_spans(input, self.schema))
+        Ok(RelationInput::from_spans_with(
+            input,
+            self.schema,
+            self.all_pairs,
+        ))
     }
 }
 
@@ -136,6 +179,9 @@ impl Composable<RelationInput, (super::text::TextInput, RelationContext)>
             RelationContext {
                 entity_labels: input.entity_labels,
                 entity_offsets: input.entity_offsets,
+                entity_probabilities: input.entity_probabilities,
+                entity_spans: input.entity_spans,
+                text_offset: input.text_offset,
             },
         ))
     }
@@ -1,28 +1,69 @@
+use crate::util::result::Result;
+use serde::Deserialize;
 use std::collections::{HashMap, HashSet};
+use std::path::Path;
 
 pub struct RelationSchema {
     relations: HashMap<String, RelationSpec>,
+    /// Relations that were pushed several times with different specs (see `validate`)
+    conflicts: HashSet<String>,
 }
 
 impl RelationSchema {
     pub fn new() -> Self {
         Self {
             relations: HashMap::new(),
+            conflicts: HashSet::new(),
         }
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(relations: &[&str]) -> Self {
         Self {
             relations: relations
                 .iter()
                 .map(|r| (r.to_string(), RelationSpec::default()))
                 .collect(),
+            conflicts: HashSet::new(),
         }
     }
 
+    /// Loads a schema from a JSON file (see `from_json_str` for the expected format)
+    pub fn from_json_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let raw = std::fs::read_to_string(path)?;
+        Self::from_json_str(&raw)
+    }
+
+    /// Parses a schema from a JSON document mapping each relation to its allowed labels
+    /// (`symmetric` and `reflexive` are optional, see `push_bidirectional` and `RelationSpec::with_reflexive`):
+    ///
+    /// ```json
+    /// {
+    ///     "founded": { "subject_labels": ["person"], "object_labels": ["company"] },
+    ///     "located in": { "subject_labels": ["company"], "object_labels": ["city", "country"] },
+    ///     "spouse of": { "subject_labels": ["person"], "object_labels": ["person"], "symmetric": true }
+    /// }
+    /// ```
+    pub fn from_json_str(json: &str) -> Result<Self> {
+        let document: HashMap<String, RelationSpecDocument> = serde_json::from_str(json)?;
+        let mut schema = Self::new();
+        for (relation, spec) in document {
+            schema.push_with_spec(
+                &relation,
+                RelationSpec {
+                    allowed_subjects: Some(spec.subject_labels.into_iter().collect()),
+                    allowed_objects: Some(spec.object_labels.into_iter().collect()),
+                    symmetric: spec.symmetric,
+                    reflexive: spec.reflexive,
+                },
+            );
+        }
+        schema.validate()?;
+        Ok(schema)
+    }
+
     pub fn push(&mut self, relation: &str) {
-        self.relations
-            .insert(relation.to_string(), RelationSpec::default());
+        self.push_with_spec(relation, RelationSpec::default());
     }
 
     pub fn push_with_allowed_labels(
@@ -31,19 +72,77 @@ impl RelationSchema {
         allowed_subjects: &[&str],
         allowed_objects: &[&str],
     ) {
-        self.relations.insert(
-            relation.to_string(),
+        self.push_with_spec(
+            relation,
             RelationSpec::new(allowed_subjects, allowed_objects),
         );
     }
 
+    /// Adds a symmetric relation (like "spouse of"), which holds in both directions: a relation
+    /// is accepted if its entities match the allowed labels in either direction, and each pair of
+    /// entities is reported only once.
+    pub fn push_bidirectional(
+        &mut self,
+        relation: &str,
+        allowed_subjects: &[&str],
+        allowed_objects: &[&str],
+    ) {
+        self.push_with_spec(
+            relation,
+            RelationSpec::new(allowed_subjects, allowed_objects).with_symmetric(true),
+        );
+    }
+
+    /// Adds a relation with the given spec. Pushing a relation again replaces its spec, but if both
+    /// specs differ the conflict is reported by `validate`.
     pub fn push_with_spec(&mut self, relation: &str, spec: RelationSpec) {
-        self.relations.insert(relation.to_string(), spec);
+        if let Some(previous) = self.relations.insert(relation.to_string(), spec) {
+            if self.relations.get(relation) != Some(&previous) {
+                self.conflicts.insert(relation.to_string());
+            }
+        }
     }
 
     pub fn relations(&self) -> &HashMap<String, RelationSpec> {
         &self.relations
     }
+
+    /// Iterates over the relations along with their specs (in no particular order)
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &RelationSpec)> {
+        self.relations
+            .iter()
+            .map(|(relation, spec)| (relation.as_str(), spec))
+    }
+
+    /// Checks that the schema is sound, reporting every problem found:
+    ///
+    /// * relations with an empty name
+    /// * relations whose allowed subject or object labels are empty (which would reject every relation)
+    /// * relations pushed several times with different specs
+    pub fn validate(&self) -> Result<()> {
+        let mut problems = Vec::new();
+        let mut relations: Vec<(&str, &RelationSpec)> = self.iter().collect();
+        relations.sort_unstable_by_key(|(relation, _)| *relation);
+        for (relation, spec) in relations {
+            if relation.trim().is_empty() {
+                problems.push("empty relation name".to_string());
+            }
+            if spec.allowed_subjects().is_some_and(HashSet::is_empty) {
+                problems.push(format!("empty subject labels for relation '{relation}'"));
+            }
+            if spec.allowed_objects().is_some_and(HashSet::is_empty) {
+                problems.push(format!("empty object labels for relation '{relation}'"));
+            }
+            if self.conflicts.contains(relation) {
+                problems.push(format!("conflicting specs for relation '{relation}'"));
+            }
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("invalid relation schema: {}", problems.join(", ")).into())
+        }
+    }
 }
 
 impl Default for RelationSchema {
@@ -52,9 +151,12 @@ impl Default for RelationSchema {
     }
 }
 
+#[derive(Debug, Default, PartialEq)]
 pub struct RelationSpec {
     allowed_subjects: Option<HashSet<String>>,
     allowed_objects: Option<HashSet<String>>,
+    symmetric: bool,
+    reflexive: bool,
 }
 
 impl RelationSpec {
@@ -62,9 +164,42 @@ impl RelationSpec {
         Self {
             allowed_subjects: Some(allowed_subjects.iter().map(|x| x.to_string()).collect()),
             allowed_objects: Some(allowed_objects.iter().map(|x| x.to_string()).collect()),
+            symmetric: false,
+            reflexive: false,
         }
     }
 
+    /// Makes the relation symmetric (see `RelationSchema::push_bidirectional`)
+    pub fn with_symmetric(mut self, symmetric: bool) -> Self {
+        self.symmetric = symmetric;
+        self
+    }
+
+    pub fn is_symmetric(&self) -> bool {
+        self.symmetric
+    }
+
+    /// Allows relations whose subject and object are the same entity (rejected by default, as
+    /// such relations are almost always spurious)
+    pub fn with_reflexive(mut self, reflexive: bool) -> Self {
+        self.reflexive = reflexive;
+        self
+    }
+
+    pub fn is_reflexive(&self) -> bool {
+        self.reflexive
+    }
+
+    /// Allowed subject labels (`None` means that any label is allowed)
+    pub fn allowed_subjects(&self) -> Option<&HashSet<String>> {
+        self.allowed_subjects.as_ref()
+    }
+
+    /// Allowed object labels (`None` means that any label is allowed)
+    pub fn allowed_objects(&self) -> Option<&HashSet<String>> {
+        self.allowed_objects.as_ref()
+    }
+
     pub fn allows_subject(&self, label: &str) -> bool {
         match &self.allowed_subjects {
             None => true,
@@ -72,6 +207,12 @@ impl RelationSpec {
         }
     }
 
+    /// Same as `allows_subject`, but also accepts allowed objects if the relation is symmetric
+    /// (as the subject of the prediction might be the object of the declared relation)
+    pub fn allows_subject_in_any_direction(&self, label: &str) -> bool {
+        self.allows_subject(label) || (self.symmetric && self.allows_object(label))
+    }
+
     pub fn allows_object(&self, label: &str) -> bool {
         match &self.allowed_objects {
             None => true,
@@ -94,11 +235,70 @@ impl RelationSpec {
     }
 }
 
-impl Default for RelationSpec {
-    fn default() -> Self {
-        Self {
-            allowed_subjects: None,
-            allowed_objects: None,
-        }
+/// Entry of the JSON representation of a schema (see `RelationSchema::from_json_str`)
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RelationSpecDocument {
+    subject_labels: Vec<String>,
+    object_labels: Vec<String>,
+    #[serde(default)]
+    symmetric: bool,
+    #[serde(default)]
+    reflexive: bool,
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_json_str() -> Result<()> {
+        #![allow(clippy::unwrap_used)]
+        let schema = RelationSchema::from_json_str(
+            r#"{
+                "founded": { "subject_labels": ["person"], "object_labels": ["company"] },
+                "located in": { "subject_labels": ["company"], "object_labels": ["city", "country"] }
+            }"#,
+        )?;
+        assert_eq!(schema.relations().len(), 2);
+        let spec = schema.relations().get("located in").unwrap();
+        assert!(spec.allows_subject("company"));
+        assert!(!spec.allows_subject("person"));
+        assert!(spec.allows_object("country"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_json_str_invalid() {
+        assert!(RelationSchema::from_json_str(
+            r#"{ "": { "subject_labels": ["person"], "object_labels": ["company"] } }"#
+        )
+        .is_err());
+        assert!(RelationSchema::from_json_str(
+            r#"{ "founded": { "subject_labels": [], "object_labels": ["company"] } }"#
+        )
+        .is_err());
+        assert!(RelationSchema::from_json_str(
+            r#"{ "founded": { "subject_labels": ["person"] } }"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_validate() {
+        #![allow(clippy::unwrap_used)]
+        let mut schema = RelationSchema::new();
+        schema.push_with_allowed_labels("founded", &["person"], &["company"]);
+        schema.push_with_allowed_labels("founded", &["person"], &["company"]);
+        assert!(schema.validate().is_ok());
+        assert_eq!(schema.iter().count(), 1);
+        schema.push_with_allowed_labels("works for", &["person"], &[]);
+        schema.push_with_allowed_labels("founded", &["person"], &["organization"]);
+        let error = schema.validate().unwrap_err().to_string();
+        assert_eq!(
+            error,
+            "invalid relation schema: conflicting specs for relation 'founded', empty object labels for relation 'works for'"
+        );
     }
 }
@@ -0,0 +1,188 @@
+//! Incremental segmentation of long documents into windows of words
+
+use crate::text::splitter::Splitter;
+use crate::text::token::Token;
+use crate::util::result::Result;
+use std::io::BufRead;
+
+/// Maximum number of bytes read at once
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// Window of a document, with its byte offset within the whole document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextWindow {
+    pub offset: usize,
+    pub text: String,
+}
+
+/// Iterator over the windows of a document read incrementally (by chunks of at most `CHUNK_SIZE` bytes), each
+/// window being made of `max_words` words (according to the splitter), consecutive windows sharing `overlap` words.
+///
+/// Unlike windowing at the tokenization step (see `TokenizedInput::from_windows`), the whole document
+/// never needs to be held in memory: only the current window and the chunks read beyond it are. Since a chunk
+/// may end in the middle of a word, only the text up to the last whitespace is split until the end of the document.
+/// A `&[u8]` being a `BufRead`, a string slice can be streamed as well with `text.as_bytes()`.
+pub struct TextWindows<'a, R, S> {
+    reader: R,
+    splitter: &'a S,
+    max_words: usize,
+    overlap: usize,
+    /// Text read but not entirely processed yet
+    buffer: String,
+    /// Bytes read beyond the buffer, if a chunk ends in the middle of a UTF-8 character
+    pending: Vec<u8>,
+    /// Offset of the buffer within the document
+    offset: usize,
+    eof: bool,
+}
+
+impl<'a, R: BufRead, S: Splitter> TextWindows<'a, R, S> {
+    pub fn new(reader: R, splitter: &'a S, max_words: usize, overlap: usize) -> Result<Self> {
+        if overlap >= max_words {
+            return Err(format!(
+                "invalid parameters: overlap ({overlap}) must be lower than the window size ({max_words})"
+            )
+            .into());
+        }
+        Ok(Self {
+            reader,
+            splitter,
+            max_words,
+            overlap,
+            buffer: String::new(),
+            pending: Vec::new(),
+            offset: 0,
+            eof: false,
+        })
+    }
+
+    fn next_window(&mut self) -> Result<Option<TextWindow>> {
+        // read until one more word than the window is available (to know whether it is the last one)
+        let mut words = self.complete_words()?;
+        while words.len() <= self.max_words && !self.eof {
+            self.eof = !self.read_chunk()?;
+            words = self.complete_words()?;
+        }
+        let (Some(first), Some(last)) = (
+            words.first(),
+            words.get(self.max_words - 1).or(words.last()),
+        ) else {
+            return Ok(None);
+        };
+        let window = TextWindow {
+            offset: self.offset + first.start(),
+            text: self.buffer[first.start()..last.end()].to_string(),
+        };
+        // the next window starts with the last `overlap` words of this one (if it is not the last one)
+        let next_start = match words.get(self.max_words) {
+            Some(_) => words[self.max_words - self.overlap].start(),
+            None => self.buffer.len(),
+        };
+        self.buffer.drain(..next_start);
+        self.offset += next_start;
+        Ok(Some(window))
+    }
+
+    /// Splits the buffer up to its last whitespace (the last word being possibly incomplete), or entirely
+    /// once the document has been read
+    fn complete_words(&self) -> Result<Vec<Token>> {
+        let end = match self.eof {
+            true => self.buffer.len(),
+            false => self.buffer.rfind(char::is_whitespace).unwrap_or(0),
+        };
+        self.splitter
+            .split(&self.buffer[..end], Some(self.max_words + 1))
+    }
+
+    /// Appends the next chunk of the document to the buffer, returning `false` at the end of the document
+    fn read_chunk(&mut self) -> Result<bool> {
+        let chunk = self.reader.fill_buf()?;
+        if chunk.is_empty() {
+            return match self.pending.is_empty() {
+                true => Ok(false),
+                false => {
+                    Err("invalid UTF-8: the document ends with an incomplete character".into())
+                }
+            };
+        }
+        let len = chunk.len().min(CHUNK_SIZE);
+        self.pending.extend_from_slice(&chunk[..len]);
+        self.reader.consume(len);
+        // an incomplete character at the end of the chunk is kept for the next one
+        let valid = match std::str::from_utf8(&self.pending) {
+            Ok(text) => text.len(),
+            Err(error) if error.error_len().is_none() => error.valid_up_to(),
+            Err(error) => return Err(error.into()),
+        };
+        self.buffer
+            .push_str(std::str::from_utf8(&self.pending[..valid])?);
+        self.pending.drain(..valid);
+        Ok(true)
+    }
+}
+
+impl<R: BufRead, S: Splitter> Iterator for TextWindows<'_, R, S> {
+    type Item = Result<TextWindow>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_window().transpose()
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::splitter::RegexSplitter;
+
+    #[test]
+    fn test_windows() -> Result<()> {
+        let text = "one two three\nfour five\n\nsix seven";
+        let splitter = RegexSplitter::default();
+        let windows =
+            TextWindows::new(text.as_bytes(), &splitter, 3, 1)?.collect::<Result<Vec<_>>>()?;
+        let texts: Vec<&str> = windows.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            vec!["one two three", "three\nfour five", "five\n\nsix seven"]
+        );
+        for window in &windows {
+            assert_eq!(
+                &text[window.offset..window.offset + window.text.len()],
+                window.text
+            );
+        }
+        assert!(TextWindows::new("".as_bytes(), &splitter, 3, 1)?
+            .next()
+            .is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_chunks() -> Result<()> {
+        // chunks (of the reader, then of `CHUNK_SIZE`) ending in the middle of words and characters
+        let text = "héllo wörld-wide ".repeat(CHUNK_SIZE / 4);
+        let splitter = RegexSplitter::default();
+        let reader = std::io::BufReader::with_capacity(7, text.as_bytes());
+        let windows = TextWindows::new(reader, &splitter, 5, 2)?.collect::<Result<Vec<_>>>()?;
+        for window in &windows {
+            assert_eq!(
+                &text[window.offset..window.offset + window.text.len()],
+                window.text
+            );
+        }
+        // only the last window may have fewer words
+        for window in &windows[..windows.len() - 1] {
+            assert_eq!(splitter.split(&window.text, None)?.len(), 5);
+        }
+        assert_eq!(
+            windows.last().map(|w| w.offset + w.text.len()),
+            Some(text.trim_end().len())
+        );
+        let invalid: &[u8] = &[b'a', b' ', 0xff, b' '];
+        assert!(TextWindows::new(invalid, &splitter, 3, 1)?
+            .next()
+            .is_some_and(|window| window.is_err()));
+        Ok(())
+    }
+}
@@ -1,2 +1,37 @@
+use std::sync::Arc;
+
 pub mod span;
 pub mod token;
+
+const TENSOR_INPUT_IDS: &str = "input_ids";
+const TENSOR_ATTENTION_MASK: &str = "attention_mask";
+const TENSOR_WORD_MASK: &str = "words_mask";
+const TENSOR_TEXT_LENGTHS: &str = "text_lengths";
+
+/// Names of the input tensors shared by both modes, which may differ from the usual ones
+/// depending on the toolchain used to export the model (see `ModelConfig::input_names`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputNames {
+    pub input_ids: Arc<str>,
+    pub attention_mask: Arc<str>,
+    pub words_mask: Arc<str>,
+    pub text_lengths: Arc<str>,
+}
+
+impl InputNames {
+    /// Returns `true` if these are the usual names
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl Default for InputNames {
+    fn default() -> Self {
+        Self {
+            input_ids: TENSOR_INPUT_IDS.into(),
+            attention_mask: TENSOR_ATTENTION_MASK.into(),
+            words_mask: TENSOR_WORD_MASK.into(),
+            text_lengths: TENSOR_TEXT_LENGTHS.into(),
+        }
+    }
+}
@@ -1,13 +1,12 @@
 use super::super::super::pipeline::context::EntityContext;
 use super::super::encoded::EncodedInput;
+use super::{
+    InputNames, TENSOR_ATTENTION_MASK, TENSOR_INPUT_IDS, TENSOR_TEXT_LENGTHS, TENSOR_WORD_MASK,
+};
 use crate::util::result::Result;
 use composable::Composable;
 use ort::session::SessionInputs;
 
-const TENSOR_INPUT_IDS: &str = "input_ids";
-const TENSOR_ATTENTION_MASK: &str = "attention_mask";
-const TENSOR_WORD_MASK: &str = "words_mask";
-const TENSOR_TEXT_LENGTHS: &str = "text_lengths";
 const TENSOR_SPAN_IDX: &str = "span_idx";
 const TENSOR_SPAN_MASK: &str = "span_mask";
 
@@ -18,13 +17,13 @@ pub struct SpanTensors<'a> {
 }
 
 impl SpanTensors<'_> {
-    pub fn from(encoded: EncodedInput, max_width: usize) -> Result<Self> {
+    pub fn from(encoded: EncodedInput, max_width: usize, names: &InputNames) -> Result<Self> {
         let (span_idx, span_mask) = Self::make_spans_tensors(&encoded, max_width);
         let inputs = ort::inputs! {
-            TENSOR_INPUT_IDS => encoded.input_ids,
-            TENSOR_ATTENTION_MASK => encoded.attention_masks,
-            TENSOR_WORD_MASK => encoded.word_masks,
-            TENSOR_TEXT_LENGTHS => encoded.text_lengths,
+            names.input_ids.to_string() => encoded.input_ids,
+            names.attention_mask.to_string() => encoded.attention_masks,
+            names.words_mask.to_string() => encoded.word_masks,
+            names.text_lengths.to_string() => encoded.text_lengths,
             TENSOR_SPAN_IDX => span_idx,
             TENSOR_SPAN_MASK => span_mask,
         }?;
@@ -34,12 +33,25 @@ impl SpanTensors<'_> {
                 texts: encoded.texts,
                 tokens: encoded.tokens,
                 entities: encoded.entities,
+                sequence_ids: encoded.sequence_ids,
                 num_words: encoded.num_words,
             },
         })
     }
 
-    pub fn inputs() -> [&'static str; 6] {
+    pub fn inputs(names: &InputNames) -> [&str; 6] {
+        [
+            &names.input_ids,
+            &names.attention_mask,
+            &names.words_mask,
+            &names.text_lengths,
+            TENSOR_SPAN_IDX,
+            TENSOR_SPAN_MASK,
+        ]
+    }
+
+    /// Same as `inputs`, with the usual names
+    pub fn default_inputs() -> [&'static str; 6] {
         [
             TENSOR_INPUT_IDS,
             TENSOR_ATTENTION_MASK,
@@ -80,11 +92,11 @@ impl SpanTensors<'_> {
         let num_spans = encoded.num_words * max_width;
 
         // prepare output tensors (zero-filled, values will be set in place)
-        let mut span_idx = ndarray::Array::zeros((encoded.texts.len(), num_spans, 2));
-        let mut span_mask = ndarray::Array::from_elem((encoded.texts.len(), num_spans), false);
+        let mut span_idx = ndarray::Array::zeros((encoded.tokens.len(), num_spans, 2));
+        let mut span_mask = ndarray::Array::from_elem((encoded.tokens.len(), num_spans), false);
 
         // iterate over segments
-        for s in 0..encoded.texts.len() {
+        for s in 0..encoded.tokens.len() {
             // get the actual width of the current segment
             let text_width = *encoded.text_lengths.get((s, 0)).unwrap() as usize;
 
@@ -114,17 +126,27 @@ impl SpanTensors<'_> {
 /// Composable: Encoded => SpanTensors
 pub struct EncodedToTensors {
     max_width: usize,
+    names: InputNames,
 }
 
 impl EncodedToTensors {
     pub fn new(max_width: usize) -> Self {
-        Self { max_width }
+        Self {
+            max_width,
+            names: InputNames::default(),
+        }
+    }
+
+    /// Sets the names of the input tensors (default: the usual ones)
+    pub fn with_input_names(mut self, names: InputNames) -> Self {
+        self.names = names;
+        self
     }
 }
 
 impl<'a> Composable<EncodedInput, SpanTensors<'a>> for EncodedToTensors {
     fn apply(&self, input: EncodedInput) -> Result<SpanTensors<'a>> {
-        SpanTensors::from(input, self.max_width)
+        SpanTensors::from(input, self.max_width, &self.names)
     }
 }
 
@@ -163,7 +185,7 @@ mod tests {
             super::super::super::tokenized::TokenizedInput::from(input, &splitter, None)?;
         let prepared = super::super::super::prompt::PromptInput::from(tokenized);
         let encoded = EncodedInput::from(prepared, &tokenizer)?;
-        let spans = SpanTensors::from(encoded, 12)?;
+        let spans = SpanTensors::from(encoded, 12, &InputNames::default())?;
         let span_idx = get_tensor("span_idx", &spans.tensors)?;
         let span_idx = span_idx.try_extract_tensor::<i64>()?;
         let span_masks = get_tensor("span_mask", &spans.tensors)?;
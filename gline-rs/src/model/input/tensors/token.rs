@@ -1,14 +1,12 @@
 use super::super::super::pipeline::context::EntityContext;
 use super::super::encoded::EncodedInput;
+use super::{
+    InputNames, TENSOR_ATTENTION_MASK, TENSOR_INPUT_IDS, TENSOR_TEXT_LENGTHS, TENSOR_WORD_MASK,
+};
 use crate::util::result::Result;
 use composable::Composable;
 use ort::session::SessionInputs;
 
-const TENSOR_INPUT_IDS: &str = "input_ids";
-const TENSOR_ATTENTION_MASK: &str = "attention_mask";
-const TENSOR_WORD_MASK: &str = "words_mask";
-const TENSOR_TEXT_LENGTHS: &str = "text_lengths";
-
 /// Ready-for-inference tensors (token mode)
 pub struct TokenTensors<'a> {
     pub tensors: SessionInputs<'a, 'a>,
@@ -16,12 +14,12 @@ pub struct TokenTensors<'a> {
 }
 
 impl TokenTensors<'_> {
-    pub fn from(encoded: EncodedInput) -> Result<Self> {
+    pub fn from(encoded: EncodedInput, names: &InputNames) -> Result<Self> {
         let inputs = ort::inputs! {
-            TENSOR_INPUT_IDS => encoded.input_ids,
-            TENSOR_ATTENTION_MASK => encoded.attention_masks,
-            TENSOR_WORD_MASK => encoded.word_masks,
-            TENSOR_TEXT_LENGTHS => encoded.text_lengths,
+            names.input_ids.to_string() => encoded.input_ids,
+            names.attention_mask.to_string() => encoded.attention_masks,
+            names.words_mask.to_string() => encoded.word_masks,
+            names.text_lengths.to_string() => encoded.text_lengths,
         }?;
         Ok(Self {
             tensors: inputs.into(),
@@ -29,12 +27,23 @@ impl TokenTensors<'_> {
                 texts: encoded.texts,
                 tokens: encoded.tokens,
                 entities: encoded.entities,
+                sequence_ids: encoded.sequence_ids,
                 num_words: encoded.num_words,
             },
         })
     }
 
-    pub fn inputs() -> [&'static str; 4] {
+    pub fn inputs(names: &InputNames) -> [&str; 4] {
+        [
+            &names.input_ids,
+            &names.attention_mask,
+            &names.words_mask,
+            &names.text_lengths,
+        ]
+    }
+
+    /// Same as `inputs`, with the usual names
+    pub fn default_inputs() -> [&'static str; 4] {
         [
             TENSOR_INPUT_IDS,
             TENSOR_ATTENTION_MASK,
@@ -46,11 +55,21 @@ impl TokenTensors<'_> {
 
 /// Composable: Encoded => TokenTensors
 #[derive(Default)]
-pub struct EncodedToTensors {}
+pub struct EncodedToTensors {
+    names: InputNames,
+}
+
+impl EncodedToTensors {
+    /// Sets the names of the input tensors (default: the usual ones)
+    pub fn with_input_names(mut self, names: InputNames) -> Self {
+        self.names = names;
+        self
+    }
+}
 
 impl<'a> Composable<EncodedInput, TokenTensors<'a>> for EncodedToTensors {
     fn apply(&self, input: EncodedInput) -> Result<TokenTensors<'a>> {
-        TokenTensors::from(input)
+        TokenTensors::from(input, &self.names)
     }
 }
 
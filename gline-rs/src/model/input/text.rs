@@ -1,3 +1,4 @@
+use crate::text::token::Token;
 use crate::util::result::Result;
 use std::path::Path;
 
@@ -5,19 +6,86 @@ use std::path::Path;
 pub struct TextInput {
     pub texts: Vec<String>,
     pub entities: Vec<String>,
+    /// Word boundaries (as byte offsets) of each text, if already known (see `with_words`)
+    words: Option<Vec<Vec<(usize, usize)>>>,
 }
 
 impl TextInput {
     /// Default constructor that moves the input data given as a vector of the text
     /// sequences to be analyzed, and a vector of entity classes.
+    ///
+    /// Returns an error if no text or no entity class is given, or if an entity class is blank.
     pub fn new(texts: Vec<String>, entities: Vec<String>) -> Result<Self> {
-        if texts.is_empty() || entities.is_empty() {
-            Err("invalid input: empty texts and/or entities".into())
+        if texts.is_empty() {
+            Err("invalid input: at least one text is required".into())
+        } else if entities.is_empty() {
+            Err("invalid input: at least one entity class (label) is required".into())
+        } else if entities.iter().any(|entity| entity.trim().is_empty()) {
+            Err("invalid input: entity classes (labels) must not be blank".into())
         } else {
-            Ok(Self { texts, entities })
+            Ok(Self {
+                texts,
+                entities,
+                words: None,
+            })
         }
     }
 
+    /// Same as `new`, with explicit word boundaries (as byte offsets) for each text, so that the
+    /// splitter of the pipeline is not used. Entities will then be aligned on these words.
+    pub fn new_pretokenized(
+        texts: Vec<String>,
+        entities: Vec<String>,
+        words: Vec<Vec<(usize, usize)>>,
+    ) -> Result<Self> {
+        Self::new(texts, entities)?.with_words(words)
+    }
+
+    /// Sets explicit word boundaries (as byte offsets) for each text (see `new_pretokenized`).
+    ///
+    /// Returns an error if there is not one sequence of words per text, or if some offsets do not match the text.
+    pub fn with_words(mut self, words: Vec<Vec<(usize, usize)>>) -> Result<Self> {
+        if words.len() != self.texts.len() {
+            return Err(format!(
+                "invalid input: got {} texts but {} sequences of words",
+                self.texts.len(),
+                words.len()
+            )
+            .into());
+        }
+        for (text, words) in self.texts.iter().zip(&words) {
+            for &(start, end) in words {
+                if start >= end || text.get(start..end).is_none() {
+                    return Err(format!(
+                        "invalid input: word offsets ({start}, {end}) do not match the text"
+                    )
+                    .into());
+                }
+            }
+        }
+        self.words = Some(words);
+        Ok(self)
+    }
+
+    /// Whether word boundaries were given for the texts (see `with_words`)
+    pub fn is_pretokenized(&self) -> bool {
+        self.words.is_some()
+    }
+
+    /// Returns the words of the sequence at the given index if they were provided, `None` otherwise
+    /// (at most `limit` words if set).
+    pub fn words(&self, index: usize, limit: Option<usize>) -> Option<Vec<Token>> {
+        let words = self.words.as_ref()?.get(index)?;
+        let text = self.texts.get(index)?;
+        Some(
+            words
+                .iter()
+                .take(limit.unwrap_or(usize::MAX))
+                .map(|&(start, end)| Token::new(start, end, &text[start..end]))
+                .collect(),
+        )
+    }
+
     /// This constructor will mostly be used to test with plain arrays of static `str`s.
     pub fn from_str(texts: &[&str], entities: &[&str]) -> Result<Self> {
         Self::new(
@@ -42,4 +110,127 @@ impl TextInput {
             .collect();
         Self::new(texts, entities)
     }
+
+    /// Builds inputs for texts having each their own set of entity classes (given as parallel vectors).
+    ///
+    /// Texts sharing the same set of classes are grouped into the same input, so that they can still be
+    /// processed in one batch. Each input comes with the indices of its texts in the original vector.
+    pub fn group_by_entities(
+        texts: Vec<String>,
+        entities: Vec<Vec<String>>,
+    ) -> Result<Vec<(Self, Vec<usize>)>> {
+        if texts.is_empty() {
+            return Err("invalid input: at least one text is required".into());
+        }
+        if texts.len() != entities.len() {
+            return Err(format!(
+                "invalid input: got {} texts but {} sets of entities",
+                texts.len(),
+                entities.len()
+            )
+            .into());
+        }
+        let mut groups: Vec<(Vec<String>, Vec<String>, Vec<usize>)> = Vec::new();
+        for (index, (text, entities)) in texts.into_iter().zip(entities).enumerate() {
+            match groups.iter_mut().find(|(e, _, _)| *e == entities) {
+                Some((_, texts, indices)) => {
+                    texts.push(text);
+                    indices.push(index);
+                }
+                None => groups.push((entities, vec![text], vec![index])),
+            }
+        }
+        groups
+            .into_iter()
+            .map(|(entities, texts, indices)| Ok((Self::new(texts, entities)?, indices)))
+            .collect()
+    }
+
+    /// Splits the input into sub-batches of at most `batch_size` texts each (preserving order),
+    /// all of them sharing the same entity classes.
+    pub fn into_batches(self, batch_size: usize) -> Result<Vec<Self>> {
+        if batch_size == 0 {
+            return Err("invalid batch size: must be greater than 0".into());
+        }
+        let mut words = self.words.as_ref().map(|words| words.chunks(batch_size));
+        Ok(self
+            .texts
+            .chunks(batch_size)
+            .map(|texts| Self {
+                texts: texts.to_vec(),
+                entities: self.entities.clone(),
+                words: words.as_mut().and_then(Iterator::next).map(<[_]>::to_vec),
+            })
+            .collect())
+    }
+
+    /// Same as `into_batches`, but texts are first sorted by length, so that each sub-batch holds texts
+    /// of similar lengths (which reduces padding). Each sub-batch comes with the indices of its texts
+    /// in the original input.
+    ///
+    /// The length of a text is its number of words if pre-tokenized, and its length in bytes otherwise.
+    pub fn into_length_buckets(self, batch_size: usize) -> Result<Vec<(Self, Vec<usize>)>> {
+        let mut indices: Vec<usize> = (0..self.texts.len()).collect();
+        indices.sort_by_key(|&i| match &self.words {
+            Some(words) => words[i].len(),
+            None => self.texts[i].len(),
+        });
+        let sorted = Self {
+            texts: indices.iter().map(|&i| self.texts[i].clone()).collect(),
+            entities: self.entities,
+            words: self
+                .words
+                .map(|words| indices.iter().map(|&i| words[i].clone()).collect()),
+        };
+        Ok(sorted
+            .into_batches(batch_size)?
+            .into_iter()
+            .zip(indices.chunks(batch_size).map(<[_]>::to_vec))
+            .collect())
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_input() {
+        assert!(TextInput::from_str(&["a"], &["person"]).is_ok());
+        assert!(TextInput::from_str(&[], &["person"]).is_err());
+        assert!(TextInput::from_str(&["a"], &[]).is_err());
+        assert!(TextInput::from_str(&["a"], &["person", " "]).is_err());
+        assert!(TextInput::group_by_entities(vec![], vec![]).is_err());
+    }
+
+    #[test]
+    fn test_group_by_entities() -> Result<()> {
+        let texts = ["a", "b", "c"].map(String::from).to_vec();
+        let entities = vec![
+            vec!["person".to_string()],
+            vec!["city".to_string()],
+            vec!["person".to_string()],
+        ];
+        let groups = TextInput::group_by_entities(texts, entities)?;
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].0.texts, vec!["a", "c"]);
+        assert_eq!(groups[0].1, vec![0, 2]);
+        assert_eq!(groups[1].0.entities, vec!["city"]);
+        assert_eq!(groups[1].1, vec![1]);
+        assert!(TextInput::group_by_entities(vec!["a".to_string()], vec![]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_length_buckets() -> Result<()> {
+        let input = TextInput::from_str(&["long text", "a", "longest text", "ab"], &["person"])?;
+        let buckets = input.into_length_buckets(2)?;
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[0].0.texts, vec!["a", "ab"]);
+        assert_eq!(buckets[0].1, vec![1, 3]);
+        assert_eq!(buckets[1].0.texts, vec!["long text", "longest text"]);
+        assert_eq!(buckets[1].1, vec![0, 2]);
+        Ok(())
+    }
 }
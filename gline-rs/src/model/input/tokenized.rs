@@ -1,9 +1,22 @@
+use super::normalizer::{Normalization, NormalizingSplitter};
 use super::text::TextInput;
 use crate::text::splitter::Splitter;
 use crate::text::token::Token;
 use crate::util::result::Result;
 use composable::Composable;
 
+/// What to do when a sequence is longer than `max_length` words (and windowing is not enabled)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TruncationPolicy {
+    /// Extra words are silently dropped
+    #[default]
+    Silent,
+    /// Extra words are dropped, and a warning tells how many of them for each truncated sequence
+    Warn,
+    /// An error tells which sequence is too long, and by how many words
+    Error,
+}
+
 /// Represents the output of the word-level segmentation
 pub struct TokenizedInput {
     /// Tokens
@@ -12,6 +25,8 @@ pub struct TokenizedInput {
     pub texts: Vec<String>,
     /// Original entities
     pub entities: Vec<String>,
+    /// Original sequence (index in `texts`) of each token sequence
+    pub sequence_ids: Vec<usize>,
 }
 
 impl TokenizedInput {
@@ -21,15 +36,95 @@ impl TokenizedInput {
         max_length: Option<usize>,
     ) -> Result<Self> {
         // leverage the given `Splitter` to tokenize each input sequence
+        // unless the words are already known
+        let mut tokens = Vec::with_capacity(input.texts.len());
+        for (i, s) in input.texts.iter().enumerate() {
+            tokens.push(match input.words(i, max_length) {
+                Some(words) => words,
+                None => splitter.split(s, max_length)?,
+            });
+        }
+
+        Ok(Self {
+            sequence_ids: (0..tokens.len()).collect(),
+            tokens,
+            texts: input.texts,
+            entities: input.entities,
+        })
+    }
+
+    /// Same as `from`, applying the given policy to the sequences longer than `max_length` (which requires
+    /// to split the whole texts, instead of stopping at `max_length` words)
+    pub fn from_with_truncation(
+        input: TextInput,
+        splitter: &impl Splitter,
+        max_length: Option<usize>,
+        truncation: TruncationPolicy,
+    ) -> Result<Self> {
+        let max_length = match (max_length, truncation) {
+            (Some(max_length), TruncationPolicy::Warn | TruncationPolicy::Error) => max_length,
+            _ => return Self::from(input, splitter, max_length),
+        };
+        let mut result = Self::from(input, splitter, None)?;
+        for (sequence_id, tokens) in result.tokens.iter_mut().enumerate() {
+            if tokens.len() <= max_length {
+                continue;
+            }
+            let overflow = tokens.len() - max_length;
+            if truncation == TruncationPolicy::Error {
+                return Err(format!(
+                    "sequence {sequence_id} exceeds max_length ({max_length}) by {overflow} words"
+                )
+                .into());
+            }
+            log::warn!(
+                "sequence {sequence_id} truncated to max_length ({max_length}): {overflow} words dropped"
+            );
+            tokens.truncate(max_length);
+        }
+        Ok(result)
+    }
+
+    /// Same as `from`, but sequences longer than `max_length` are split into overlapping
+    /// windows of at most `max_length` tokens instead of being truncated. Consecutive
+    /// windows share `stride` tokens. Token offsets remain relative to the original text.
+    pub fn from_windows(
+        input: TextInput,
+        splitter: &impl Splitter,
+        max_length: usize,
+        stride: usize,
+    ) -> Result<Self> {
+        if stride >= max_length {
+            return Err(format!(
+                "invalid parameters: stride ({stride}) must be lower than max_length ({max_length})"
+            )
+            .into());
+        }
+
         let mut tokens = Vec::with_capacity(input.texts.len());
-        for s in &input.texts {
-            tokens.push(splitter.split(s, max_length)?);
+        let mut sequence_ids = Vec::with_capacity(input.texts.len());
+        for (sequence_id, s) in input.texts.iter().enumerate() {
+            let sequence = match input.words(sequence_id, None) {
+                Some(words) => words,
+                None => splitter.split(s, None)?,
+            };
+            let mut start = 0;
+            loop {
+                let end = std::cmp::min(start + max_length, sequence.len());
+                tokens.push(sequence[start..end].to_vec());
+                sequence_ids.push(sequence_id);
+                if end == sequence.len() {
+                    break;
+                }
+                start = end - stride;
+            }
         }
 
         Ok(Self {
             tokens,
             texts: input.texts,
             entities: input.entities,
+            sequence_ids,
         })
     }
 }
@@ -38,6 +133,9 @@ impl TokenizedInput {
 pub struct RawToTokenized<'a, S> {
     splitter: &'a S,
     max_length: Option<usize>,
+    stride: Option<usize>,
+    normalization: Option<Normalization>,
+    truncation: TruncationPolicy,
 }
 
 impl<'a, S> RawToTokenized<'a, S> {
@@ -45,13 +143,69 @@ impl<'a, S> RawToTokenized<'a, S> {
         Self {
             splitter,
             max_length,
+            stride: None,
+            normalization: None,
+            truncation: TruncationPolicy::Silent,
+        }
+    }
+
+    /// Enables windowing (see `TokenizedInput::from_windows`), provided that `max_length` is set
+    pub fn with_stride(mut self, stride: Option<usize>) -> Self {
+        self.stride = stride;
+        self
+    }
+
+    /// Normalizes the texts before splitting them (see `NormalizingSplitter`), unless words are given
+    pub fn with_normalization(mut self, normalization: Option<Normalization>) -> Self {
+        self.normalization = normalization;
+        self
+    }
+
+    /// Sets what to do with sequences longer than `max_length` if windowing is not enabled (default: silent truncation)
+    pub fn with_truncation(mut self, truncation: TruncationPolicy) -> Self {
+        self.truncation = truncation;
+        self
+    }
+
+    fn tokenize(&self, input: TextInput, splitter: &impl Splitter) -> Result<TokenizedInput> {
+        match (self.max_length, self.stride) {
+            (Some(max_length), Some(stride)) => {
+                TokenizedInput::from_windows(input, splitter, max_length, stride)
+            }
+            _ => TokenizedInput::from_with_truncation(
+                input,
+                splitter,
+                self.max_length,
+                self.truncation,
+            ),
         }
     }
 }
 
+/// Returns the words of the given text as split by the given splitter, with their offsets in characters
+/// (instead of bytes), which is mostly useful to inspect word boundaries (e.g. to diagnose unexpected entity offsets)
+pub fn preview(splitter: &impl Splitter, text: &str) -> Result<Vec<(String, usize, usize)>> {
+    let mut chars = 0;
+    let mut bytes = 0;
+    let mut result = Vec::new();
+    for token in splitter.split(text, None)? {
+        let char_start = chars + text[bytes..token.start()].chars().count();
+        let char_end = char_start + text[token.start()..token.end()].chars().count();
+        result.push((token.text().to_string(), char_start, char_end));
+        (chars, bytes) = (char_end, token.end());
+    }
+    Ok(result)
+}
+
 impl<S: Splitter> Composable<TextInput, TokenizedInput> for RawToTokenized<'_, S> {
     fn apply(&self, input: TextInput) -> Result<TokenizedInput> {
-        TokenizedInput::from(input, self.splitter, self.max_length)
+        match self.normalization {
+            Some(normalization) => self.tokenize(
+                input,
+                &NormalizingSplitter::new(self.splitter, normalization),
+            ),
+            None => self.tokenize(input, self.splitter),
+        }
     }
 }
 
@@ -95,4 +249,81 @@ mod tests {
         // Everything rules
         Ok(())
     }
+
+    #[test]
+    fn test_preview() -> Result<()> {
+        let splitter = crate::text::splitter::RegexSplitter::default();
+        let words = preview(&splitter, "Élise lives in Zürich")?;
+        assert_eq!(words.len(), 4);
+        assert_eq!(words[0], ("Élise".to_string(), 0, 5));
+        assert_eq!(words[3], ("Zürich".to_string(), 15, 21));
+        Ok(())
+    }
+
+    #[test]
+    fn test_windows() -> Result<()> {
+        #![allow(clippy::unwrap_used)]
+        let splitter = crate::text::splitter::RegexSplitter::default();
+        let batch = ["w1 w2 w3 w4 w5 w6 w7", "short one"];
+        let entities = ["person"];
+        let input = TextInput::from_str(&batch, &entities)?;
+        let tokenized = TokenizedInput::from_windows(input, &splitter, 4, 1)?;
+        // windows: [w1..w4], [w4..w7], [short one]
+        assert_eq!(tokenized.tokens.len(), 3);
+        assert_eq!(tokenized.sequence_ids, vec![0, 0, 1]);
+        let second = tokenized.tokens.get(1).unwrap();
+        assert_eq!(second.len(), 4);
+        assert_eq!(second.first().unwrap().text(), "w4");
+        assert_eq!(second.first().unwrap().start(), 9);
+        assert_eq!(second.last().unwrap().end(), batch[0].len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_windows_invalid_stride() -> Result<()> {
+        let splitter = crate::text::splitter::RegexSplitter::default();
+        let input = TextInput::from_str(&["some text"], &["person"])?;
+        assert!(TokenizedInput::from_windows(input, &splitter, 4, 4).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_pretokenized() -> Result<()> {
+        let splitter = crate::text::splitter::RegexSplitter::default();
+        let input = TextInput::new_pretokenized(
+            vec!["New-York City".to_string()],
+            vec!["city".to_string()],
+            vec![vec![(0, 3), (4, 8), (9, 13)]],
+        )?;
+        let tokenized = TokenizedInput::from(input, &splitter, Some(2))?;
+        let words: Vec<&str> = tokenized.tokens[0].iter().map(|t| t.text()).collect();
+        assert_eq!(words, vec!["New", "York"]);
+        assert!(TextInput::new_pretokenized(
+            vec!["New York".to_string()],
+            vec!["city".to_string()],
+            vec![vec![(4, 9)]],
+        )
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_truncation_policy() -> Result<()> {
+        let splitter = crate::text::splitter::RegexSplitter::default();
+        let input = || TextInput::from_str(&["short", "this one is too long"], &["person"]);
+        let tokenized =
+            |policy| TokenizedInput::from_with_truncation(input()?, &splitter, Some(3), policy);
+        for policy in [TruncationPolicy::Silent, TruncationPolicy::Warn] {
+            let lengths: Vec<usize> = tokenized(policy)?.tokens.iter().map(Vec::len).collect();
+            assert_eq!(lengths, vec![1, 3]);
+        }
+        let error = tokenized(TruncationPolicy::Error)
+            .err()
+            .map(|e| e.to_string());
+        assert_eq!(
+            error.as_deref(),
+            Some("sequence 1 exceeds max_length (3) by 2 words")
+        );
+        Ok(())
+    }
 }
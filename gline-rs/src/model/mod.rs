@@ -2,21 +2,39 @@
 
 pub mod config;
 pub mod gliner2;
+pub mod info;
 pub mod input;
 pub mod output;
 pub mod params;
 pub mod pipeline;
 pub mod runtime;
+pub mod session;
 
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::io::BufRead;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
+use crate::text::span::Span;
+use crate::text::splitter::Splitter;
+use crate::text::tokenizer::Tokenizer;
 use crate::util::result::Result;
+use crate::util::timing::{Stage, StageTimings, Timings};
 use config::{ConfigMode, ModelConfig};
-use orp::model::Model;
-use orp::params::RuntimeParameters;
+use info::{ComponentReport, ModelDirReport, ModelInfo};
+use input::relation::schema::RelationSchema;
+use input::stream::TextWindows;
+use input::tensors::InputNames;
+use input::text::TextInput;
 use orp::pipeline::Pipeline;
+use output::decoded::SpanOutput;
+use output::relation::RelationOutput;
 use params::Parameters;
+use pipeline::context::EntityContext;
+use pipeline::relation::RelationPipeline;
 use runtime::InferenceMode;
+use session::{Model, SessionParameters};
 
 /// Basic GLiNER, to be parametrized by a specific pipeline (see implementations within the pipeline module)
 ///
@@ -25,25 +43,458 @@ pub struct GLiNER<P> {
     params: Parameters,
     model: Model,
     pipeline: P,
+    known_labels: Option<Vec<String>>,
 }
 
 impl<P> GLiNER<P> {
     pub fn get_inner_model(&self) -> &Model {
         &self.model
     }
+
+    pub fn get_parameters(&self) -> &Parameters {
+        &self.params
+    }
+
+    pub fn get_pipeline(&self) -> &P {
+        &self.pipeline
+    }
+
+    /// Names, element types and shapes of the actual inputs and outputs of the ONNX model, as read when loading it
+    pub fn model_info(&self) -> &info::ModelInfo {
+        self.model.info()
+    }
+
+    /// Names of the actual inputs of the ONNX model (see `model_info`)
+    pub fn input_names(&self) -> Vec<&str> {
+        self.model.info().input_names().collect()
+    }
+
+    /// Names of the actual outputs of the ONNX model (see `model_info`)
+    pub fn output_names(&self) -> Vec<&str> {
+        self.model.info().output_names().collect()
+    }
+
+    /// Entity labels declared in the model configuration, if any (see `ModelConfig::labels`)
+    pub fn known_labels(&self) -> Option<&[String]> {
+        self.known_labels.as_deref()
+    }
+}
+
+impl<'a, P: Pipeline<'a>> GLiNER<P> {
+    /// Loads the ONNX model (see `load_model`), and checks that it provides the tensors expected
+    /// by the pipeline (the error lists the actual ones otherwise)
+    fn load<M: AsRef<Path>>(
+        params: Parameters,
+        runtime_parameters: impl Into<SessionParameters>,
+        model_path: M,
+        pipeline: P,
+        known_labels: Option<Vec<String>>,
+    ) -> Result<Self> {
+        let model = load_model(model_path, runtime_parameters)?;
+        model.check_schema(&pipeline)?;
+        Ok(Self {
+            params: params.for_model(model.info()),
+            model,
+            pipeline,
+            known_labels,
+        })
+    }
+
+    /// Same as `load`, given the contents of the ONNX model
+    fn load_from_bytes(
+        params: Parameters,
+        runtime_parameters: impl Into<SessionParameters>,
+        model_bytes: &[u8],
+        pipeline: P,
+        known_labels: Option<Vec<String>>,
+    ) -> Result<Self> {
+        let model = load_model_from_bytes(model_bytes, runtime_parameters)?;
+        model.check_schema(&pipeline)?;
+        Ok(Self {
+            params: params.for_model(model.info()),
+            model,
+            pipeline,
+            known_labels,
+        })
+    }
 }
 
 impl<'a, P: Pipeline<'a, Parameters = Parameters>> GLiNER<P> {
     pub fn inference(&'a self, input: P::Input) -> Result<P::Output> {
-        self.model.inference(input, &self.pipeline, &self.params)
+        self.inference_with_params(input, &self.params)
+    }
+
+    /// Same as `inference`, but overrides the parameters given at construction time for this call only
+    pub fn inference_with_params(
+        &'a self,
+        input: P::Input,
+        params: &Parameters,
+    ) -> Result<P::Output> {
+        params.validate()?;
+        // parameters built without the model do not know yet whether it is quantized
+        let params = match params.quantized {
+            Some(_) => Cow::Borrowed(params),
+            None => Cow::Owned(params.clone().for_model(self.model.info())),
+        };
+        let params = params.as_ref();
+        let Some(timings) = &params.timings else {
+            return self.model.inference(input, &self.pipeline, params);
+        };
+        // the session duration is what remains once the durations of the other stages are deducted
+        let from = timings.len();
+        let start = std::time::Instant::now();
+        let output = self.model.inference(input, &self.pipeline, params)?;
+        timings.record_remainder(Stage::Session, start.elapsed(), from);
+        Ok(output)
+    }
+}
+
+impl<'a, P> GLiNER<P>
+where
+    P: Pipeline<'a, Input = TextInput, Output = SpanOutput, Parameters = Parameters>,
+{
+    /// Performs NER on the given texts, looking for entities of the given classes (shorthand for building
+    /// the `TextInput` and calling `inference_in_batches` with the parameters given at construction time)
+    pub fn predict(&'a self, texts: &[&str], labels: &[&str]) -> Result<SpanOutput> {
+        self.inference_in_batches(TextInput::from_str(texts, labels)?, &self.params)
+    }
+
+    /// Same as `inference_in_batches` with the parameters given at construction time, also returning the
+    /// time spent in each phase of the pipeline (summed over all batches)
+    pub fn inference_timed(&'a self, input: TextInput) -> Result<(SpanOutput, StageTimings)> {
+        let timings = Arc::new(Timings::default());
+        let params = self.params.clone().with_timings(Some(timings.clone()));
+        let output = self.inference_in_batches(input, &params)?;
+        Ok((output, StageTimings::from_timings(&timings)))
+    }
+
+    /// Same as `predict`, for a single text: returns its spans directly
+    pub fn predict_entity(&'a self, text: &str, labels: &[&str]) -> Result<Vec<Span>> {
+        let output = self.predict(&[text], labels)?;
+        Ok(output.spans.into_iter().next().unwrap_or_default())
+    }
+
+    /// Same as `inference_with_params`, but processes the texts by sub-batches if `batch_size` is set
+    /// in the parameters, and concatenates the results (preserving order). With `bucket_by_length`,
+    /// texts of similar lengths are processed together (see `TextInput::into_length_buckets`).
+    pub fn inference_in_batches(
+        &'a self,
+        input: TextInput,
+        params: &Parameters,
+    ) -> Result<SpanOutput> {
+        self.inference_in_batches_with_progress(input, params, &mut |_, _| Ok(()))
+    }
+
+    /// Same as `inference_in_batches`, calling `progress(completed, total)` (numbers of texts) after each
+    /// sub-batch, e.g. to drive a progress bar. An error returned by `progress` aborts the inference.
+    pub fn inference_in_batches_with_progress(
+        &'a self,
+        input: TextInput,
+        params: &Parameters,
+        progress: &mut (dyn FnMut(usize, usize) -> Result<()> + Send),
+    ) -> Result<SpanOutput> {
+        params.validate()?;
+        let total = input.texts.len();
+        let batch_size = match params.batch_size {
+            Some(batch_size) if batch_size < total => batch_size,
+            _ => {
+                let output = self.inference_with_params(input, params)?;
+                progress(total, total)?;
+                return Ok(output);
+            }
+        };
+        if params.bucket_by_length {
+            let mut all = empty_output(total, input.entities.clone());
+            let mut completed = 0;
+            for (batch, indices) in input.into_length_buckets(batch_size)? {
+                let output = self.inference_with_params(batch, params)?;
+                completed += indices.len();
+                scatter_output(output, indices, &mut all);
+                progress(completed, total)?;
+            }
+            return Ok(all);
+        }
+        let mut batches = input.into_batches(batch_size)?.into_iter();
+        let mut output = match batches.next() {
+            Some(batch) => self.inference_with_params(batch, params)?,
+            None => return Err("invalid input: empty texts".into()),
+        };
+        progress(output.texts.len(), total)?;
+        for batch in batches {
+            output.append(self.inference_with_params(batch, params)?);
+            progress(output.texts.len(), total)?;
+        }
+        Ok(output)
+    }
+
+    /// Performs NER on the texts pulled lazily from the given iterator (e.g. the receiving end of a channel), by
+    /// batches of `batch_size` texts, with the parameters given at construction time.
+    ///
+    /// The spans of each text are yielded in order, and the next batch is only pulled from the source once the
+    /// results of the current one have all been consumed, so that the corpus is never buffered as a whole. If
+    /// the inference of a batch fails, an error is yielded for each of its texts, and the next batches are still
+    /// processed. Returns an error if `batch_size` is 0.
+    pub fn predict_stream<I, T>(
+        &'a self,
+        texts: I,
+        labels: &[&str],
+        batch_size: usize,
+    ) -> Result<impl Iterator<Item = Result<Vec<Span>>> + 'a>
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: 'a,
+        T: AsRef<str>,
+    {
+        let labels: Vec<String> = labels.iter().map(|label| label.to_string()).collect();
+        stream_batches(texts, batch_size, move |batch| {
+            let input = TextInput::new(batch, labels.clone())?;
+            Ok(self.inference_in_batches(input, &self.params)?.spans)
+        })
+    }
+
+    /// Performs NER on a (possibly huge) document read incrementally from the given reader, so that memory usage
+    /// is bounded by the size of the windows rather than the one of the document.
+    ///
+    /// The document is cut into windows of `max_length` words sharing `stride` words (see `TextWindows`), which
+    /// are processed by sub-batches of `batch_size` windows (default: 1). The spans are relative to the whole
+    /// document (as sequence 0), and an entity found in two overlapping windows is reported once (see `window_merge`).
+    pub fn inference_stream<R: BufRead>(
+        &'a self,
+        reader: R,
+        splitter: &impl Splitter,
+        labels: &[&str],
+        params: &Parameters,
+    ) -> Result<Vec<Span>> {
+        let max_length = params
+            .max_length
+            .ok_or("invalid parameters: streaming requires max_length to be set")?;
+        let mut windows =
+            TextWindows::new(reader, splitter, max_length, params.stride.unwrap_or(0))?;
+        let batch_size = params.batch_size.unwrap_or(1).max(1);
+        let labels: Vec<String> = labels.iter().map(|label| label.to_string()).collect();
+        // spans ending before the current window cannot be merged with the ones of the next windows
+        let mut result = Vec::new();
+        let mut pending: Vec<Span> = Vec::new();
+        loop {
+            let batch = windows
+                .by_ref()
+                .take(batch_size)
+                .collect::<Result<Vec<_>>>()?;
+            if batch.is_empty() {
+                break;
+            }
+            let offsets: Vec<usize> = batch.iter().map(|window| window.offset).collect();
+            let texts = batch.into_iter().map(|window| window.text).collect();
+            let output =
+                self.inference_with_params(TextInput::new(texts, labels.clone())?, params)?;
+            for (offset, spans) in offsets.into_iter().zip(output.spans) {
+                let (done, kept): (Vec<Span>, Vec<Span>) = pending
+                    .into_iter()
+                    .partition(|span| span.offsets().1 <= offset);
+                result.extend(done);
+                pending = kept;
+                params.window_merge.merge(
+                    &mut pending,
+                    spans
+                        .into_iter()
+                        .map(|span| span.with_sequence(0).with_offset(offset))
+                        .collect(),
+                );
+            }
+        }
+        result.extend(pending);
+        result.sort_unstable();
+        Ok(result)
+    }
+
+    /// Performs NER and then relation extraction, re-using the pipeline and the model already loaded
+    /// for NER (i.e. without loading the tokenizer or the model again).
+    pub fn extract_relations(
+        &'a self,
+        input: TextInput,
+        relation_schema: &'a RelationSchema,
+        params: &Parameters,
+    ) -> Result<RelationOutput>
+    where
+        P: Clone + Pipeline<'a, Context = EntityContext>,
+    {
+        params.validate()?;
+        let params = &params.clone().with_keep_sub_threshold(false);
+        let entities = self.inference_in_batches(input, params)?;
+        self.extract_relations_from_entities(entities, relation_schema, params)
+    }
+
+    /// Performs relation extraction only, given already known entities (for example curated ones,
+    /// see `SpanOutput::from_entities`) instead of the ones found by NER.
+    pub fn extract_relations_from_entities(
+        &'a self,
+        entities: SpanOutput,
+        relation_schema: &'a RelationSchema,
+        params: &Parameters,
+    ) -> Result<RelationOutput>
+    where
+        P: Clone + Pipeline<'a, Context = EntityContext>,
+    {
+        params.validate()?;
+        let params = &params.clone().with_keep_sub_threshold(false);
+        self.model.inference(
+            entities,
+            &RelationPipeline::borrowed(&self.pipeline, relation_schema),
+            params,
+        )
+    }
+
+    /// Performs inference on texts having each their own set of entity classes (given as parallel vectors).
+    ///
+    /// Texts sharing the same classes are processed together (see `TextInput::group_by_entities`). In the
+    /// output, spans keep the order of the input texts, and `entities` is the union of all classes.
+    pub fn inference_with_entities(
+        &'a self,
+        texts: Vec<String>,
+        entities: Vec<Vec<String>>,
+        params: &Parameters,
+    ) -> Result<SpanOutput> {
+        self.inference_with_entities_with_progress(texts, entities, params, &mut |_, _| Ok(()))
+    }
+
+    /// Same as `inference_with_entities`, calling `progress(completed, total)` after each sub-batch
+    /// (see `inference_in_batches_with_progress`)
+    pub fn inference_with_entities_with_progress(
+        &'a self,
+        texts: Vec<String>,
+        entities: Vec<Vec<String>>,
+        params: &Parameters,
+        progress: &mut (dyn FnMut(usize, usize) -> Result<()> + Send),
+    ) -> Result<SpanOutput> {
+        let total = texts.len();
+        let mut all = empty_output(total, Vec::new());
+        let mut completed = 0;
+        for (input, indices) in TextInput::group_by_entities(texts, entities)? {
+            for entity in &input.entities {
+                if !all.entities.contains(entity) {
+                    all.entities.push(entity.clone());
+                }
+            }
+            let output =
+                self.inference_in_batches_with_progress(input, params, &mut |done, _| {
+                    progress(completed + done, total)
+                })?;
+            completed += indices.len();
+            scatter_output(output, indices, &mut all);
+        }
+        Ok(all)
+    }
+}
+
+/// Moves the texts, spans, token counts and words of a partial output to their original positions (given by `indices`)
+fn scatter_output(output: SpanOutput, indices: Vec<usize>, all: &mut SpanOutput) {
+    let token_counts = output
+        .token_counts
+        .into_iter()
+        .map(Some)
+        .chain(std::iter::repeat(None));
+    let word_offsets = output
+        .word_offsets
+        .into_iter()
+        .map(Some)
+        .chain(std::iter::repeat(None));
+    for ((((index, text), spans), token_count), words) in indices
+        .into_iter()
+        .zip(output.texts)
+        .zip(output.spans)
+        .zip(token_counts)
+        .zip(word_offsets)
+    {
+        all.texts[index] = text;
+        all.spans[index] = spans
+            .into_iter()
+            .map(|span| span.with_sequence(index))
+            .collect();
+        all.token_counts[index] = token_count.unwrap_or_default();
+        all.word_offsets[index] = words.unwrap_or_default();
+    }
+}
+
+/// Output of `len` empty sequences (to be filled by `scatter_output`)
+fn empty_output(len: usize, entities: Vec<String>) -> SpanOutput {
+    SpanOutput::new(vec![String::new(); len], entities, vec![Vec::new(); len])
+        .with_token_counts(vec![0; len])
+        .with_word_offsets(vec![Vec::new(); len])
+}
+
+/// Locations where the ONNX model is searched for (relative to the model directory), if not specified
+pub const ONNX_MODEL_PATHS: [&str; 3] =
+    ["onnx/model.onnx", "model.onnx", "onnx/model_quantized.onnx"];
+
+impl<S: Splitter, T: Tokenizer> GLiNER<pipeline::span::SpanPipeline<S, T>> {
+    /// Performs NER once and returns the outputs at each of the given thresholds (in the same order), which is
+    /// much cheaper than one inference per threshold, e.g. to draw precision/recall curves or to tune the threshold.
+    ///
+    /// Spans are decoded whatever their probability (see `keep_sub_threshold`), and each output keeps the ones
+    /// reaching its threshold (see `SpanOutput::at_threshold`). Since spans are scored independently in span mode,
+    /// and overlap resolution only discards spans in favor of more probable ones, this gives the same entities as
+    /// separate inferences, except with `merge_adjacent` (merged spans getting the mean probability of their parts).
+    /// This is not the case in token mode, where the extent of the spans depends on the threshold, hence this
+    /// method being restricted to span pipelines. Since every candidate span is decoded, consider setting
+    /// `batch_size` to bound memory usage.
+    pub fn predict_multi_threshold(
+        &self,
+        texts: &[&str],
+        labels: &[&str],
+        thresholds: &[f32],
+    ) -> Result<Vec<SpanOutput>> {
+        if thresholds.is_empty() {
+            return Err("invalid parameters: at least one threshold is required".into());
+        }
+        if let Some(threshold) = thresholds.iter().find(|t| !(0.0..=1.0).contains(*t)) {
+            return Err(format!(
+                "invalid parameters: threshold must be within [0, 1], got {threshold}"
+            )
+            .into());
+        }
+        let params = self.params.clone().with_keep_sub_threshold(true);
+        let output = self.inference_in_batches(TextInput::from_str(texts, labels)?, &params)?;
+        Ok(thresholds
+            .iter()
+            .map(|threshold| output.at_threshold(*threshold))
+            .collect())
     }
 }
 
 impl GLiNER<()> {
+    /// Returns the path of the ONNX model within the given model directory.
+    ///
+    /// If `onnx_model_path` is set, it is relative to the model directory (unless absolute), otherwise
+    /// the first existing location among `ONNX_MODEL_PATHS` is returned. In both cases the resulting
+    /// path must exist, otherwise an error tells which locations were searched.
+    pub fn resolve_onnx_model_path<P: AsRef<Path>>(
+        model_dir: P,
+        onnx_model_path: Option<&str>,
+    ) -> Result<PathBuf> {
+        let model_dir = model_dir.as_ref();
+        if onnx_model_path.is_some() {
+            let path = resolve_component_path(model_dir, onnx_model_path, "");
+            validate_required_file("ONNX model", &path)?;
+            return Ok(path);
+        }
+        ONNX_MODEL_PATHS
+            .iter()
+            .map(|path| model_dir.join(path))
+            .find(|path| path.is_file())
+            .ok_or_else(|| {
+                format!(
+                    "missing required ONNX model file in {} (searched: {})",
+                    model_dir.display(),
+                    ONNX_MODEL_PATHS.join(", ")
+                )
+                .into()
+            })
+    }
+
     pub fn from_dir<P: AsRef<Path>>(
         model_dir: P,
         parameters: Parameters,
-        runtime_parameters: RuntimeParameters,
+        runtime_parameters: impl Into<SessionParameters>,
     ) -> Result<InferenceMode> {
         Self::from_dir_with(model_dir, parameters, runtime_parameters, None, None, None)
     }
@@ -51,7 +502,7 @@ impl GLiNER<()> {
     pub fn from_dir_with<P: AsRef<Path>>(
         model_dir: P,
         parameters: Parameters,
-        runtime_parameters: RuntimeParameters,
+        runtime_parameters: impl Into<SessionParameters>,
         tokenizer_path: Option<&str>,
         onnx_model_path: Option<&str>,
         config_path: Option<&str>,
@@ -59,35 +510,282 @@ impl GLiNER<()> {
         let model_dir = model_dir.as_ref();
 
         let tokenizer_path = resolve_component_path(model_dir, tokenizer_path, "tokenizer.json");
-        let onnx_model_path = resolve_component_path(model_dir, onnx_model_path, "onnx/model.onnx");
+        let onnx_model_path = Self::resolve_onnx_model_path(model_dir, onnx_model_path)?;
         let config_path = resolve_component_path(model_dir, config_path, "gliner_config.json");
 
-        validate_required_file("tokenizer", &tokenizer_path)?;
-        validate_required_file("ONNX model", &onnx_model_path)?;
+        // without `tokenizer.json`, the tokenizer can still be assembled from `vocab.txt` (see `HFTokenizer::from_path`)
+        if !tokenizer_path.with_file_name("vocab.txt").is_file() {
+            validate_required_file("tokenizer", &tokenizer_path)?;
+        }
         validate_required_file("config", &config_path)?;
 
         let config = ModelConfig::from_file(&config_path)?;
-        let parameters = parameters.with_max_width(config.max_width);
+        let parameters = config.apply(parameters)?;
+        let names = config.input_names()?;
 
-        match config.mode {
-            ConfigMode::Span => Ok(InferenceMode::Span(
-                GLiNER::<pipeline::span::SpanMode>::new(
+        let model = match config.mode {
+            ConfigMode::Span => {
+                let pipeline = pipeline::span::SpanPipeline::new(tokenizer_path)?
+                    .with_input_names(names.clone());
+                config.check_vocab_size(pipeline.tokenizer().vocab_size())?;
+                InferenceMode::Span(GLiNER::load(
                     parameters,
                     runtime_parameters,
-                    tokenizer_path,
-                    onnx_model_path,
-                )?,
-            )),
-            ConfigMode::Token => Ok(InferenceMode::Token(
-                GLiNER::<pipeline::token::TokenMode>::new(
+                    &onnx_model_path,
+                    pipeline,
+                    config.labels.clone(),
+                )?)
+            }
+            ConfigMode::Token => {
+                let pipeline = pipeline::token::TokenPipeline::new(tokenizer_path)?
+                    .with_input_names(names.clone());
+                config.check_vocab_size(pipeline.tokenizer().vocab_size())?;
+                InferenceMode::Token(GLiNER::load(
                     parameters,
                     runtime_parameters,
-                    tokenizer_path,
-                    onnx_model_path,
-                )?,
+                    &onnx_model_path,
+                    pipeline,
+                    config.labels.clone(),
+                )?)
+            }
+        };
+        check_tensors(model.model_info(), config.mode, &names)?;
+        Ok(model)
+    }
+
+    /// Checks that the given model directory holds every component required by `from_dir`, and that
+    /// they can be parsed, without loading the model.
+    ///
+    /// The configuration is applied to default parameters, the tokenizer is loaded and its vocabulary
+    /// size checked, and the ONNX model is only scanned for its versions and tensor names (no session
+    /// is created). Use `ModelDirReport::check` to turn the report into an error.
+    pub fn validate_dir<P: AsRef<Path>>(model_dir: P) -> ModelDirReport {
+        Self::validate_dir_with(model_dir, None, None, None)
+    }
+
+    /// Same as `validate_dir`, with the optional component paths of `from_dir_with`
+    pub fn validate_dir_with<P: AsRef<Path>>(
+        model_dir: P,
+        tokenizer_path: Option<&str>,
+        onnx_model_path: Option<&str>,
+        config_path: Option<&str>,
+    ) -> ModelDirReport {
+        let model_dir = model_dir.as_ref();
+        let mut components = Vec::new();
+
+        let config_path = resolve_component_path(model_dir, config_path, "gliner_config.json");
+        let config = match validate_required_file("config", &config_path) {
+            Ok(()) => {
+                let result = ModelConfig::from_file(&config_path).and_then(|config| {
+                    config.apply(Parameters::default())?;
+                    config.input_names()?;
+                    Ok(config)
+                });
+                validate_component(&mut components, "config", config_path, result)
+            }
+            Err(error) => {
+                components.push(ComponentReport::missing(
+                    "config",
+                    config_path,
+                    error.to_string(),
+                ));
+                None
+            }
+        };
+
+        // as in `from_dir_with`, the tokenizer can be assembled from `vocab.txt` if `tokenizer.json` is missing
+        let tokenizer_path = resolve_component_path(model_dir, tokenizer_path, "tokenizer.json");
+        let vocab_path = tokenizer_path.with_file_name("vocab.txt");
+        let tokenizer_file = if tokenizer_path.is_file() || !vocab_path.is_file() {
+            tokenizer_path.clone()
+        } else {
+            vocab_path
+        };
+        match validate_required_file("tokenizer", &tokenizer_file) {
+            Ok(()) => {
+                let result = crate::text::tokenizer::HFTokenizer::from_path(&tokenizer_path)
+                    .and_then(|tokenizer| match &config {
+                        Some(config) => config.check_vocab_size(tokenizer.vocab_size()),
+                        None => Ok(()),
+                    });
+                validate_component(&mut components, "tokenizer", tokenizer_file, result);
+            }
+            Err(error) => components.push(ComponentReport::missing(
+                "tokenizer",
+                tokenizer_file,
+                error.to_string(),
+            )),
+        }
+
+        match Self::resolve_onnx_model_path(model_dir, onnx_model_path) {
+            Ok(path) => {
+                let result = validate_onnx_model(&path, config.as_ref());
+                validate_component(&mut components, "onnx_model", path, result);
+            }
+            Err(error) => components.push(ComponentReport::missing(
+                "onnx_model",
+                resolve_component_path(model_dir, onnx_model_path, ONNX_MODEL_PATHS[0]),
+                error.to_string(),
             )),
         }
+
+        ModelDirReport { components }
     }
+
+    /// Same as `from_dir`, but loads every component from memory (contents of `gliner_config.json`,
+    /// `tokenizer.json` and the ONNX model)
+    pub fn from_bytes(
+        config_bytes: &[u8],
+        tokenizer_bytes: &[u8],
+        model_bytes: &[u8],
+        parameters: Parameters,
+        runtime_parameters: impl Into<SessionParameters>,
+    ) -> Result<InferenceMode> {
+        let config = ModelConfig::from_bytes(config_bytes)?;
+        let parameters = config.apply(parameters)?;
+        let names = config.input_names()?;
+
+        let model = match config.mode {
+            ConfigMode::Span => {
+                let pipeline = pipeline::span::SpanPipeline::new_from_bytes(tokenizer_bytes)?
+                    .with_input_names(names.clone());
+                config.check_vocab_size(pipeline.tokenizer().vocab_size())?;
+                InferenceMode::Span(GLiNER::load_from_bytes(
+                    parameters,
+                    runtime_parameters,
+                    model_bytes,
+                    pipeline,
+                    config.labels.clone(),
+                )?)
+            }
+            ConfigMode::Token => {
+                let pipeline = pipeline::token::TokenPipeline::new_from_bytes(tokenizer_bytes)?
+                    .with_input_names(names.clone());
+                config.check_vocab_size(pipeline.tokenizer().vocab_size())?;
+                InferenceMode::Token(GLiNER::load_from_bytes(
+                    parameters,
+                    runtime_parameters,
+                    model_bytes,
+                    pipeline,
+                    config.labels.clone(),
+                )?)
+            }
+        };
+        check_tensors(model.model_info(), config.mode, &names)?;
+        Ok(model)
+    }
+}
+
+/// Loads an ONNX model, after checking that its versions are supported (see `OnnxVersions::check`)
+fn load_model<P: AsRef<Path>>(
+    model_path: P,
+    runtime_parameters: impl Into<SessionParameters>,
+) -> Result<Model> {
+    info::OnnxVersions::from_file(&model_path)?.check()?;
+    Model::new(model_path, runtime_parameters)
+}
+
+/// Same as `load_model`, given the contents of the ONNX model
+fn load_model_from_bytes(
+    model_bytes: &[u8],
+    runtime_parameters: impl Into<SessionParameters>,
+) -> Result<Model> {
+    info::OnnxVersions::from_bytes(model_bytes)?.check()?;
+    Model::new_from_bytes(model_bytes, runtime_parameters)
+}
+
+/// Pulls the texts from the given iterator by batches of `batch_size`, lazily yielding the spans found by
+/// `inference` for each text. This is what `GLiNER::predict_stream` does, for callers which cannot lend
+/// the model to the iterator (e.g. sharing it behind an `Arc`). Returns an error if `batch_size` is 0.
+pub fn stream_batches<'a, I, T, F>(
+    texts: I,
+    batch_size: usize,
+    mut inference: F,
+) -> Result<impl Iterator<Item = Result<Vec<Span>>> + 'a>
+where
+    I: IntoIterator<Item = T>,
+    I::IntoIter: 'a,
+    T: AsRef<str>,
+    F: FnMut(Vec<String>) -> Result<Vec<Vec<Span>>> + 'a,
+{
+    if batch_size == 0 {
+        return Err("invalid parameters: batch_size must be greater than 0".into());
+    }
+    let mut texts = texts.into_iter();
+    Ok(std::iter::from_fn(move || {
+        let batch: Vec<String> = texts
+            .by_ref()
+            .take(batch_size)
+            .map(|text| text.as_ref().to_string())
+            .collect();
+        if batch.is_empty() {
+            return None;
+        }
+        let len = batch.len();
+        Some(match inference(batch) {
+            Ok(spans) => spans.into_iter().map(Ok).collect::<Vec<_>>(),
+            Err(error) => {
+                let message = error.to_string();
+                std::iter::once(Err(error))
+                    .chain((1..len).map(|_| Err(message.clone().into())))
+                    .collect()
+            }
+        })
+    })
+    .flatten())
+}
+
+/// Adds the report of an existing component, returning the parsed value if it is valid
+fn validate_component<T>(
+    components: &mut Vec<ComponentReport>,
+    component: &'static str,
+    path: PathBuf,
+    result: Result<T>,
+) -> Option<T> {
+    match result {
+        Ok(value) => {
+            components.push(ComponentReport::new(component, path, Ok(())));
+            Some(value)
+        }
+        Err(error) => {
+            components.push(ComponentReport::new(component, path, Err(error)));
+            None
+        }
+    }
+}
+
+/// Checks the versions of an ONNX model, and its tensors against the configuration (if valid)
+fn validate_onnx_model(path: &Path, config: Option<&ModelConfig>) -> Result<()> {
+    info::OnnxVersions::from_file(path)?.check()?;
+    let info = ModelInfo::from_graph_file(path)?;
+    if let Some(config) = config {
+        check_tensors(&info, config.mode, &config.input_names()?)?;
+    }
+    Ok(())
+}
+
+/// Checks the tensors of a model against the ones expected in the given mode, given the names of the inputs
+/// (which the pipelines only check themselves when they are the usual ones)
+fn check_tensors(info: &ModelInfo, mode: ConfigMode, names: &InputNames) -> Result<()> {
+    let (inputs, outputs): (HashSet<&str>, HashSet<&str>) = match mode {
+        ConfigMode::Span => (
+            input::tensors::span::SpanTensors::inputs(names)
+                .into_iter()
+                .collect(),
+            output::decoded::span::TensorsToDecoded::outputs()
+                .into_iter()
+                .collect(),
+        ),
+        ConfigMode::Token => (
+            input::tensors::token::TokenTensors::inputs(names)
+                .into_iter()
+                .collect(),
+            output::decoded::token::TensorsToDecoded::outputs()
+                .into_iter()
+                .collect(),
+        ),
+    };
+    info.check(Some(&inputs), Some(&outputs))
 }
 
 fn resolve_component_path(
@@ -115,3 +813,74 @@ fn validate_required_file(component: &str, path: &Path) -> Result<()> {
         Err(format!("missing required {component} file: {}", path.display()).into())
     }
 }
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_stream_batches() -> Result<()> {
+        let pulled = Cell::new(0);
+        let texts = ["a", "b", "c", "d", "e"]
+            .into_iter()
+            .inspect(|_| pulled.set(pulled.get() + 1));
+        // one span per text, failing for the batch of "c"
+        let inference = |batch: Vec<String>| match batch.contains(&"c".to_string()) {
+            true => Err("failed".into()),
+            false => Ok(batch
+                .iter()
+                .map(|text| vec![Span::new(0, 0, 1, text.clone(), "letter".into(), 1.0)])
+                .collect()),
+        };
+        let mut spans = stream_batches(texts, 2, inference)?;
+        assert_eq!(pulled.get(), 0);
+        assert_eq!(
+            spans.next().transpose()?.map(|s| s[0].text().to_string()),
+            Some("a".into())
+        );
+        assert_eq!(pulled.get(), 2);
+        assert!(spans.next().is_some_and(|s| s.is_ok()));
+        assert_eq!(pulled.get(), 2);
+        assert!(spans.next().is_some_and(|s| s.is_err()));
+        assert!(spans.next().is_some_and(|s| s.is_err()));
+        assert_eq!(pulled.get(), 4);
+        assert!(spans.next().is_some_and(|s| s.is_ok()));
+        assert!(spans.next().is_none());
+        assert_eq!(pulled.get(), 5);
+        assert!(stream_batches(["a"], 0, inference).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_parameters() -> Result<()> {
+        use crate::text::tokenizer::testing::CharTokenizer;
+        // the parameters are rejected before the pipeline or the model are used
+        let model = GLiNER {
+            params: Parameters::default(),
+            model: Model::new_from_bytes(
+                &session::testing::identity_model(),
+                orp::params::RuntimeParameters::default(),
+            )?,
+            pipeline: pipeline::span::SpanPipeline::with_tokenizer(
+                crate::text::splitter::RegexSplitter::default(),
+                CharTokenizer,
+            ),
+            known_labels: None,
+        };
+        let input = || TextInput::from_str(&["Alice met Bob"], &["person"]);
+        let params = Parameters::default().with_threshold(1.5);
+        let is_invalid = |result: Result<SpanOutput>| {
+            result.is_err_and(|error| error.to_string().starts_with("invalid parameters"))
+        };
+        assert!(is_invalid(model.inference_with_params(input()?, &params)));
+        assert!(is_invalid(model.inference_in_batches(input()?, &params)));
+        let mut schema = RelationSchema::new();
+        schema.push_with_allowed_labels("met", &["person"], &["person"]);
+        assert!(model
+            .extract_relations(input()?, &schema, &params)
+            .is_err_and(|error| error.to_string().starts_with("invalid parameters")));
+        Ok(())
+    }
+}
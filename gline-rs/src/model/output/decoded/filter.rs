@@ -0,0 +1,59 @@
+//! Optional step keeping only the spans of some classes
+
+use super::SpanOutput;
+use crate::util::result::Result;
+use composable::Composable;
+use std::collections::HashSet;
+
+/// Drops the spans whose class is not in the allow-list, so that following steps
+/// (sort, greedy-search...) do not have to process them.
+///
+/// The default instance keeps every span, so that it can always be part of a pipeline.
+#[derive(Default)]
+pub struct LabelFilter {
+    allowed: Option<HashSet<String>>,
+}
+
+impl LabelFilter {
+    pub fn new(allowed: HashSet<String>) -> Self {
+        Self {
+            allowed: Some(allowed),
+        }
+    }
+}
+
+/// Composable: SpanOutput => SpanOutput
+impl Composable<SpanOutput, SpanOutput> for LabelFilter {
+    fn apply(&self, input: SpanOutput) -> Result<SpanOutput> {
+        let allowed = match &self.allowed {
+            Some(allowed) => allowed,
+            None => return Ok(input),
+        };
+        let mut spans = input.spans;
+        for sequence in &mut spans {
+            sequence.retain(|span| allowed.contains(span.class()));
+        }
+        Ok(SpanOutput { spans, ..input })
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::span::Span;
+
+    #[test]
+    fn test_filter() -> Result<()> {
+        let span = |class: &str| Span::new(0, 0, 4, "Bill".to_string(), class.to_string(), 0.9);
+        let input = SpanOutput::new(
+            vec!["Bill".to_string()],
+            vec!["person".to_string(), "name".to_string()],
+            vec![vec![span("person"), span("name")]],
+        );
+        let filter = LabelFilter::new(HashSet::from(["person".to_string()]));
+        let output = filter.apply(input)?;
+        assert_eq!(output.spans, vec![vec![span("person")]]);
+        Ok(())
+    }
+}
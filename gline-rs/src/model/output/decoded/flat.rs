@@ -0,0 +1,92 @@
+//! Optional step guaranteeing non-overlapping spans
+
+use super::sort::SpanSort;
+use super::SpanOutput;
+use crate::text::span::Span;
+use crate::util::result::Result;
+use composable::Composable;
+use std::collections::BTreeMap;
+
+/// Guarantees that no two spans of a sequence share a byte, whatever their classes and whatever the
+/// decoding parameters (e.g. `multi_label`), which is required by consumers expecting a flat annotation.
+///
+/// Residual conflicts are resolved by score: spans are accepted from the most probable to the least
+/// probable one (the longest first in case of tie), provided that they do not overlap an accepted span.
+/// Spans are then sorted by offsets. This step does nothing if it is not enabled, so that it can always
+/// be part of a pipeline.
+pub struct ForceFlat {
+    enabled: bool,
+}
+
+impl ForceFlat {
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled }
+    }
+
+    /// Removes the overlapping spans of one sequence
+    pub fn flatten(mut spans: Vec<Span>) -> Vec<Span> {
+        spans.sort_by(|a, b| {
+            b.probability()
+                .total_cmp(&a.probability())
+                .then_with(|| Self::len(b).cmp(&Self::len(a)))
+                .then_with(|| a.offsets().cmp(&b.offsets()))
+        });
+        // end offset of the accepted spans, by start offset
+        let mut accepted = BTreeMap::new();
+        let mut result = Vec::with_capacity(spans.len());
+        for span in spans {
+            let (start, end) = span.offsets();
+            // accepted spans are disjoint, so only the last one starting before this end may overlap
+            let overlaps = accepted
+                .range(..end)
+                .next_back()
+                .is_some_and(|(_, accepted_end)| *accepted_end > start);
+            if !overlaps {
+                accepted.insert(start, end);
+                result.push(span);
+            }
+        }
+        result.sort_unstable_by(SpanSort::compare);
+        result
+    }
+
+    fn len(span: &Span) -> usize {
+        let (start, end) = span.offsets();
+        end - start
+    }
+}
+
+/// Composable: SpanOutput => SpanOutput
+impl Composable<SpanOutput, SpanOutput> for ForceFlat {
+    fn apply(&self, input: SpanOutput) -> Result<SpanOutput> {
+        if !self.enabled {
+            return Ok(input);
+        }
+        let spans = input.spans.into_iter().map(Self::flatten).collect();
+        Ok(SpanOutput { spans, ..input })
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::output::decoded::testing::span;
+
+    #[test]
+    fn test_flatten() {
+        let text = "New York City council";
+        // overlapping spans of different classes, as given by `multi_label`
+        let spans = vec![
+            span(text, 0, 8, "state", 0.7),
+            span(text, 0, 13, "city", 0.9),
+            span(text, 4, 13, "place", 0.9),
+            span(text, 9, 21, "org", 0.8),
+            span(text, 14, 21, "org", 0.6),
+        ];
+        let flat = ForceFlat::flatten(spans);
+        let texts: Vec<&str> = flat.iter().map(Span::text).collect();
+        assert_eq!(texts, vec!["New York City", "council"]);
+        assert!(flat.windows(2).all(|w| !w[0].strictly_overlaps(&w[1])));
+    }
+}
@@ -1,5 +1,6 @@
 //! Greedy-search is the second step of span decoding
 
+use super::sort::SpanSort;
 use super::SpanOutput;
 use crate::text::span::Span;
 use crate::util::result::Result;
@@ -12,6 +13,7 @@ pub struct GreedySearch {
     flat_ner: bool,
     dup_label: bool,
     multi_label: bool,
+    top_k: Option<usize>,
 }
 
 impl GreedySearch {
@@ -25,17 +27,43 @@ impl GreedySearch {
             flat_ner,
             dup_label,
             multi_label,
+            top_k: None,
         }
     }
 
+    /// If `flat_ner` is `false`, setting `top_k` means that up to `k` overlapping spans are kept for any
+    /// region of the text (the most probable ones), whatever their classes (`multi_label` is then ignored).
+    /// Overlapping spans of the *same* class are still only allowed if `dup_label` is `true`.
+    pub fn with_top_k(mut self, top_k: Option<usize>) -> Self {
+        self.top_k = top_k;
+        self
+    }
+
+    /// Resolves overlaps among arbitrary spans (e.g. candidates coming from another source), independently
+    /// of any pipeline: spans are first sorted (see `SpanSort`), and then filtered by greedy search with
+    /// the given flags (see `new`).
+    pub fn resolve(
+        mut spans: Vec<Span>,
+        flat_ner: bool,
+        dup_label: bool,
+        multi_label: bool,
+    ) -> Vec<Span> {
+        spans.sort_unstable_by(SpanSort::compare);
+        Self::new(flat_ner, dup_label, multi_label).search(&spans)
+    }
+
     /// Perform greedy search
     ///
-    /// Note: spans are supposed to be sorted by start, and then end, offsets.
+    /// Note: spans are supposed to be sorted by start, and then end, offsets (see `resolve` otherwise).
     pub fn search(&self, spans: &[Span]) -> Vec<Span> {
         if spans.is_empty() {
             return Vec::new();
         }
 
+        if let (false, Some(top_k)) = (self.flat_ner, self.top_k) {
+            return self.search_top_k(spans, top_k);
+        }
+
         let mut result = Vec::with_capacity(spans.len());
         let mut prev = 0usize;
         let mut next = 1usize;
@@ -58,6 +86,51 @@ impl GreedySearch {
         result
     }
 
+    /// Keeps the most probable spans, as long as no position of the text is covered by more than `top_k` of them
+    /// (and same-class overlaps are allowed by `dup_label`). The result is sorted by offsets, as the input.
+    fn search_top_k(&self, spans: &[Span], top_k: usize) -> Vec<Span> {
+        let mut candidates: Vec<&Span> = spans.iter().collect();
+        candidates.sort_by(|a, b| {
+            b.probability()
+                .total_cmp(&a.probability())
+                .then_with(|| SpanSort::compare(a, b))
+        });
+
+        let mut result: Vec<Span> = Vec::new();
+        for span in candidates {
+            let (start, end) = span.offsets();
+            let overlapping: Vec<&Span> = result
+                .iter()
+                .filter(|s| s.offsets().0 < end && start < s.offsets().1)
+                .collect();
+            if !self.dup_label && overlapping.iter().any(|s| s.class() == span.class()) {
+                continue;
+            }
+            // the coverage is maximal at the start of the span or at the start of an overlapping one
+            let depth = std::iter::once(start)
+                .chain(
+                    overlapping
+                        .iter()
+                        .map(|s| s.offsets().0)
+                        .filter(|p| *p > start),
+                )
+                .map(|p| {
+                    overlapping
+                        .iter()
+                        .filter(|s| s.offsets().0 <= p && p < s.offsets().1)
+                        .count()
+                })
+                .max()
+                .unwrap_or(0);
+            if depth < top_k {
+                result.push(span.clone());
+            }
+        }
+
+        result.sort_unstable_by(SpanSort::compare);
+        result
+    }
+
     /// Returns `true` iif the span is valid wrt. the provided flags.
     ///
     /// Namely:
@@ -66,6 +139,7 @@ impl GreedySearch {
     /// * And `multi_label=true` allows for overlapping spans with *different* labels.
     ///
     /// The checks are only relative to the previous span, as we expect them to be sorted by offset.    
+    #[allow(clippy::if_same_then_else)]
     fn accept(&self, s1: &Span, s2: &Span) -> bool {
         // if there is no overlap, we accept immediately
         if s1.is_disjoint(s2) {
@@ -90,6 +164,53 @@ impl GreedySearch {
 impl Composable<SpanOutput, SpanOutput> for GreedySearch {
     fn apply(&self, input: SpanOutput) -> Result<SpanOutput> {
         let spans = input.spans.iter().map(|s| self.search(s)).collect();
-        Ok(SpanOutput::new(input.texts, input.entities, spans))
+        Ok(SpanOutput { spans, ..input })
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::output::decoded::testing::span;
+
+    #[test]
+    fn test_resolve() {
+        let text = "Bill Gates founded Microsoft";
+        // unsorted candidates
+        let spans = vec![
+            span(text, 19, 28, "company", 0.8),
+            span(text, 5, 18, "company", 0.4),
+            span(text, 0, 10, "person", 0.9),
+        ];
+        let result = GreedySearch::resolve(spans.clone(), true, false, false);
+        let texts: Vec<&str> = result.iter().map(|s| s.text()).collect();
+        assert_eq!(texts, vec!["Bill Gates", "Microsoft"]);
+        let result = GreedySearch::resolve(spans, false, false, true);
+        assert_eq!(result.len(), 3);
+    }
+
+    #[test]
+    fn test_top_k_nested() {
+        let text = "University of California";
+        let spans = vec![
+            span(text, 0, 10, "organization", 0.6),
+            span(text, 0, 24, "organization", 0.9),
+            span(text, 14, 24, "location", 0.8),
+        ];
+        // without `top_k`, the nested location is lost
+        let result = GreedySearch::new(false, false, false).search(&spans);
+        assert_eq!(result.len(), 1);
+        // with `top_k`, both the outer and the inner spans survive (but not the overlapping span of the same class)
+        let result = GreedySearch::new(false, false, false)
+            .with_top_k(Some(2))
+            .search(&spans);
+        let texts: Vec<&str> = result.iter().map(|s| s.text()).collect();
+        assert_eq!(texts, vec!["University of California", "California"]);
+        // `top_k` is ignored for flat NER
+        let result = GreedySearch::new(true, false, false)
+            .with_top_k(Some(2))
+            .search(&spans);
+        assert_eq!(result.len(), 1);
     }
 }
@@ -0,0 +1,134 @@
+//! Optional step merging adjacent spans of the same class
+
+use super::SpanOutput;
+use crate::text::span::Span;
+use crate::util::error::IndexError;
+use crate::util::result::Result;
+use composable::Composable;
+
+/// Merges adjacent spans having the same class, i.e. spans which are contiguous or only
+/// separated by whitespace (for example "New" and "York" into "New York").
+///
+/// The probability of a merged span is the mean of the probabilities of its parts.
+/// Spans are supposed to be sorted by offsets (which is the case after greedy-search).
+/// This step does nothing if it is not enabled, so that it can always be part of a pipeline.
+pub struct MergeAdjacent {
+    enabled: bool,
+}
+
+impl MergeAdjacent {
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled }
+    }
+
+    /// Merges the adjacent spans of one sequence
+    pub fn merge(&self, text: &str, spans: Vec<Span>) -> Result<Vec<Span>> {
+        let mut result: Vec<Span> = Vec::with_capacity(spans.len());
+        // number of original spans that were merged into the last span of the result
+        let mut parts = 0usize;
+
+        for span in spans {
+            if let Some(last) = result.last_mut() {
+                if Self::adjacent(text, last, &span)? {
+                    let (start, _) = last.offsets();
+                    let (_, end) = span.offsets();
+                    let probability = (last.probability() * parts as f32 + span.probability())
+                        / (parts + 1) as f32;
+                    *last = Span::new(
+                        span.sequence(),
+                        start,
+                        end,
+                        Self::substring(text, start, end)?.to_string(),
+                        span.class().to_string(),
+                        probability,
+                    );
+                    parts += 1;
+                    continue;
+                }
+            }
+            result.push(span);
+            parts = 1;
+        }
+
+        Ok(result)
+    }
+
+    fn adjacent(text: &str, s1: &Span, s2: &Span) -> Result<bool> {
+        let (_, end) = s1.offsets();
+        let (start, _) = s2.offsets();
+        Ok(s1.class() == s2.class()
+            && end <= start
+            && Self::substring(text, end, start)?
+                .chars()
+                .all(char::is_whitespace))
+    }
+
+    fn substring(text: &str, start: usize, end: usize) -> Result<&str> {
+        Ok(text
+            .get(start..end)
+            .ok_or(IndexError::with("span offsets do not match the text"))?)
+    }
+}
+
+/// Composable: SpanOutput => SpanOutput
+impl Composable<SpanOutput, SpanOutput> for MergeAdjacent {
+    fn apply(&self, input: SpanOutput) -> Result<SpanOutput> {
+        if !self.enabled {
+            return Ok(input);
+        }
+        let mut spans = Vec::with_capacity(input.spans.len());
+        for (text, sequence) in input.texts.iter().zip(input.spans) {
+            spans.push(self.merge(text, sequence)?);
+        }
+        Ok(SpanOutput { spans, ..input })
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(text: &str, start: usize, end: usize, class: &str, probability: f32) -> Span {
+        Span::new(
+            0,
+            start,
+            end,
+            text[start..end].to_string(),
+            class.to_string(),
+            probability,
+        )
+    }
+
+    #[test]
+    fn test_merge() -> Result<()> {
+        #![allow(clippy::unwrap_used)]
+        let text = "I live in New  York,Paris and Lyon";
+        let spans = vec![
+            span(text, 10, 13, "city", 0.9),
+            span(text, 15, 19, "city", 0.7),
+            span(text, 20, 25, "city", 0.8),
+            span(text, 30, 34, "city", 0.6),
+        ];
+        let merged = MergeAdjacent::new(true).merge(text, spans)?;
+        assert_eq!(merged.len(), 3);
+        let first = merged.first().unwrap();
+        assert_eq!(first.text(), "New  York");
+        assert_eq!(first.offsets(), (10, 19));
+        assert!((first.probability() - 0.8).abs() < 1e-6);
+        assert_eq!(merged.get(1).unwrap().text(), "Paris");
+        Ok(())
+    }
+
+    #[test]
+    fn test_different_classes() -> Result<()> {
+        let text = "New York";
+        let spans = vec![
+            span(text, 0, 3, "city", 0.9),
+            span(text, 4, 8, "state", 0.7),
+        ];
+        let merged = MergeAdjacent::new(true).merge(text, spans)?;
+        assert_eq!(merged.len(), 2);
+        Ok(())
+    }
+}
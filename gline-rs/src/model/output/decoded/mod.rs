@@ -1,34 +1,319 @@
 //! Span decoding steps
 
+pub mod filter;
+pub mod flat;
 pub mod greedy;
+pub mod merge;
+pub mod nms;
+pub mod slice;
 pub mod sort;
 pub mod span;
 pub mod token;
 pub mod token_flat;
+pub mod window;
 
 use crate::text::span::Span;
+use crate::util::result::Result;
+use serde::{Deserialize, Serialize};
+
+/// Builds the class distribution of a span given the score of each class (in the order of `entities`),
+/// keeping the classes whose score reaches the threshold, in decreasing order of score.
+pub fn class_distribution(
+    entities: &[String],
+    scores: impl IntoIterator<Item = f32>,
+    threshold: f32,
+) -> Vec<(String, f32)> {
+    let mut result: Vec<(String, f32)> = entities
+        .iter()
+        .zip(scores)
+        .filter(|(_, score)| *score >= threshold)
+        .map(|(entity, score)| (entity.clone(), score))
+        .collect();
+    result.sort_by(|a, b| b.1.total_cmp(&a.1));
+    result
+}
+
+/// Returns `true` if the span has at least `min_chars` characters, leading and trailing whitespace excluded
+pub fn has_min_chars(span: &Span, min_chars: usize) -> bool {
+    min_chars == 0 || span.text().trim().chars().count() >= min_chars
+}
 
 /// Represents the final output of the post-processing steps, as a list of spans for each input sequence
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct SpanOutput {
     pub texts: Vec<String>,
     pub entities: Vec<String>,
     pub spans: Vec<Vec<Span>>,
+    /// Number of tokens (i.e. words) processed for each sequence, counting each window if the sequence
+    /// was split, and excluding truncated tokens (empty if unknown, e.g. for an output built by hand)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub token_counts: Vec<usize>,
+    /// Byte offsets of the words each sequence was split into by the pipeline (the words shared by
+    /// overlapping windows appearing once, and truncated words being excluded), empty if unknown
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub word_offsets: Vec<Vec<(usize, usize)>>,
 }
 
+/// Marker printed for sequences without any span (see the `Display` implementation of `SpanOutput`)
+pub const NO_ENTITIES: &str = "(no entities)";
+
 impl SpanOutput {
     pub fn new(texts: Vec<String>, entities: Vec<String>, spans: Vec<Vec<Span>>) -> Self {
         Self {
             texts,
             entities,
             spans,
+            token_counts: Vec::new(),
+            word_offsets: Vec::new(),
+        }
+    }
+
+    /// Returns the same output, carrying the given number of tokens processed for each sequence
+    pub fn with_token_counts(mut self, token_counts: Vec<usize>) -> Self {
+        self.token_counts = token_counts;
+        self
+    }
+
+    /// Returns the same output, carrying the offsets of the words of each sequence
+    pub fn with_word_offsets(mut self, word_offsets: Vec<Vec<(usize, usize)>>) -> Self {
+        self.word_offsets = word_offsets;
+        self
+    }
+
+    /// Total number of tokens processed (see `token_counts`)
+    pub fn num_tokens(&self) -> usize {
+        self.token_counts.iter().sum()
+    }
+
+    /// Builds an output from already known entities, given for each text as `(start, end, class)` (byte
+    /// offsets within the text), for example to perform relation extraction on curated entities (see
+    /// `GLiNER::extract_relations_from_entities`). The probability of such entities is 1.0.
+    pub fn from_entities(
+        texts: Vec<String>,
+        entities: Vec<Vec<(usize, usize, String)>>,
+    ) -> Result<Self> {
+        if texts.len() != entities.len() {
+            return Err(format!(
+                "got {} texts but entities for {} texts",
+                texts.len(),
+                entities.len()
+            )
+            .into());
+        }
+        let mut classes: Vec<String> = Vec::new();
+        let mut spans = Vec::with_capacity(texts.len());
+        for (sequence, (text, sequence_entities)) in texts.iter().zip(entities).enumerate() {
+            let mut sequence_spans = Vec::with_capacity(sequence_entities.len());
+            for (start, end, class) in sequence_entities {
+                let entity_text = text
+                    .get(start..end)
+                    .filter(|entity_text| !entity_text.is_empty())
+                    .ok_or_else(|| {
+                        format!("invalid offsets ({start}..{end}) for entity '{class}' of text {sequence}")
+                    })?;
+                if !classes.contains(&class) {
+                    classes.push(class.clone());
+                }
+                sequence_spans.push(Span::new(
+                    sequence,
+                    start,
+                    end,
+                    entity_text.to_string(),
+                    class,
+                    1.0,
+                ));
+            }
+            spans.push(sequence_spans);
+        }
+        Ok(Self::new(texts, classes, spans))
+    }
+
+    /// Iterates over the spans of all sequences, along with the index of their sequence
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &Span)> {
+        self.spans
+            .iter()
+            .enumerate()
+            .flat_map(|(sequence, spans)| spans.iter().map(move |span| (sequence, span)))
+    }
+
+    /// Total number of spans (over all sequences)
+    pub fn len(&self) -> usize {
+        self.spans.iter().map(Vec::len).sum()
+    }
+
+    /// Whether no span was found at all
+    pub fn is_empty(&self) -> bool {
+        self.spans.iter().all(Vec::is_empty)
+    }
+
+    /// Returns a copy of this output, keeping only the spans whose probability reaches the given threshold
+    pub fn at_threshold(&self, threshold: f32) -> Self {
+        let spans = self
+            .spans
+            .iter()
+            .map(|spans| {
+                spans
+                    .iter()
+                    .filter(|span| span.probability() >= threshold)
+                    .cloned()
+                    .collect()
+            })
+            .collect();
+        Self::new(self.texts.clone(), self.entities.clone(), spans)
+            .with_token_counts(self.token_counts.clone())
+            .with_word_offsets(self.word_offsets.clone())
+    }
+
+    /// Index of the sentence of its text in which each span starts (see `output::sentence::sentences`), in the
+    /// same order as `spans`, for example to group the entities by sentence. Each text is segmented once.
+    pub fn sentence_indices(&self) -> Vec<Vec<Option<usize>>> {
+        self.texts
+            .iter()
+            .zip(&self.spans)
+            .map(|(text, spans)| {
+                let sentences = super::sentence::sentences(text);
+                spans
+                    .iter()
+                    .map(|span| super::sentence::sentence_index(&sentences, span.offsets().0))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Clusters the spans of each sequence into groups of (transitively) overlapping spans, in the sense
+    /// of `Span::strictly_overlaps`. Groups are ordered by offsets, and spans that do not overlap with any
+    /// other one come as singletons.
+    pub fn overlapping_groups(&self) -> Vec<Vec<Vec<&Span>>> {
+        self.spans
+            .iter()
+            .map(|spans| {
+                let mut sorted: Vec<&Span> = spans.iter().collect();
+                sorted.sort_by_key(|span| span.offsets());
+                let mut groups: Vec<Vec<&Span>> = Vec::new();
+                let mut group_end = 0;
+                for span in sorted {
+                    let (start, end) = span.offsets();
+                    match groups.last_mut() {
+                        Some(group) if start < group_end => {
+                            group.push(span);
+                            group_end = group_end.max(end);
+                        }
+                        _ => {
+                            groups.push(vec![span]);
+                            group_end = end;
+                        }
+                    }
+                }
+                groups
+            })
+            .collect()
+    }
+
+    /// Appends the output of another batch (with the same entity classes) to this one, re-indexing
+    /// its spans so that they keep referring to the right sequence.
+    pub fn append(&mut self, other: SpanOutput) {
+        let offset = self.texts.len();
+        // token counts remain meaningful only if both outputs carry them
+        if self.token_counts.len() == offset && other.token_counts.len() == other.texts.len() {
+            self.token_counts.extend(other.token_counts);
+        } else {
+            self.token_counts.clear();
+        }
+        if self.word_offsets.len() == offset && other.word_offsets.len() == other.texts.len() {
+            self.word_offsets.extend(other.word_offsets);
+        } else {
+            self.word_offsets.clear();
+        }
+        self.texts.extend(other.texts);
+        self.spans.extend(other.spans.into_iter().map(|spans| {
+            spans
+                .into_iter()
+                .map(|span| {
+                    let sequence = span.sequence() + offset;
+                    span.with_sequence(sequence)
+                })
+                .collect()
+        }));
+    }
+
+    /// Formats the output in the JSON format of spaCy entities, i.e. an array holding one
+    /// `{"text": ..., "ents": [{"start": ..., "end": ..., "label": ...}, ...]}` document per sequence,
+    /// with offsets in characters (spans whose offsets do not match the text are skipped).
+    pub fn to_spacy_json(&self) -> String {
+        let docs: Vec<serde_json::Value> = self
+            .texts
+            .iter()
+            .zip(&self.spans)
+            .map(|(text, spans)| {
+                let ents: Vec<serde_json::Value> = spans
+                    .iter()
+                    .filter_map(|span| {
+                        let (start, end) = span.char_offsets(text)?;
+                        Some(serde_json::json!({"start": start, "end": end, "label": span.class()}))
+                    })
+                    .collect();
+                serde_json::json!({"text": text, "ents": ents})
+            })
+            .collect();
+        serde_json::Value::Array(docs).to_string()
+    }
+
+    /// Formats the output in CoNLL-2003 style: one `token tag` line per word, using `B-`/`I-`
+    /// prefixed labels or `O`, and a blank line between sequences.
+    ///
+    /// The words are the ones the pipeline split the texts into (see `word_offsets`), so that
+    /// the tags are always aligned with the spans, and truncated words are not printed. When spans
+    /// overlap, the most probable one wins, and less probable ones are dropped altogether. Fails if
+    /// the words are unknown (e.g. for an output built by hand).
+    pub fn to_conll(&self) -> Result<String> {
+        if self.word_offsets.len() != self.texts.len() {
+            return Err("the words of the sequences are unknown, CoNLL cannot be formatted".into());
         }
+        let mut result = String::new();
+        for ((text, spans), words) in self.texts.iter().zip(&self.spans).zip(&self.word_offsets) {
+            let mut tags: Vec<Option<String>> = vec![None; words.len()];
+
+            let mut spans: Vec<&Span> = spans.iter().collect();
+            spans.sort_by(|a, b| b.probability().total_cmp(&a.probability()));
+            for span in spans {
+                let (start, end) = span.offsets();
+                let covered: Vec<usize> = words
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, (word_start, word_end))| *word_start >= start && *word_end <= end)
+                    .map(|(i, _)| i)
+                    .collect();
+                if covered.is_empty() || covered.iter().any(|i| tags[*i].is_some()) {
+                    continue;
+                }
+                for (n, i) in covered.into_iter().enumerate() {
+                    let prefix = if n == 0 { "B" } else { "I" };
+                    tags[i] = Some(format!("{prefix}-{}", span.class()));
+                }
+            }
+
+            if !result.is_empty() {
+                result.push('\n');
+            }
+            for ((start, end), tag) in words.iter().zip(tags) {
+                let word = text.get(*start..*end).ok_or_else(|| {
+                    format!("invalid word offsets ({start}..{end}) for text '{text}'")
+                })?;
+                result.push_str(&format!("{} {}\n", word, tag.as_deref().unwrap_or("O")));
+            }
+        }
+        Ok(result)
     }
 }
 
+/// Prints one line per span. With the alternate flag (`{:#}`), sequences without any span are printed as well,
+/// with an explicit marker (instead of being omitted).
 impl std::fmt::Display for SpanOutput {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for spans in &self.spans {
+        for (sequence, spans) in self.spans.iter().enumerate() {
+            if spans.is_empty() && f.alternate() {
+                writeln!(f, "{:3} | {}", sequence, NO_ENTITIES)?;
+            }
             for span in spans {
                 writeln!(
                     f,
@@ -43,3 +328,280 @@ impl std::fmt::Display for SpanOutput {
         Ok(())
     }
 }
+
+/// Test fixtures shared by the unit tests of the crate
+#[cfg(test)]
+pub(crate) mod testing {
+    use crate::text::span::Span;
+
+    /// Span of the first sequence, whose text is sliced from the given one at its offsets
+    pub fn span(text: &str, start: usize, end: usize, class: &str, probability: f32) -> Span {
+        Span::new(
+            0,
+            start,
+            end,
+            text[start..end].to_string(),
+            class.to_string(),
+            probability,
+        )
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sentence_indices() {
+        let text = "Bill Gates founded Microsoft. Steve Jobs founded Apple.";
+        let span = |start: usize, end: usize| {
+            Span::new(
+                0,
+                start,
+                end,
+                text[start..end].to_string(),
+                "x".to_string(),
+                0.9,
+            )
+        };
+        let output = SpanOutput::new(
+            vec![text.to_string()],
+            vec!["x".to_string()],
+            vec![vec![span(0, 10), span(19, 28), span(30, 40)]],
+        );
+        assert_eq!(
+            output.sentence_indices(),
+            vec![vec![Some(0), Some(0), Some(1)]]
+        );
+    }
+
+    #[test]
+    fn test_json_round_trip() -> Result<()> {
+        let output = SpanOutput::new(
+            vec!["Bill Gates founded Microsoft".to_string()],
+            vec!["person".to_string(), "company".to_string()],
+            vec![vec![
+                Span::new(
+                    0,
+                    0,
+                    10,
+                    "Bill Gates".to_string(),
+                    "person".to_string(),
+                    0.9,
+                ),
+                Span::new(
+                    0,
+                    19,
+                    28,
+                    "Microsoft".to_string(),
+                    "company".to_string(),
+                    0.8,
+                ),
+            ]],
+        );
+        let json = serde_json::to_string(&output)?;
+        let decoded: SpanOutput = serde_json::from_str(&json)?;
+        assert_eq!(decoded.texts, output.texts);
+        assert_eq!(decoded.entities, output.entities);
+        assert_eq!(decoded.spans, output.spans);
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_entities() -> Result<()> {
+        let texts = vec!["Bill Gates founded Microsoft".to_string()];
+        let output = SpanOutput::from_entities(
+            texts.clone(),
+            vec![vec![
+                (0, 10, "person".to_string()),
+                (19, 28, "company".to_string()),
+            ]],
+        )?;
+        assert_eq!(output.entities, vec!["person", "company"]);
+        assert_eq!(output.spans[0][1].text(), "Microsoft");
+        assert_eq!(output.spans[0][1].probability(), 1.0);
+        assert!(SpanOutput::from_entities(texts.clone(), vec![]).is_err());
+        assert!(
+            SpanOutput::from_entities(texts.clone(), vec![vec![(5, 5, "person".to_string())]])
+                .is_err()
+        );
+        assert!(
+            SpanOutput::from_entities(texts, vec![vec![(19, 40, "company".to_string())]]).is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_append() {
+        let span = |sequence| {
+            Span::new(
+                sequence,
+                0,
+                4,
+                "Bill".to_string(),
+                "person".to_string(),
+                0.9,
+            )
+        };
+        let entities = vec!["person".to_string()];
+        let mut output = SpanOutput::new(
+            vec!["Bill".to_string(), "Bob".to_string()],
+            entities.clone(),
+            vec![vec![span(0)], vec![]],
+        );
+        output.append(SpanOutput::new(
+            vec!["Bill".to_string()],
+            entities,
+            vec![vec![span(0)]],
+        ));
+        assert_eq!(output.texts.len(), 3);
+        assert_eq!(output.spans[2], vec![span(2)]);
+        assert_eq!(output.len(), 2);
+        let sequences: Vec<usize> = output.iter().map(|(sequence, _)| sequence).collect();
+        assert_eq!(sequences, vec![0, 2]);
+    }
+
+    #[test]
+    fn test_at_threshold() {
+        let span = |start, probability| {
+            Span::new(
+                0,
+                start,
+                start + 1,
+                "x".into(),
+                "letter".into(),
+                probability,
+            )
+        };
+        let output = SpanOutput::new(
+            vec!["x x x".to_string()],
+            vec!["letter".to_string()],
+            vec![vec![span(0, 0.3), span(2, 0.5), span(4, 0.8)]],
+        )
+        .with_token_counts(vec![3]);
+        let counts: Vec<usize> = [0.3, 0.5, 0.9]
+            .iter()
+            .map(|threshold| output.at_threshold(*threshold).len())
+            .collect();
+        assert_eq!(counts, vec![3, 2, 0]);
+        assert_eq!(output.at_threshold(0.5).token_counts, vec![3]);
+    }
+
+    #[test]
+    fn test_overlapping_groups() {
+        let span = |start, end| Span::new(0, start, end, String::new(), "person".to_string(), 0.9);
+        let output = SpanOutput::new(
+            vec!["Bill Gates founded Microsoft".to_string()],
+            vec!["person".to_string()],
+            vec![vec![span(19, 28), span(5, 18), span(0, 10), span(18, 19)]],
+        );
+        let groups = output.overlapping_groups();
+        assert_eq!(groups.len(), 1);
+        let offsets: Vec<Vec<(usize, usize)>> = groups[0]
+            .iter()
+            .map(|group| group.iter().map(|span| span.offsets()).collect())
+            .collect();
+        assert_eq!(
+            offsets,
+            vec![vec![(0, 10), (5, 18)], vec![(18, 19)], vec![(19, 28)]]
+        );
+    }
+
+    #[test]
+    fn test_conll() -> Result<()> {
+        let output = SpanOutput::new(
+            vec![
+                "Bill Gates founded Microsoft".to_string(),
+                "Hello".to_string(),
+            ],
+            vec!["person".to_string(), "company".to_string()],
+            vec![
+                vec![
+                    Span::new(
+                        0,
+                        0,
+                        10,
+                        "Bill Gates".to_string(),
+                        "person".to_string(),
+                        0.9,
+                    ),
+                    Span::new(
+                        0,
+                        5,
+                        18,
+                        "Gates founded".to_string(),
+                        "company".to_string(),
+                        0.5,
+                    ),
+                    Span::new(
+                        0,
+                        19,
+                        28,
+                        "Microsoft".to_string(),
+                        "company".to_string(),
+                        0.8,
+                    ),
+                ],
+                vec![],
+            ],
+        );
+        assert!(output.to_conll().is_err());
+        // words as split by the pipeline, the last one of the first text being truncated
+        let output = output.with_word_offsets(vec![vec![(0, 4), (5, 10), (11, 18)], vec![(0, 5)]]);
+        let expected = "Bill B-person\nGates I-person\nfounded O\n\nHello O\n";
+        assert_eq!(output.to_conll()?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_display_empty_sequences() {
+        let output = SpanOutput::new(
+            vec!["Nothing here".to_string(), "Bill Gates".to_string()],
+            vec!["person".to_string()],
+            vec![
+                vec![],
+                vec![Span::new(
+                    1,
+                    0,
+                    10,
+                    "Bill Gates".to_string(),
+                    "person".to_string(),
+                    0.9,
+                )],
+            ],
+        );
+        assert_eq!(output.to_string().lines().count(), 1);
+        let lines: Vec<String> = format!("{output:#}").lines().map(String::from).collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], format!("  0 | {NO_ENTITIES}"));
+        assert!(lines[1].starts_with("  1 | Bill Gates"));
+    }
+
+    #[test]
+    fn test_spacy_json() -> Result<()> {
+        let text = "Zoë Smith founded Acme";
+        let output = SpanOutput::new(
+            vec![text.to_string(), "Hello".to_string()],
+            vec!["person".to_string(), "company".to_string()],
+            vec![
+                vec![
+                    Span::new(0, 0, 10, "Zoë Smith".to_string(), "person".to_string(), 0.9),
+                    Span::new(0, 19, 23, "Acme".to_string(), "company".to_string(), 0.8),
+                ],
+                vec![],
+            ],
+        );
+        let expected = r#"[
+            {"text": "Zoë Smith founded Acme", "ents": [
+                {"start": 0, "end": 9, "label": "person"},
+                {"start": 18, "end": 22, "label": "company"}
+            ]},
+            {"text": "Hello", "ents": []}
+        ]"#;
+        let actual: serde_json::Value = serde_json::from_str(&output.to_spacy_json())?;
+        let expected: serde_json::Value = serde_json::from_str(expected)?;
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+//! Non-maximum suppression, an alternative to greedy-search for resolving overlapping spans
+
+use super::greedy::GreedySearch;
+use super::sort::SpanSort;
+use super::SpanOutput;
+use crate::text::span::Span;
+use crate::util::result::Result;
+use composable::Composable;
+
+/// Classic non-maximum suppression: spans are considered by decreasing probability, and a span is
+/// suppressed if its intersection-over-union (IoU) with an already kept span exceeds the threshold.
+///
+/// The IoU is computed on the character offsets of the spans (so that multi-byte characters do not weigh
+/// more than others), whatever their classes. Hence a threshold of `0.0`
+/// suppresses any overlap, while a threshold of `1.0` suppresses nothing (even identical spans
+/// of different classes are kept).
+pub struct NonMaxSuppression {
+    iou_threshold: f32,
+}
+
+impl NonMaxSuppression {
+    pub fn new(iou_threshold: f32) -> Self {
+        Self { iou_threshold }
+    }
+
+    /// Intersection-over-union of two `(start, end)` ranges (0 if they are disjoint or both empty, 1 if they
+    /// are the same non-empty range)
+    pub fn iou((start1, end1): (usize, usize), (start2, end2): (usize, usize)) -> f32 {
+        let intersection = end1.min(end2).saturating_sub(start1.max(start2));
+        let union = end1.max(end2) - start1.min(start2);
+        match union {
+            0 => 0.0,
+            union => intersection as f32 / union as f32,
+        }
+    }
+
+    /// Performs non-maximum suppression on spans of the given text. The result is sorted by offsets
+    /// (see `SpanSort`).
+    pub fn suppress(&self, spans: &[Span], text: &str) -> Vec<Span> {
+        // spans not matching the text (e.g. built by hand) fall back to their byte offsets
+        let mut candidates: Vec<(&Span, (usize, usize))> = spans
+            .iter()
+            .map(|span| (span, span.char_offsets(text).unwrap_or(span.offsets())))
+            .collect();
+        candidates.sort_by(|(a, _), (b, _)| {
+            b.probability()
+                .total_cmp(&a.probability())
+                .then_with(|| SpanSort::compare(a, b))
+        });
+
+        let mut kept: Vec<(&Span, (usize, usize))> = Vec::new();
+        for (span, offsets) in candidates {
+            if kept
+                .iter()
+                .all(|(_, kept)| Self::iou(*kept, offsets) <= self.iou_threshold)
+            {
+                kept.push((span, offsets));
+            }
+        }
+
+        let mut result: Vec<Span> = kept.into_iter().map(|(span, _)| span.clone()).collect();
+        result.sort_unstable_by(SpanSort::compare);
+        result
+    }
+}
+
+/// Composable: SpanOutput => SpanOutput
+impl Composable<SpanOutput, SpanOutput> for NonMaxSuppression {
+    fn apply(&self, input: SpanOutput) -> Result<SpanOutput> {
+        let spans = input
+            .spans
+            .iter()
+            .zip(&input.texts)
+            .map(|(spans, text)| self.suppress(spans, text))
+            .collect();
+        Ok(SpanOutput { spans, ..input })
+    }
+}
+
+/// Step resolving overlapping spans, either by greedy-search (the default) or by non-maximum suppression
+/// (see the `nms_iou_threshold` parameter)
+pub enum OverlapResolution {
+    Greedy(GreedySearch),
+    Nms(NonMaxSuppression),
+}
+
+/// Composable: SpanOutput => SpanOutput
+impl Composable<SpanOutput, SpanOutput> for OverlapResolution {
+    fn apply(&self, input: SpanOutput) -> Result<SpanOutput> {
+        match self {
+            Self::Greedy(greedy) => greedy.apply(input),
+            Self::Nms(nms) => nms.apply(input),
+        }
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::output::decoded::testing::span;
+
+    #[test]
+    fn test_iou_thresholds() {
+        let text = "University of California at Berkeley";
+        let spans = vec![
+            span(text, 0, 24, "organization", 0.9),
+            span(text, 0, 36, "organization", 0.7),
+            span(text, 14, 24, "location", 0.8),
+            span(text, 28, 36, "location", 0.6),
+        ];
+        let texts = |iou_threshold: f32| -> Vec<String> {
+            NonMaxSuppression::new(iou_threshold)
+                .suppress(&spans, text)
+                .iter()
+                .map(|s| s.text().to_string())
+                .collect()
+        };
+        // any overlap is suppressed
+        assert_eq!(texts(0.0), vec!["University of California", "Berkeley"]);
+        // the long organization (IoU=0.67 with the best span) is suppressed, but not the nested location (IoU=0.42)
+        assert_eq!(
+            texts(0.5),
+            vec!["University of California", "California", "Berkeley"]
+        );
+        // nothing is suppressed
+        assert_eq!(texts(1.0).len(), spans.len());
+    }
+
+    #[test]
+    fn test_iou() {
+        assert_eq!(NonMaxSuppression::iou((0, 4), (0, 4)), 1.0);
+        assert_eq!(NonMaxSuppression::iou((0, 4), (2, 6)), 1.0 / 3.0);
+        assert_eq!(NonMaxSuppression::iou((0, 2), (2, 4)), 0.0);
+        // empty spans do not overlap anything (instead of giving NaN)
+        assert_eq!(NonMaxSuppression::iou((3, 3), (3, 3)), 0.0);
+    }
+
+    #[test]
+    fn test_char_offsets() {
+        // "Zürich" is 7 bytes but 6 characters: its IoU with "Zür" is 0.5 in characters, but 4/7 in bytes
+        let text = "Zürich";
+        let spans = vec![
+            Span::new(0, 0, 7, text.to_string(), "city".to_string(), 0.9),
+            Span::new(0, 0, 4, "Zür".to_string(), "city".to_string(), 0.8),
+        ];
+        assert_eq!(NonMaxSuppression::new(0.55).suppress(&spans, text).len(), 2);
+    }
+}
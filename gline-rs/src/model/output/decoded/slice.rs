@@ -0,0 +1,127 @@
+//! Optional step re-slicing the text of each span from the original input
+
+use super::SpanOutput;
+use crate::text::span::Span;
+use crate::util::error::IndexError;
+use crate::util::result::Result;
+use composable::Composable;
+
+/// Sets the text of each span to the slice of the original input at its offsets, so that
+/// `span.text() == &text[start..end]` is guaranteed whatever the previous steps did. An error is
+/// raised if the offsets of a span do not fall on character boundaries of its input.
+///
+/// This step does nothing if it is not enabled, so that it can always be part of a pipeline.
+pub struct SliceText {
+    enabled: bool,
+}
+
+impl SliceText {
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled }
+    }
+
+    /// Re-slices the spans of one sequence
+    pub fn slice(&self, text: &str, spans: Vec<Span>) -> Result<Vec<Span>> {
+        spans
+            .into_iter()
+            .map(|span| {
+                let (start, end) = span.offsets();
+                let slice = text.get(start..end).ok_or(IndexError::with(
+                    "span offsets do not fall on character boundaries of the text",
+                ))?;
+                Ok(span.with_text(slice.to_string()))
+            })
+            .collect()
+    }
+}
+
+/// Composable: SpanOutput => SpanOutput
+impl Composable<SpanOutput, SpanOutput> for SliceText {
+    fn apply(&self, input: SpanOutput) -> Result<SpanOutput> {
+        if !self.enabled {
+            return Ok(input);
+        }
+        let mut spans = Vec::with_capacity(input.spans.len());
+        for (text, sequence) in input.texts.iter().zip(input.spans) {
+            spans.push(self.slice(text, sequence)?);
+        }
+        Ok(SpanOutput { spans, ..input })
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::input::normalizer::{Normalization, NormalizingSplitter};
+    use crate::model::input::text::TextInput;
+    use crate::model::input::tokenized::TokenizedInput;
+    use crate::model::output::decoded::merge::MergeAdjacent;
+    use crate::model::pipeline::context::EntityContext;
+    use crate::text::splitter::RegexSplitter;
+
+    const CORPUS: [&str; 6] = [
+        "  Leading and trailing   spaces  ",
+        "Tabs\tand\t\tnew\nlines\r\nbetween words",
+        "Non\u{a0}breaking\u{2009}thin and\u{3000}ideographic spaces",
+        "Accents: Zoë  Saldaña visited Ålesund",
+        "Ｆｕｌｌｗｉｄｔｈ  ﬁ  ligatures and e\u{301}  combining marks",
+        "Emoji 👩‍🔬  scientists   in 東京 ,Paris( London )",
+    ];
+
+    /// Creates a span for each sequence of at most 3 words of each text, merges adjacent ones
+    /// (which may include whitespace), and re-slices them
+    fn all_spans(splitter: &impl crate::text::splitter::Splitter) -> Result<SpanOutput> {
+        let input = TextInput::from_str(&CORPUS, &["any"])?;
+        let tokenized = TokenizedInput::from(input, splitter, None)?;
+        let context = EntityContext {
+            num_words: tokenized.tokens.iter().map(Vec::len).max().unwrap_or(0),
+            texts: tokenized.texts,
+            tokens: tokenized.tokens,
+            entities: tokenized.entities,
+            sequence_ids: tokenized.sequence_ids,
+        };
+        let mut spans = Vec::new();
+        for (sequence, tokens) in context.tokens.iter().enumerate() {
+            let mut sequence_spans = Vec::new();
+            for start in 0..tokens.len() {
+                for end in start..std::cmp::min(start + 3, tokens.len()) {
+                    sequence_spans.push(context.create_span(sequence, start, end, 0, 0.9)?);
+                }
+            }
+            spans.push(sequence_spans);
+        }
+        let output = context.into_output(spans);
+        let output = MergeAdjacent::new(true).apply(output)?;
+        SliceText::new(true).apply(output)
+    }
+
+    #[test]
+    fn test_irregular_spacing() -> Result<()> {
+        let splitter = RegexSplitter::default();
+        for splitter in [
+            NormalizingSplitter::new(&splitter, Normalization::Nfc),
+            NormalizingSplitter::new(&splitter, Normalization::Nfkc),
+        ] {
+            let output = all_spans(&splitter)?;
+            assert!(!output.is_empty());
+            for (sequence, span) in output.iter() {
+                let (start, end) = span.offsets();
+                assert_eq!(span.text(), &CORPUS[sequence][start..end]);
+            }
+        }
+        let output = all_spans(&splitter)?;
+        for (sequence, span) in output.iter() {
+            let (start, end) = span.offsets();
+            assert_eq!(span.text(), &CORPUS[sequence][start..end]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_offsets() {
+        let text = "Zoë";
+        let span = Span::new(0, 0, 3, "Zo".to_string(), "person".to_string(), 0.9);
+        assert!(SliceText::new(true).slice(text, vec![span]).is_err());
+    }
+}
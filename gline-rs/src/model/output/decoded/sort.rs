@@ -1,22 +1,75 @@
 //! Sort spans by offsets (which is expected by greedy-search)
 
 use super::SpanOutput;
+use crate::text::span::Span;
 use crate::util::result::Result;
 use composable::Composable;
+use std::cmp::Ordering;
 
 #[derive(Default)]
 pub struct SpanSort {}
 
+impl SpanSort {
+    /// Total order on spans: by start, then end, offsets (as expected by greedy-search). Spans sharing the
+    /// same offsets are then ordered by decreasing probability, and finally by class name, so that the
+    /// result never depends on the initial order of the spans.
+    pub fn compare(s1: &Span, s2: &Span) -> Ordering {
+        s1.offsets()
+            .cmp(&s2.offsets())
+            .then_with(|| s2.probability().total_cmp(&s1.probability()))
+            .then_with(|| s1.class().cmp(s2.class()))
+    }
+}
+
 /// Composable: SpanOutput => SpanOutput
 impl Composable<SpanOutput, SpanOutput> for SpanSort {
     fn apply(&self, input: SpanOutput) -> Result<SpanOutput> {
         let mut spans = input.spans;
         for sequence in &mut spans {
             // "Unstable" sort (which is perfectly safe despite the name ;) is more efficient, and sufficient
-            // in our case as we don't need to preserve the initial order of equal elements. Also note that
-            // calling `cmp()` on a tuple does exactly what we ant here (sort by start, then end, offsets).
-            sequence.sort_unstable_by(|s1, s2| s1.offsets().cmp(&s2.offsets()));
+            // in our case as the order is total: equal elements are identical anyway.
+            sequence.sort_unstable_by(Self::compare);
+        }
+        Ok(SpanOutput { spans, ..input })
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::output::decoded::greedy::GreedySearch;
+
+    #[test]
+    fn test_ties() -> Result<()> {
+        let span = |start, end, class: &str| {
+            Span::new(
+                0,
+                start,
+                end,
+                "x".repeat(end - start),
+                class.to_string(),
+                0.5,
+            )
+        };
+        let spans = vec![
+            span(0, 4, "person"),
+            span(0, 4, "name"),
+            span(0, 8, "person"),
+            span(5, 8, "city"),
+            span(5, 8, "country"),
+        ];
+        let mut outputs = Vec::new();
+        for rotation in 0..spans.len() {
+            let mut sequence = spans.clone();
+            sequence.rotate_left(rotation);
+            let input = SpanOutput::new(vec![String::new()], vec![], vec![sequence]);
+            let output =
+                GreedySearch::new(true, false, false).apply(SpanSort::default().apply(input)?)?;
+            outputs.push(output.spans);
         }
-        Ok(SpanOutput::new(input.texts, input.entities, spans))
+        assert!(outputs.windows(2).all(|w| w[0] == w[1]));
+        assert_eq!(outputs[0][0].first().map(|s| s.class()), Some("name"));
+        Ok(())
     }
 }
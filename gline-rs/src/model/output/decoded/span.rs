@@ -1,12 +1,14 @@
 //! First step of span decoding (in span mode)
 
-use super::SpanOutput;
-use crate::model::output::tensors::TensorOutput;
+use super::{class_distribution, has_min_chars, window::WindowMerge, SpanOutput};
+use crate::model::output::tensors::{Logits, TensorOutput};
 use crate::model::pipeline::context::EntityContext;
 use crate::text::span::Span;
+use crate::util::error::IndexError;
 use crate::util::math::sigmoid;
 use crate::util::result::Result;
 use composable::Composable;
+use ndarray::ArrayView4;
 
 const TENSOR_LOGITS: &str = "logits";
 
@@ -14,9 +16,17 @@ const TENSOR_LOGITS: &str = "logits";
 ///
 /// See sections 2.1 and 2.3 of the [original paper](https://arxiv.org/abs/2311.08526).
 /// Note: greedy search is not included in this step and must be applied subsequently.
+///
+/// Expects raw (pre-sigmoid) `logits` of shape (batch_size, num_words, max_width, num_classes),
+/// as `f32` or half-precision values (see `Logits`). Models whose logits come out on
+/// another scale (typically quantized ones) can be calibrated with a temperature.
 pub struct TensorsToDecoded {
     threshold: f32,
     max_width: usize,
+    temperature: f32,
+    class_scores_threshold: Option<f32>,
+    min_span_chars: usize,
+    window_merge: WindowMerge,
 }
 
 impl TensorsToDecoded {
@@ -24,18 +34,43 @@ impl TensorsToDecoded {
         Self {
             threshold,
             max_width,
+            temperature: 1.0,
+            class_scores_threshold: None,
+            min_span_chars: 0,
+            window_merge: WindowMerge::default(),
         }
     }
 
+    /// Sets the temperature `T` applied to the logits before the sigmoid, i.e. `sigmoid(logit / T)` (default: 1.0)
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = temperature;
+        self
+    }
+
+    /// If set, each span carries the distribution of the classes whose probability reaches the given
+    /// threshold (see `Span::class_scores`), and not only its own class (default: None)
+    pub fn with_class_scores(mut self, class_scores_threshold: Option<f32>) -> Self {
+        self.class_scores_threshold = class_scores_threshold;
+        self
+    }
+
+    /// Drops the spans having less than `min_span_chars` characters, whitespace excluded (default: 0)
+    pub fn with_min_span_chars(mut self, min_span_chars: usize) -> Self {
+        self.min_span_chars = min_span_chars;
+        self
+    }
+
+    /// Sets how the spans found in overlapping windows are merged (default: only merging spans with the same offsets)
+    pub fn with_window_merge(mut self, window_merge: WindowMerge) -> Self {
+        self.window_merge = window_merge;
+        self
+    }
+
     pub fn outputs() -> [&'static str; 1] {
         [TENSOR_LOGITS]
     }
 
     fn decode(&self, input: &TensorOutput) -> Result<Vec<Vec<Span>>> {
-        // prepare output vector
-        let batch_size = input.context.texts.len();
-        let mut result: Vec<Vec<Span>> = Vec::new();
-
         // look for logits and check its shape
         let logits = input
             .tensors
@@ -43,58 +78,102 @@ impl TensorsToDecoded {
             .ok_or("logits not found in model output")?;
         self.check_shape(logits.shape()?, &input.context)?;
 
-        // extract the actual array
-        let array = logits.try_extract_tensor::<f32>()?;
+        self.decode_sequences(&Logits::extract(logits)?, &input.context)
+    }
+
+    /// Decodes the spans of each sequence given the logits, of shape (batch_size, num_words, max_width, num_classes).
+    pub fn decode_logits(
+        &self,
+        logits: ArrayView4<f32>,
+        context: &EntityContext,
+    ) -> Result<Vec<Vec<Span>>> {
+        self.decode_sequences(&Logits::F32(logits.into_dyn()), context)
+    }
 
-        // iterate over the sequences
-        for sequence_id in 0..batch_size {
-            // get a slice for the current sequence (1st dimension)
-            let sequence = array.slice(ndarray::s![sequence_id, .., .., ..]);
-            let num_tokens = input.context.tokens.get(sequence_id).unwrap().len();
-            //println!("{:?}", sequence.map(|x| crate::util::math::sigmoid(*x)));
+    /// Decodes the spans of each sequence in turn (see `Logits::sequence`).
+    ///
+    /// With the `rayon` feature enabled, the sequences are decoded in parallel.
+    fn decode_sequences(&self, logits: &Logits, context: &EntityContext) -> Result<Vec<Vec<Span>>> {
+        let batch_size = context.tokens.len();
 
-            // prepare the list of spans for this sequence
-            let mut spans = Vec::new();
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::prelude::*;
+            (0..batch_size)
+                .into_par_iter()
+                .map(|sequence_id| self.decode_sequence(logits, context, sequence_id))
+                .collect()
+        }
 
-            // iterate over all spans
-            for ((start, end, class), score) in sequence.indexed_iter() {
-                // check that the tokens actually exist in the current sequence (we could do better here, to avoid iterating over these ones)
-                if start >= num_tokens || start + end >= num_tokens {
-                    continue;
-                }
-                // check that the score is above threshold (otherwise continue)
-                let score = sigmoid(*score);
-                if score >= self.threshold {
-                    // if yes, create the span
-                    spans.push(input.context.create_span(
-                        sequence_id,
-                        start,
-                        start + end,
-                        class,
-                        score,
-                    )?);
+        #[cfg(not(feature = "rayon"))]
+        {
+            (0..batch_size)
+                .map(|sequence_id| self.decode_sequence(logits, context, sequence_id))
+                .collect()
+        }
+    }
+
+    fn decode_sequence(
+        &self,
+        logits: &Logits,
+        context: &EntityContext,
+        sequence_id: usize,
+    ) -> Result<Vec<Span>> {
+        // get the logits of the current sequence (1st dimension)
+        let sequence = logits.sequence(0, sequence_id);
+        let sequence = sequence.view().into_dimensionality::<ndarray::Ix3>()?;
+        let (num_words, max_width, num_classes) = sequence.dim();
+        let num_tokens = context
+            .tokens
+            .get(sequence_id)
+            .ok_or(IndexError::new("meta.tokens", sequence_id))?
+            .len();
+
+        // prepare the list of spans for this sequence
+        let mut spans = Vec::new();
+
+        // iterate over the spans whose tokens actually exist in the current sequence (padding is skipped)
+        for start in 0..std::cmp::min(num_words, num_tokens) {
+            for width in 0..std::cmp::min(max_width, num_tokens - start) {
+                for class in 0..num_classes {
+                    // check that the score is above threshold (otherwise continue)
+                    let score = sigmoid(sequence[[start, width, class]] / self.temperature);
+                    if score >= self.threshold {
+                        // if yes, create the span unless it is too short
+                        let span =
+                            context.create_span(sequence_id, start, start + width, class, score)?;
+                        if !has_min_chars(&span, self.min_span_chars) {
+                            continue;
+                        }
+                        // along with its class distribution if requested
+                        let class_scores = self.class_scores_threshold.map(|threshold| {
+                            let scores = (0..num_classes)
+                                .map(|c| sigmoid(sequence[[start, width, c]] / self.temperature));
+                            class_distribution(&context.entities, scores, threshold)
+                        });
+                        spans.push(span.with_class_scores(class_scores));
+                    }
                 }
             }
-
-            // add the list of spans for this sequence
-            result.push(spans);
         }
 
-        // return
-        Ok(result)
+        Ok(spans)
     }
 
     /// Checks coherence of the output shape
     /// Expected shape is (batch_size, num_words, num_spans, num_classes)
     fn check_shape(&self, actual_shape: Vec<i64>, context: &EntityContext) -> Result<()> {
         let expected_shape = vec![
-            context.texts.len() as i64,
+            context.tokens.len() as i64,
             context.num_words as i64,
             self.max_width as i64,
             context.entities.len() as i64,
         ];
         if actual_shape != expected_shape {
-            Err("unexpected logits shape".into())
+            Err(format!(
+                "unexpected logits shape: expected {expected_shape:?}, got {actual_shape:?}"
+            )
+            .into())
         } else {
             Ok(())
         }
@@ -104,10 +183,203 @@ impl TensorsToDecoded {
 impl Composable<TensorOutput<'_>, SpanOutput> for TensorsToDecoded {
     fn apply(&self, input: TensorOutput) -> Result<SpanOutput> {
         let decoded = self.decode(&input)?;
-        Ok(SpanOutput::new(
-            input.context.texts,
-            input.context.entities,
-            decoded,
-        ))
+        Ok(input.context.into_output_with(decoded, self.window_merge))
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::params::Parameters;
+    use crate::text::token::Token;
+
+    /// Context of a single text, whose words are separated by spaces
+    fn context(text: &str, entities: &[&str]) -> EntityContext {
+        let mut start = 0;
+        let tokens: Vec<Token> = text
+            .split(' ')
+            .map(|word| {
+                let token = Token::new(start, start + word.len(), word);
+                start += word.len() + 1;
+                token
+            })
+            .collect();
+        EntityContext {
+            texts: vec![text.to_string()],
+            num_words: tokens.len(),
+            tokens: vec![tokens],
+            entities: entities.iter().map(|entity| entity.to_string()).collect(),
+            sequence_ids: vec![0],
+        }
+    }
+
+    #[test]
+    fn test_class_scores() -> Result<()> {
+        #![allow(clippy::unwrap_used)]
+        let context = context("Paris", &["person", "city", "company"]);
+        // logits of shape (1, 1, 1, 3): sigmoid gives ~0.27, ~0.88 and ~0.05
+        let logits = ndarray::Array4::from_shape_vec((1, 1, 1, 3), vec![-1.0, 2.0, -3.0])?;
+
+        let spans = TensorsToDecoded::new(0.5, 1).decode_logits(logits.view(), &context)?;
+        assert_eq!(spans[0].len(), 1);
+        assert!(spans[0][0].class_scores().is_none());
+
+        let spans = TensorsToDecoded::new(0.5, 1)
+            .with_class_scores(Some(0.2))
+            .decode_logits(logits.view(), &context)?;
+        let class_scores = spans[0][0].class_scores().unwrap();
+        let classes: Vec<&str> = class_scores.iter().map(|(c, _)| c.as_str()).collect();
+        assert_eq!(classes, vec!["city", "person"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_half_precision_logits() -> Result<()> {
+        let context = context("Paris", &["person", "city"]);
+        let values = [-1.0, 2.0];
+        let logits = ndarray::Array4::from_shape_vec((1, 1, 1, 2), values.to_vec())?;
+        let half_logits = logits.mapv(half::f16::from_f32).into_dyn();
+
+        let decoder = TensorsToDecoded::new(0.5, 1);
+        let spans = decoder.decode_logits(logits.view(), &context)?;
+        let half_spans = decoder.decode_sequences(&Logits::F16(half_logits.view()), &context)?;
+        assert_eq!(half_spans[0].len(), 1);
+        assert_eq!(half_spans[0][0].class(), spans[0][0].class());
+        assert_eq!(half_spans[0][0].probability(), spans[0][0].probability());
+        Ok(())
+    }
+
+    #[test]
+    fn test_keep_sub_threshold() -> Result<()> {
+        let context = context("Paris", &["person", "city"]);
+        let logits = ndarray::Array4::from_shape_vec((1, 1, 1, 2), vec![-1.0, 2.0])?;
+        let params = Parameters::default().with_keep_sub_threshold(true);
+        let spans = TensorsToDecoded::new(params.decoding_threshold(), 1)
+            .decode_logits(logits.view(), &context)?;
+        assert_eq!(spans[0].len(), 2);
+        assert!(spans[0][0].probability() < params.threshold);
+        Ok(())
+    }
+
+    #[test]
+    fn test_quantized_model() -> Result<()> {
+        // minimal ONNX model: graph { node { op_type = "DynamicQuantizeLinear" } output { name = "logits" } }
+        let op_type = b"DynamicQuantizeLinear";
+        let node = [&[0x22, op_type.len() as u8], &op_type[..]].concat();
+        let graph = [
+            &[0x0a, node.len() as u8],
+            &node[..],
+            &[0x62, 0x08, 0x0a, 0x06],
+            TENSOR_LOGITS.as_bytes(),
+        ]
+        .concat();
+        let model = [&[0x08, 0x08, 0x3a, graph.len() as u8], &graph[..]].concat();
+        let info = crate::model::info::ModelInfo::from_graph_bytes(&model)?;
+        assert!(info.quantized);
+
+        let context = context("Paris", &["person", "city"]);
+        // the quantized model gives logits twice as large as the reference (sigmoid gives ~0.88 and ~0.95)
+        let logits = ndarray::Array4::from_shape_vec((1, 1, 1, 2), vec![2.0, 3.0])?;
+        let quantized_logits = logits.mapv(|logit| logit * 2.0);
+        let decode = |params: &Parameters, logits: &ndarray::Array4<f32>| {
+            TensorsToDecoded::new(params.threshold, 1)
+                .with_temperature(params.decoding_temperature())
+                .decode_logits(logits.view(), &context)
+        };
+        let params = Parameters::default()
+            .with_threshold(0.9)
+            .with_quantized_temperature(Some(2.0));
+        let expected = decode(&params, &logits)?;
+        assert_eq!(expected[0].len(), 1);
+        assert_eq!(expected[0][0].class(), "city");
+
+        // uncalibrated, the quantized logits are overconfident
+        assert_eq!(decode(&params, &quantized_logits)?[0].len(), 2);
+        // calibrated once the model is known to be quantized
+        let params = params.for_model(&info);
+        assert_eq!(params.decoding_temperature(), 2.0);
+        let spans = decode(&params, &quantized_logits)?;
+        assert_eq!(spans[0].len(), 1);
+        assert_eq!(spans[0][0].class(), "city");
+        assert_eq!(spans[0][0].probability(), expected[0][0].probability());
+        // an explicit setting wins over the detection
+        let params = params.with_quantized(Some(false)).for_model(&info);
+        assert_eq!(params.decoding_temperature(), 1.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_multi_threshold() -> Result<()> {
+        use crate::model::output::decoded::sort::SpanSort;
+        let context = || context("New York City", &["city", "state"]);
+        // overlapping spans of probabilities from ~0.05 to ~0.95, for both classes
+        let values: Vec<f32> = (0..12).map(|i| (i as f32 - 5.5) / 2.0).rev().collect();
+        let logits = ndarray::Array4::from_shape_vec((1, 3, 2, 2), values)?;
+        let params = Parameters::default();
+        // same post-processing as the span pipeline (see `predict_multi_threshold`)
+        let decode = |threshold: f32| -> Result<SpanOutput> {
+            let spans =
+                TensorsToDecoded::new(threshold, 2).decode_logits(logits.view(), &context())?;
+            composable::composed![SpanSort::default(), params.overlap_resolution()]
+                .apply(context().into_output(spans))
+        };
+        let all = decode(
+            params
+                .clone()
+                .with_keep_sub_threshold(true)
+                .decoding_threshold(),
+        )?;
+        for threshold in [0.1, 0.3, 0.5, 0.7, 0.9] {
+            let expected = decode(threshold)?;
+            assert_eq!(all.at_threshold(threshold).spans, expected.spans);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_min_span_chars() -> Result<()> {
+        let text = "I saw Paris";
+        let context = context(text, &["city"]);
+        // noisy logits of shape (1, 3, 1, 1): every single word is a city
+        let logits = ndarray::Array4::from_shape_vec((1, 3, 1, 1), vec![2.0, 2.0, 2.0])?;
+        let spans = TensorsToDecoded::new(0.5, 1).decode_logits(logits.view(), &context)?;
+        assert_eq!(spans[0].len(), 3);
+        let spans = TensorsToDecoded::new(0.5, 1)
+            .with_min_span_chars(4)
+            .decode_logits(logits.view(), &context)?;
+        let texts: Vec<&str> = spans[0].iter().map(|s| s.text()).collect();
+        assert_eq!(texts, vec!["Paris"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_classes_are_input_labels() -> Result<()> {
+        use crate::model::input::{encoded, prompt, text::TextInput, tokenized};
+        use crate::text::tokenizer::testing::CharTokenizer;
+        let splitter = crate::text::splitter::RegexSplitter::default();
+        let labels = ["company / organization", " Person", "PERSON"];
+        // same pre-processing as the span pipeline (except the tensors), with label normalization
+        let encoded = composable::composed![
+            tokenized::RawToTokenized::new(&splitter, None),
+            prompt::TokenizedToPrompt::new(Default::default()).with_label_normalization(true),
+            encoded::PromptsToEncoded::new(&CharTokenizer)
+        ]
+        .apply(TextInput::from_str(&["Acme hired Bob"], &labels)?)?;
+        let context = EntityContext {
+            texts: encoded.texts,
+            tokens: encoded.tokens,
+            entities: encoded.entities,
+            sequence_ids: encoded.sequence_ids,
+            num_words: encoded.num_words,
+        };
+        // every span is found for every class
+        let logits = ndarray::Array4::from_elem((1, 3, 2, labels.len()), 2.0);
+        let spans = TensorsToDecoded::new(0.5, 2).decode_logits(logits.view(), &context)?;
+        assert!(spans[0].iter().all(|span| labels.contains(&span.class())));
+        for label in labels {
+            assert!(spans[0].iter().any(|span| span.class() == label));
+        }
+        Ok(())
     }
 }
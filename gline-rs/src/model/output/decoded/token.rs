@@ -1,7 +1,7 @@
 //! First step of span decoding (in token mode)
 
-use super::SpanOutput;
-use crate::model::output::tensors::TensorOutput;
+use super::{class_distribution, has_min_chars, window::WindowMerge, SpanOutput};
+use crate::model::output::tensors::{Logits, TensorOutput};
 use crate::model::pipeline::context::EntityContext;
 use crate::text::span::Span;
 use crate::util::math::sigmoid;
@@ -20,13 +20,53 @@ const TENSOR_LOGITS: &str = "logits";
 ///
 /// Rq: greedy search must be applied the same way as in span mode (shall be called as a subsequent
 /// step in the pipeline).
+///
+/// Expects raw (pre-sigmoid) `logits` of shape (3, batch_size, num_words, num_classes), holding
+/// the start, end and inside scores, as `f32` or half-precision values (see `Logits`).
+/// Models whose logits come out on another scale (typically quantized ones) can be calibrated
+/// with a temperature.
 pub struct TensorsToDecoded {
     threshold: f32,
+    temperature: f32,
+    class_scores_threshold: Option<f32>,
+    min_span_chars: usize,
+    window_merge: WindowMerge,
 }
 
 impl TensorsToDecoded {
     pub fn new(threshold: f32) -> Self {
-        Self { threshold }
+        Self {
+            threshold,
+            temperature: 1.0,
+            class_scores_threshold: None,
+            min_span_chars: 0,
+            window_merge: WindowMerge::default(),
+        }
+    }
+
+    /// Sets the temperature `T` applied to the logits before the sigmoid, i.e. `sigmoid(logit / T)` (default: 1.0)
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = temperature;
+        self
+    }
+
+    /// If set, each span carries the distribution of the classes whose score reaches the given threshold
+    /// (see `Span::class_scores`), the score of a class being the mean of its inside scores (default: None)
+    pub fn with_class_scores(mut self, class_scores_threshold: Option<f32>) -> Self {
+        self.class_scores_threshold = class_scores_threshold;
+        self
+    }
+
+    /// Drops the spans having less than `min_span_chars` characters, whitespace excluded (default: 0)
+    pub fn with_min_span_chars(mut self, min_span_chars: usize) -> Self {
+        self.min_span_chars = min_span_chars;
+        self
+    }
+
+    /// Sets how the spans found in overlapping windows are merged (default: only merging spans with the same offsets)
+    pub fn with_window_merge(mut self, window_merge: WindowMerge) -> Self {
+        self.window_merge = window_merge;
+        self
     }
 
     pub fn outputs() -> [&'static str; 1] {
@@ -35,7 +75,7 @@ impl TensorsToDecoded {
 
     fn decode(&self, input: &TensorOutput) -> Result<Vec<Vec<Span>>> {
         // prepare output vector
-        let batch_size = input.context.texts.len();
+        let batch_size = input.context.tokens.len();
         let mut result: Vec<Vec<Span>> =
             std::iter::repeat_with(Vec::new).take(batch_size).collect();
 
@@ -47,13 +87,13 @@ impl TensorsToDecoded {
         self.check_shape(logits.shape()?, &input.context)?;
 
         // extract the actual array
-        let array = logits.try_extract_tensor::<f32>()?;
-        //println!("{:?}", array.map(|x| crate::util::math::sigmoid(*x)));
+        let logits = Logits::extract(logits)?;
 
         // iterate over sequences
         for sequence_id in 0..batch_size {
-            // get a slice for the current sequence (2nd dimension)
-            let scores = array.slice(ndarray::s![.., sequence_id, .., ..]);
+            // get the scores of the current sequence (2nd dimension)
+            let scores = logits.sequence(1, sequence_id);
+            let scores = scores.view().into_dimensionality::<ndarray::Ix3>()?;
 
             // get slices for start, end, and inside scores (1st dimension)
             let scores_start = scores.slice(ndarray::s![0, .., ..]);
@@ -68,12 +108,22 @@ impl TensorsToDecoded {
                 if score < self.threshold {
                     continue;
                 }
-                // create actual span
+                // create actual span, unless it is too short
                 let (start_token, end_token, class) = span;
                 let span =
                     input
                         .context
                         .create_span(sequence_id, start_token, end_token, class, score)?;
+                if !has_min_chars(&span, self.min_span_chars) {
+                    continue;
+                }
+                // along with its class distribution if requested
+                let class_scores = self.class_scores_threshold.map(|threshold| {
+                    let scores = (0..scores_inside.dim().1)
+                        .map(|c| self.mean_inside_score(start_token, end_token, c, &scores_inside));
+                    class_distribution(&input.context.entities, scores, threshold)
+                });
+                let span = span.with_class_scores(class_scores);
                 result.get_mut(sequence_id).unwrap().push(span);
             }
         }
@@ -95,12 +145,14 @@ impl TensorsToDecoded {
         let mut result = Vec::new();
         for class in 0..num_classes {
             for start in 0..num_tokens {
-                let score_start = sigmoid(*scores_start.get((start, class)).unwrap());
+                let score_start =
+                    sigmoid(*scores_start.get((start, class)).unwrap() / self.temperature);
                 if score_start < self.threshold {
                     continue;
                 }
                 for end in start..num_tokens {
-                    let score_end = sigmoid(*scores_end.get((end, class)).unwrap());
+                    let score_end =
+                        sigmoid(*scores_end.get((end, class)).unwrap() / self.temperature);
                     if score_end < self.threshold {
                         continue;
                     }
@@ -123,7 +175,7 @@ impl TensorsToDecoded {
         assert!(end >= start);
         let mut sum = 0f32;
         for i in start..end + 1 {
-            let score_inside = sigmoid(*scores_inside.get((i, class)).unwrap());
+            let score_inside = sigmoid(*scores_inside.get((i, class)).unwrap() / self.temperature);
             if score_inside < self.threshold {
                 return 0.;
             }
@@ -132,18 +184,35 @@ impl TensorsToDecoded {
         sum / ((end - start + 1) as f32)
     }
 
+    /// Mean of the inside scores of a span for the given class (without any threshold)
+    fn mean_inside_score(
+        &self,
+        start: usize,
+        end: usize,
+        class: usize,
+        scores_inside: &ndarray::ArrayView2<f32>,
+    ) -> f32 {
+        let sum: f32 = (start..end + 1)
+            .map(|i| sigmoid(scores_inside[[i, class]] / self.temperature))
+            .sum();
+        sum / ((end - start + 1) as f32)
+    }
+
     /// Checks coherence of the output shape.
     /// Expected shape is (3, batch_size, num_words, num_classes).
     /// The first dimension is related to `start`, `end` and `inside` positions in that order.
     fn check_shape(&self, actual_shape: Vec<i64>, context: &EntityContext) -> Result<()> {
         let expected_shape = vec![
             3,
-            context.texts.len() as i64,
+            context.tokens.len() as i64,
             context.num_words as i64,
             context.entities.len() as i64,
         ];
         if actual_shape != expected_shape {
-            Err("unexpected logits shape".into())
+            Err(format!(
+                "unexpected logits shape: expected {expected_shape:?}, got {actual_shape:?}"
+            )
+            .into())
         } else {
             Ok(())
         }
@@ -153,10 +222,6 @@ impl TensorsToDecoded {
 impl Composable<TensorOutput<'_>, SpanOutput> for TensorsToDecoded {
     fn apply(&self, input: TensorOutput) -> Result<SpanOutput> {
         let decoded = self.decode(&input)?;
-        Ok(SpanOutput::new(
-            input.context.texts,
-            input.context.entities,
-            decoded,
-        ))
+        Ok(input.context.into_output_with(decoded, self.window_merge))
     }
 }
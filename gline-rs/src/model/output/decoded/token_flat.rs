@@ -119,10 +119,6 @@ impl Composable<TensorOutput<'_>, SpanOutput> for TensorsToDecoded {
             .ok_or("logits not found in model output")?;
         let (_shape, logits) = logits.try_extract_raw_tensor::<f32>()?;
         let spans = self.decoder.decode(logits, &input.context)?;
-        Ok(SpanOutput::new(
-            input.context.texts,
-            input.context.entities,
-            spans,
-        ))
+        Ok(input.context.into_output(spans))
     }
 }
@@ -0,0 +1,155 @@
+//! Merging of the spans found in overlapping windows (see `TokenizedInput::from_windows` and `GLiNER::inference_stream`)
+
+use crate::text::span::Span;
+
+/// How the spans found in overlapping windows of a same sequence are merged.
+///
+/// By default, only the spans found twice with the same offsets (relative to the whole sequence) and class are
+/// merged. With the other policies, two spans are considered as the same entity if they have the same class,
+/// come from different windows, and share at least one byte. This can only happen in the region shared by the
+/// windows, where an entity may be found twice with slightly different offsets or probabilities.
+/// Overlapping spans of different classes are left untouched (see the overlap resolution parameters instead).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WindowMerge {
+    /// Keeps the most probable of two spans with the same offsets, spans with different offsets being all kept
+    #[default]
+    ExactOffsets,
+    /// Keeps the most probable span (the first one found in case of tie)
+    KeepHighestScore,
+    /// Keeps the longest span (the most probable one in case of tie)
+    KeepLongest,
+    /// Replaces the spans with a single one covering all of them, with the highest probability
+    Union,
+}
+
+impl WindowMerge {
+    /// Merges the spans found in a window into the spans kept from the previous windows of the same sequence,
+    /// all the offsets being relative to the whole sequence. Windows are supposed to be merged in order.
+    pub fn merge(&self, kept: &mut Vec<Span>, window: Vec<Span>) {
+        // only spans of the previous windows are candidates
+        let previous = kept.len();
+        for span in window {
+            let same = kept[..previous]
+                .iter_mut()
+                .find(|k| k.class() == span.class() && self.same_entity(k, &span));
+            match same {
+                Some(same) => self.resolve(same, span),
+                None => kept.push(span),
+            }
+        }
+    }
+
+    fn same_entity(&self, a: &Span, b: &Span) -> bool {
+        match self {
+            Self::ExactOffsets => a.same_offsets(b),
+            _ => a.strictly_overlaps(b),
+        }
+    }
+
+    /// Merges the given span into the kept one, which it overlaps
+    fn resolve(&self, kept: &mut Span, span: Span) {
+        match self {
+            Self::ExactOffsets | Self::KeepHighestScore => {
+                if span.probability() > kept.probability() {
+                    *kept = span;
+                }
+            }
+            Self::KeepLongest => {
+                if (Self::len(&span), span.probability()) > (Self::len(kept), kept.probability()) {
+                    *kept = span;
+                }
+            }
+            Self::Union => *kept = Self::union(kept, &span),
+        }
+    }
+
+    fn len(span: &Span) -> usize {
+        let (start, end) = span.offsets();
+        end - start
+    }
+
+    /// Span covering two overlapping spans, whose text is rebuilt from theirs (since both are slices of the same sequence)
+    fn union(a: &Span, b: &Span) -> Span {
+        let (first, second) = if a.offsets() <= b.offsets() {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        let (start, first_end) = first.offsets();
+        let (second_start, second_end) = second.offsets();
+        let mut text = first.text().to_string();
+        if second_end > first_end {
+            text.push_str(
+                second
+                    .text()
+                    .get(first_end - second_start..)
+                    .unwrap_or_default(),
+            );
+        }
+        Span::new(
+            first.sequence(),
+            start,
+            first_end.max(second_end),
+            text,
+            first.class().to_string(),
+            first.probability().max(second.probability()),
+        )
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(start: usize, end: usize, text: &str, class: &str, probability: f32) -> Span {
+        Span::new(0, start, end, text.into(), class.into(), probability)
+    }
+
+    #[test]
+    fn test_window_merge() {
+        // "the New York City council", the second window starting at "York"
+        let first = vec![span(4, 12, "New York", "city", 0.9)];
+        let second = vec![
+            span(8, 17, "York City", "city", 0.6),
+            span(8, 17, "York City", "place", 0.7),
+            span(18, 25, "council", "org", 0.8),
+        ];
+        let merged = |merge: WindowMerge| {
+            let mut kept = Vec::new();
+            merge.merge(&mut kept, first.clone());
+            merge.merge(&mut kept, second.clone());
+            kept.iter()
+                .map(|s| (s.text().to_string(), s.class().to_string(), s.probability()))
+                .collect::<Vec<_>>()
+        };
+        let others = [
+            ("York City".into(), "place".into(), 0.7),
+            ("council".into(), "org".into(), 0.8),
+        ];
+        let expected = |text: &str, probability| {
+            let mut expected = vec![(text.to_string(), "city".to_string(), probability)];
+            expected.extend(others.iter().cloned());
+            expected
+        };
+        let mut exact = vec![("New York".into(), "city".into(), 0.9)];
+        exact.extend(expected("York City", 0.6));
+        assert_eq!(merged(WindowMerge::ExactOffsets), exact);
+        assert_eq!(
+            merged(WindowMerge::KeepHighestScore),
+            expected("New York", 0.9)
+        );
+        assert_eq!(merged(WindowMerge::KeepLongest), expected("York City", 0.6));
+        assert_eq!(merged(WindowMerge::Union), expected("New York City", 0.9));
+        // spans of a same window are never merged
+        let mut kept = Vec::new();
+        WindowMerge::Union.merge(&mut kept, second.iter().chain(&second).cloned().collect());
+        assert_eq!(kept.len(), 6);
+        // with the default policy, only spans with the same offsets are merged
+        let mut kept = Vec::new();
+        WindowMerge::default().merge(&mut kept, first.clone());
+        WindowMerge::default().merge(&mut kept, vec![span(4, 12, "New York", "city", 0.95)]);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].probability(), 0.95);
+    }
+}
@@ -2,4 +2,5 @@
 
 pub mod decoded;
 pub mod relation;
+pub mod sentence;
 pub mod tensors;
@@ -4,14 +4,23 @@ use crate::model::pipeline::context::RelationContext;
 use crate::text::span::Span;
 use crate::util::result::Result;
 use composable::Composable;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
+/// Represents the final output of relation extraction, as a list of relations for each input sequence
+#[derive(Debug, Serialize, Deserialize)]
 pub struct RelationOutput {
     pub texts: Vec<String>,
     pub entities: Vec<String>,
     pub relations: Vec<Vec<Relation>>,
+    /// Rejected relations with the reason of their rejection, for each sequence
+    /// (always empty unless the `keep_rejected_relations` parameter is set)
+    #[serde(default)]
+    pub rejected: Vec<Vec<(Relation, String)>>,
 }
 
-#[derive(Debug, Clone)]
+/// Subject or object of a relation, with its offsets in the text
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RelationEntity {
     pub text: String,
     pub label: String,
@@ -30,8 +39,41 @@ impl RelationEntity {
             probability,
         }
     }
+
+    /// Builds the entity from its actual occurrence if known, and from the entities of the context having the
+    /// same text otherwise (falling back to the given probability, and to empty offsets and label)
+    fn from_context(
+        text: String,
+        occurrence: Option<&Span>,
+        context: &RelationContext,
+        probability: f32,
+    ) -> Self {
+        if let Some(span) = occurrence {
+            let (start, end) = span.offsets();
+            return Self::new(
+                text,
+                span.class().to_string(),
+                start,
+                end,
+                span.probability(),
+            );
+        }
+        let label = context
+            .entity_labels
+            .get(&text)
+            .and_then(|labels| labels.iter().next().cloned())
+            .unwrap_or_default();
+        let (start, end) = context.entity_offsets.get(&text).copied().unwrap_or((0, 0));
+        let probability = context
+            .entity_probabilities
+            .get(&text)
+            .copied()
+            .unwrap_or(probability);
+        Self::new(text, label, start, end, probability)
+    }
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Relation {
     class: String,
     subject: RelationEntity,
@@ -40,54 +82,49 @@ pub struct Relation {
     start: usize,
     end: usize,
     probability: f32,
+    /// Whether the schema allows the labels of the subject and object (only false when all label pairs are scored)
+    #[serde(default = "default_schema_allowed")]
+    schema_allowed: bool,
+}
+
+fn default_schema_allowed() -> bool {
+    true
 }
 
 impl Relation {
+    /// Builds a relation from a span of the relation pipeline. The probabilities of the subject and object are
+    /// the ones of their detection as entities (falling back to the probability of the relation if unknown).
+    ///
+    /// The object is the entity of the sequence found at the offsets of the span. The subject being only known by
+    /// its text, it is the occurrence of this text which is the closest to the object. If an entity cannot be found
+    /// among the ones of the sequence, it is looked up by its text (see `RelationContext`).
     pub fn from(span: Span, context: &RelationContext) -> Result<Self> {
         let (subject_text, class) = Self::decode(span.class())?;
         let object_text = span.text().to_string();
         let probability = span.probability();
 
-        let subject_label = context
-            .entity_labels
-            .get(&subject_text)
-            .and_then(|labels| labels.iter().next().cloned())
-            .unwrap_or_default();
-
-        let (subject_start, subject_end) = context
-            .entity_offsets
-            .get(&subject_text)
-            .copied()
-            .unwrap_or((0, 0));
-
-        let object_label = context
-            .entity_labels
-            .get(&object_text)
-            .and_then(|labels| labels.iter().next().cloned())
-            .unwrap_or_default();
-
-        let (object_start, object_end) = context
-            .entity_offsets
-            .get(&object_text)
-            .copied()
-            .unwrap_or((0, 0));
-
-        let subject = RelationEntity::new(
-            subject_text,
-            subject_label,
-            subject_start,
-            subject_end,
-            probability,
-        );
-        let object = RelationEntity::new(
-            object_text,
-            object_label,
-            object_start,
-            object_end,
-            probability,
-        );
-
         let (start, end) = span.offsets();
+        let object_offsets = (
+            start.saturating_sub(context.text_offset),
+            end.saturating_sub(context.text_offset),
+        );
+        let entities = context
+            .entity_spans
+            .get(span.sequence())
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let object_occurrence = entities
+            .iter()
+            .find(|entity| entity.offsets() == object_offsets && entity.text() == object_text);
+        let subject_occurrence = entities
+            .iter()
+            .filter(|entity| entity.text() == subject_text)
+            .min_by_key(|entity| Self::distance(entity.offsets(), object_offsets));
+
+        let subject =
+            RelationEntity::from_context(subject_text, subject_occurrence, context, probability);
+        let object =
+            RelationEntity::from_context(object_text, object_occurrence, context, probability);
 
         Ok(Self {
             class,
@@ -97,6 +134,7 @@ impl Relation {
             start,
             end,
             probability,
+            schema_allowed: true,
         })
     }
 
@@ -124,6 +162,17 @@ impl Relation {
         self.probability
     }
 
+    /// Whether the schema allows this relation between the labels of its subject and object. This is always
+    /// the case unless the `all_relation_pairs` parameter is set.
+    pub fn is_schema_allowed(&self) -> bool {
+        self.schema_allowed
+    }
+
+    /// Number of bytes between two spans (0 if they overlap)
+    fn distance((start1, end1): (usize, usize), (start2, end2): (usize, usize)) -> usize {
+        start2.saturating_sub(end1).max(start1.saturating_sub(end2))
+    }
+
     fn decode(rel_class: &str) -> Result<(String, String)> {
         let split: Vec<&str> = rel_class.split(" <> ").collect();
         if split.len() != 2 {
@@ -159,11 +208,61 @@ impl std::fmt::Display for RelationOutput {
 
 pub struct SpanOutputToRelationOutput<'a> {
     schema: &'a RelationSchema,
+    threshold: Option<f32>,
+    keep_rejected: bool,
+    dedup: bool,
+    all_pairs: bool,
 }
 
 impl<'a> SpanOutputToRelationOutput<'a> {
     pub fn new(schema: &'a RelationSchema) -> Self {
-        Self { schema }
+        Self {
+            schema,
+            threshold: None,
+            keep_rejected: false,
+            dedup: true,
+            all_pairs: false,
+        }
+    }
+
+    /// Rejects relations whose probability is below the given threshold (if any)
+    pub fn with_threshold(mut self, threshold: Option<f32>) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Keeps rejected relations in the output, along with the reason of their rejection
+    pub fn with_rejected(mut self, keep_rejected: bool) -> Self {
+        self.keep_rejected = keep_rejected;
+        self
+    }
+
+    /// Reports identical relations of a sequence only once (default: true, see `dedup`)
+    pub fn with_dedup(mut self, dedup: bool) -> Self {
+        self.dedup = dedup;
+        self
+    }
+
+    /// Keeps the relations which are not allowed by the schema, tagging them as such (see `Relation::is_schema_allowed`).
+    /// The entities must then be prepared accordingly (see `SpanOutputToRelationInput::with_all_pairs`).
+    pub fn with_all_pairs(mut self, all_pairs: bool) -> Self {
+        self.all_pairs = all_pairs;
+        self
+    }
+
+    /// Whether the subject and the object of the relation are the same entity (same text, offsets and label),
+    /// which is only allowed for reflexive relations
+    fn is_disallowed_self_relation(&self, relation: &Relation) -> bool {
+        let (subject, object) = (&relation.subject, &relation.object);
+        let same_entity = subject.text == object.text
+            && (subject.start, subject.end) == (object.start, object.end)
+            && subject.label == object.label;
+        same_entity
+            && !self
+                .schema
+                .relations()
+                .get(relation.class())
+                .is_some_and(|spec| spec.is_reflexive())
     }
 
     fn is_valid(&self, relation: &Relation, context: &RelationContext) -> Result<bool> {
@@ -178,13 +277,122 @@ impl<'a> SpanOutputToRelationOutput<'a> {
                 ))
             })?;
 
-        // try to get the object labels from context
-        if let Some(object_labels) = context.entity_labels.get(&relation.object.text) {
-            return Ok(spec.allows_one_of_objects(object_labels));
+        // try to get the object labels from context, and fall back to the label in the predicted object
+        let allows_object = match context.entity_labels.get(&relation.object.text) {
+            Some(object_labels) => spec.allows_one_of_objects(object_labels),
+            None => spec.allows_object(&relation.object.label),
+        };
+        let allows_subject = || match context.entity_labels.get(&relation.subject.text) {
+            Some(subject_labels) => spec.allows_one_of_subjects(subject_labels),
+            None => spec.allows_subject(&relation.subject.label),
+        };
+        if !spec.is_symmetric() {
+            // subjects are already filtered when preparing the labels, unless all pairs are considered
+            return Ok(allows_object && (!self.all_pairs || allows_subject()));
         }
 
-        // fall back to the label in the predicted object
-        Ok(spec.allows_object(&relation.object.label))
+        // symmetric relations are valid in either direction
+        let allows_reversed = match (
+            context.entity_labels.get(&relation.object.text),
+            context.entity_labels.get(&relation.subject.text),
+        ) {
+            (Some(object_labels), Some(subject_labels)) => {
+                spec.allows_one_of_subjects(object_labels)
+                    && spec.allows_one_of_objects(subject_labels)
+            }
+            _ => {
+                spec.allows_subject(&relation.object.label)
+                    && spec.allows_object(&relation.subject.label)
+            }
+        };
+        Ok((allows_subject() && allows_object) || allows_reversed)
+    }
+
+    /// Reports a rejected relation (at debug level, with structured fields)
+    fn log_rejected(relation: &Relation, reason: &str) {
+        log::debug!(
+            relation = relation.class(),
+            subject = relation.subject.text.as_str(),
+            subject_label = relation.subject.label.as_str(),
+            subject_start = relation.subject.start,
+            subject_end = relation.subject.end,
+            object = relation.object.text.as_str(),
+            object_label = relation.object.label.as_str(),
+            object_start = relation.object.start,
+            object_end = relation.object.end,
+            score = relation.probability,
+            reason = reason;
+            "relation rejected: '{}' ({} -> {}), {}",
+            relation.class(),
+            relation.subject.text,
+            relation.object.text,
+            reason
+        );
+    }
+
+    /// Keeps one relation per class, subject offsets and object offsets (the most probable one), as
+    /// several entity spans may resolve to the same surface text
+    fn dedup(relations: Vec<Relation>) -> Vec<Relation> {
+        type Key = (String, (usize, usize), (usize, usize));
+        let mut result: Vec<Relation> = Vec::with_capacity(relations.len());
+        let mut keys: HashMap<Key, usize> = HashMap::new();
+        for relation in relations {
+            let key = (
+                relation.class.clone(),
+                (relation.subject.start, relation.subject.end),
+                (relation.object.start, relation.object.end),
+            );
+            match keys.get(&key) {
+                Some(index) => {
+                    if relation.probability > result[*index].probability {
+                        result[*index] = relation;
+                    }
+                }
+                None => {
+                    keys.insert(key, result.len());
+                    result.push(relation);
+                }
+            }
+        }
+        result
+    }
+
+    /// Keeps one relation per pair of entities for symmetric relations (the most probable of both directions),
+    /// entities being identified by their offsets like in `dedup`
+    fn dedup_symmetric(&self, relations: Vec<Relation>) -> Vec<Relation> {
+        type Key = (String, (usize, usize), (usize, usize));
+        let mut result: Vec<Relation> = Vec::with_capacity(relations.len());
+        let mut pairs: HashMap<Key, usize> = HashMap::new();
+        for relation in relations {
+            let symmetric = self
+                .schema
+                .relations()
+                .get(relation.class())
+                .is_some_and(|spec| spec.is_symmetric());
+            if !symmetric {
+                result.push(relation);
+                continue;
+            }
+            let subject = (relation.subject.start, relation.subject.end);
+            let object = (relation.object.start, relation.object.end);
+            let key = (
+                relation.class.clone(),
+                subject.min(object),
+                subject.max(object),
+            );
+            match pairs.get(&key) {
+                Some(index) => {
+                    if relation.probability > result[*index].probability {
+                        result[*index] = relation;
+                    }
+                }
+                None => {
+                    pairs.insert(key, result.len());
+                    result.push(relation);
+                }
+            }
+        }
+        result
     }
 }
 
@@ -193,46 +401,52 @@ impl Composable<(SpanOutput, RelationContext), RelationOutput> for SpanOutputToR
         let (input, context) = input;
         let mut result = Vec::new();
 
+        let mut rejected = Vec::new();
+
         for seq in input.spans {
             let mut relations = Vec::new();
+            let mut seq_rejected = Vec::new();
             for span in seq {
-                let relation = Relation::from(span, &context)?;
-                match self.is_valid(&relation, &context) {
-                    Ok(true) => relations.push(relation),
-                    Ok(false) => {
-                        if std::env::var("GLINER_DEBUG").is_ok() {
-                            eprintln!(
-                                "[relation rejected] '{}'\n  Subject: '{}' [{}] ({}..{})\n  Object:  '{}' [{}] ({}..{})\n  Score:   {:.4}\n  Reason:  schema mismatch",
-                                relation.class(),
-                                relation.subject.text,
-                                relation.subject.label,
-                                relation.subject.start,
-                                relation.subject.end,
-                                relation.object.text,
-                                relation.object.label,
-                                relation.object.start,
-                                relation.object.end,
-                                relation.probability
-                            );
+                let mut relation = Relation::from(span, &context)?;
+                let reason = if self
+                    .threshold
+                    .is_some_and(|threshold| relation.probability() < threshold)
+                {
+                    "below threshold".to_string()
+                } else if self.is_disallowed_self_relation(&relation) {
+                    "self relation".to_string()
+                } else {
+                    match self.is_valid(&relation, &context) {
+                        Ok(true) => {
+                            relations.push(relation);
+                            continue;
                         }
-                    }
-                    Err(err) => {
-                        if std::env::var("GLINER_DEBUG").is_ok() {
-                            eprintln!(
-                                "relation parsing failed: {}\n  text: '{}'",
-                                err, relation.object.text
-                            );
+                        Ok(false) if self.all_pairs => {
+                            relation.schema_allowed = false;
+                            relations.push(relation);
+                            continue;
                         }
+                        Ok(false) => "schema mismatch".to_string(),
+                        Err(err) => format!("invalid relation: {err}"),
                     }
+                };
+                Self::log_rejected(&relation, &reason);
+                if self.keep_rejected {
+                    seq_rejected.push((relation, reason));
                 }
             }
-            result.push(relations);
+            if self.dedup {
+                relations = Self::dedup(relations);
+            }
+            result.push(self.dedup_symmetric(relations));
+            rejected.push(seq_rejected);
         }
 
         Ok(RelationOutput {
             texts: input.texts,
             entities: input.entities,
             relations: result,
+            rejected,
         })
     }
 }
@@ -261,3 +475,368 @@ impl std::fmt::Display for RelationFormatError {
         f.write_str(&self.message)
     }
 }
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::input::relation::schema::RelationSpec;
+
+    /// Context of already known entities, given as (text, label, offsets)
+    fn context(entities: &[(&str, &str, (usize, usize))]) -> RelationContext {
+        RelationContext {
+            entity_labels: entities
+                .iter()
+                .map(|(text, label, _)| (text.to_string(), [label.to_string()].into()))
+                .collect(),
+            entity_offsets: entities
+                .iter()
+                .map(|(text, _, offsets)| (text.to_string(), *offsets))
+                .collect(),
+            entity_probabilities: HashMap::new(),
+            entity_spans: Vec::new(),
+            text_offset: 0,
+        }
+    }
+
+    #[test]
+    fn test_symmetric_relation() -> Result<()> {
+        #![allow(clippy::unwrap_used)]
+        let mut schema = RelationSchema::new();
+        schema.push_bidirectional("spouse of", &["PERSON"], &["PERSON"]);
+        schema.push_with_allowed_labels("founded", &["PERSON"], &["COMPANY"]);
+
+        let mut context = context(&[("Alice", "PERSON", (0, 5)), ("Bob", "PERSON", (19, 22))]);
+        context.entity_probabilities =
+            [("Alice".to_string(), 0.95), ("Bob".to_string(), 0.85)].into();
+
+        let text = "Alice is married to Bob";
+        let span = |subject: &str, object: &str, start: usize, probability: f32| {
+            Span::new(
+                0,
+                start,
+                start + object.len(),
+                object.to_string(),
+                format!("{subject} <> spouse of"),
+                probability,
+            )
+        };
+        let spans = SpanOutput::new(
+            vec![text.to_string()],
+            vec![
+                "Alice <> spouse of".to_string(),
+                "Bob <> spouse of".to_string(),
+            ],
+            vec![vec![
+                span("Alice", "Bob", 19, 0.7),
+                span("Bob", "Alice", 0, 0.9),
+            ]],
+        );
+
+        let output = SpanOutputToRelationOutput::new(&schema).apply((spans, context))?;
+        let relations = output.relations.first().unwrap();
+        assert_eq!(relations.len(), 1);
+        let relation = relations.first().unwrap();
+        assert_eq!(relation.class(), "spouse of");
+        assert_eq!(relation.subject().text, "Bob");
+        assert_eq!(relation.object().text, "Alice");
+        assert_eq!(relation.probability(), 0.9);
+        assert_eq!(relation.subject().probability, 0.85);
+        assert_eq!(relation.object().probability, 0.95);
+        Ok(())
+    }
+
+    #[test]
+    fn test_relation_threshold() -> Result<()> {
+        #![allow(clippy::unwrap_used)]
+        let mut schema = RelationSchema::new();
+        schema.push_with_allowed_labels("founded", &["PERSON"], &["COMPANY"]);
+        let context = || context(&[("Microsoft", "COMPANY", (19, 28))]);
+        let spans = || {
+            SpanOutput::new(
+                vec!["Bill Gates founded Microsoft".to_string()],
+                vec!["Bill Gates <> founded".to_string()],
+                vec![vec![Span::new(
+                    0,
+                    19,
+                    28,
+                    "Microsoft".to_string(),
+                    "Bill Gates <> founded".to_string(),
+                    0.6,
+                )]],
+            )
+        };
+
+        let output = SpanOutputToRelationOutput::new(&schema)
+            .with_threshold(Some(0.5))
+            .apply((spans(), context()))?;
+        assert_eq!(output.relations.first().unwrap().len(), 1);
+
+        let output = SpanOutputToRelationOutput::new(&schema)
+            .with_threshold(Some(0.7))
+            .apply((spans(), context()))?;
+        assert!(output.relations.first().unwrap().is_empty());
+        assert!(output.rejected.first().unwrap().is_empty());
+
+        let output = SpanOutputToRelationOutput::new(&schema)
+            .with_threshold(Some(0.7))
+            .with_rejected(true)
+            .apply((spans(), context()))?;
+        let (relation, reason) = output.rejected.first().unwrap().first().unwrap();
+        assert_eq!(relation.object().text, "Microsoft");
+        assert_eq!(reason, "below threshold");
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_round_trip() -> Result<()> {
+        let entity = |text: &str, label: &str, start| {
+            RelationEntity::new(
+                text.to_string(),
+                label.to_string(),
+                start,
+                start + text.len(),
+                0.8,
+            )
+        };
+        let relation = Relation {
+            class: "founded".to_string(),
+            subject: entity("Bill Gates", "person", 0),
+            object: entity("Microsoft", "company", 19),
+            sequence: 0,
+            start: 19,
+            end: 28,
+            probability: 0.8,
+            schema_allowed: true,
+        };
+        let output = RelationOutput {
+            texts: vec!["Bill Gates founded Microsoft".to_string()],
+            entities: vec!["person".to_string(), "company".to_string()],
+            relations: vec![vec![relation]],
+            rejected: vec![vec![]],
+        };
+        let json = serde_json::to_string(&output)?;
+        assert!(
+            json.contains(r#""subject":{"text":"Bill Gates","label":"person","start":0,"end":10"#)
+        );
+        let decoded: RelationOutput = serde_json::from_str(&json)?;
+        assert_eq!(decoded.texts, output.texts);
+        assert_eq!(decoded.entities, output.entities);
+        assert_eq!(decoded.relations, output.relations);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dedup() -> Result<()> {
+        #![allow(clippy::unwrap_used)]
+        let mut schema = RelationSchema::new();
+        schema.push_with_allowed_labels("founded", &["PERSON"], &["COMPANY"]);
+        let context = || context(&[("Microsoft", "COMPANY", (19, 28))]);
+        let span = |start: usize, probability: f32| {
+            Span::new(
+                0,
+                start,
+                start + 9,
+                "Microsoft".to_string(),
+                "Bill Gates <> founded".to_string(),
+                probability,
+            )
+        };
+        let spans = || {
+            SpanOutput::new(
+                vec!["Bill Gates founded Microsoft (Microsoft)".to_string()],
+                vec!["Bill Gates <> founded".to_string()],
+                vec![vec![span(19, 0.6), span(30, 0.8)]],
+            )
+        };
+
+        let output = SpanOutputToRelationOutput::new(&schema).apply((spans(), context()))?;
+        let relations = output.relations.first().unwrap();
+        assert_eq!(relations.len(), 1);
+        assert_eq!(relations.first().unwrap().probability(), 0.8);
+
+        let output = SpanOutputToRelationOutput::new(&schema)
+            .with_dedup(false)
+            .apply((spans(), context()))?;
+        assert_eq!(output.relations.first().unwrap().len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_self_relation() -> Result<()> {
+        #![allow(clippy::unwrap_used)]
+        let context = || context(&[("Alice", "PERSON", (0, 5))]);
+        let spans = || {
+            SpanOutput::new(
+                vec!["Alice knows Alice".to_string()],
+                vec!["Alice <> knows".to_string()],
+                vec![vec![Span::new(
+                    0,
+                    0,
+                    5,
+                    "Alice".to_string(),
+                    "Alice <> knows".to_string(),
+                    0.9,
+                )]],
+            )
+        };
+
+        let mut schema = RelationSchema::new();
+        schema.push_with_allowed_labels("knows", &["PERSON"], &["PERSON"]);
+        let output = SpanOutputToRelationOutput::new(&schema)
+            .with_rejected(true)
+            .apply((spans(), context()))?;
+        assert!(output.relations.first().unwrap().is_empty());
+        let (_, reason) = output.rejected.first().unwrap().first().unwrap();
+        assert_eq!(reason, "self relation");
+
+        let mut schema = RelationSchema::new();
+        schema.push_with_spec(
+            "knows",
+            RelationSpec::new(&["PERSON"], &["PERSON"]).with_reflexive(true),
+        );
+        let output = SpanOutputToRelationOutput::new(&schema).apply((spans(), context()))?;
+        assert_eq!(output.relations.first().unwrap().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_all_pairs() -> Result<()> {
+        #![allow(clippy::unwrap_used)]
+        let mut schema = RelationSchema::new();
+        schema.push_with_allowed_labels("founded", &["PERSON"], &["COMPANY"]);
+        let context = || {
+            context(&[
+                ("Microsoft", "COMPANY", (0, 9)),
+                ("Seattle", "LOCATION", (25, 32)),
+            ])
+        };
+        let spans = || {
+            SpanOutput::new(
+                vec!["Microsoft was founded in Seattle".to_string()],
+                vec!["Microsoft <> founded".to_string()],
+                vec![vec![Span::new(
+                    0,
+                    25,
+                    32,
+                    "Seattle".to_string(),
+                    "Microsoft <> founded".to_string(),
+                    0.6,
+                )]],
+            )
+        };
+        let output = SpanOutputToRelationOutput::new(&schema).apply((spans(), context()))?;
+        assert!(output.relations[0].is_empty());
+        let output = SpanOutputToRelationOutput::new(&schema)
+            .with_all_pairs(true)
+            .apply((spans(), context()))?;
+        let relation = output.relations[0].first().unwrap();
+        assert_eq!(relation.object().text, "Seattle");
+        assert!(!relation.is_schema_allowed());
+        Ok(())
+    }
+
+    #[test]
+    fn test_repeated_entities() -> Result<()> {
+        #![allow(clippy::unwrap_used)]
+        use crate::model::input::relation::{RelationInput, RelationInputToTextInput};
+        let mut schema = RelationSchema::new();
+        schema.push_with_allowed_labels("met", &["person"], &["person"]);
+        let text = "Alice met Bob, then Alice met Bob again";
+        let entities = SpanOutput::from_entities(
+            vec![text.to_string()],
+            vec![vec![
+                (0, 5, "person".to_string()),
+                (10, 13, "person".to_string()),
+                (20, 25, "person".to_string()),
+                (30, 33, "person".to_string()),
+            ]],
+        )?;
+        let (_, context) = RelationInputToTextInput::default()
+            .apply(RelationInput::from_spans(entities, &schema))?;
+        let span = |start: usize| {
+            let start = start + context.text_offset;
+            Span::new(
+                0,
+                start,
+                start + 3,
+                "Bob".to_string(),
+                "Alice <> met".to_string(),
+                0.8,
+            )
+        };
+        let spans = SpanOutput::new(
+            vec![text.to_string()],
+            vec!["Alice <> met".to_string()],
+            vec![vec![span(10), span(30)]],
+        );
+        let output = SpanOutputToRelationOutput::new(&schema).apply((spans, context))?;
+        let offsets: Vec<_> = output.relations[0]
+            .iter()
+            .map(|r| {
+                (
+                    (r.subject().start, r.subject().end),
+                    (r.object().start, r.object().end),
+                )
+            })
+            .collect();
+        assert_eq!(offsets, vec![((0, 5), (10, 13)), ((20, 25), (30, 33))]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_repeated_symmetric_relation() -> Result<()> {
+        use crate::model::input::relation::{RelationInput, RelationInputToTextInput};
+        let mut schema = RelationSchema::new();
+        schema.push_bidirectional("met", &["person"], &["person"]);
+        let text = "Alice met Bob. Later, Alice met Bob.";
+        let entities = SpanOutput::from_entities(
+            vec![text.to_string()],
+            vec![vec![
+                (0, 5, "person".to_string()),
+                (10, 13, "person".to_string()),
+                (22, 27, "person".to_string()),
+                (32, 35, "person".to_string()),
+            ]],
+        )?;
+        let (_, context) = RelationInputToTextInput::default()
+            .apply(RelationInput::from_spans(entities, &schema))?;
+        let span = |subject: &str, object: &str, start: usize, probability: f32| {
+            let start = start + context.text_offset;
+            Span::new(
+                0,
+                start,
+                start + object.len(),
+                object.to_string(),
+                format!("{subject} <> met"),
+                probability,
+            )
+        };
+        let spans = SpanOutput::new(
+            vec![text.to_string()],
+            vec!["Alice <> met".to_string(), "Bob <> met".to_string()],
+            vec![vec![
+                span("Alice", "Bob", 10, 0.7),
+                span("Alice", "Bob", 32, 0.8),
+                span("Bob", "Alice", 0, 0.9),
+            ]],
+        );
+        let output = SpanOutputToRelationOutput::new(&schema).apply((spans, context))?;
+        let relations: Vec<_> = output.relations[0]
+            .iter()
+            .map(|r| {
+                (
+                    (r.subject().start, r.subject().end),
+                    (r.object().start, r.object().end),
+                    r.probability(),
+                )
+            })
+            .collect();
+        // both mentions of the pair are kept, each one once
+        assert_eq!(
+            relations,
+            vec![((10, 13), (0, 5), 0.9), ((22, 27), (32, 35), 0.8)]
+        );
+        Ok(())
+    }
+}
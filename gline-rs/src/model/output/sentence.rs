@@ -0,0 +1,76 @@
+//! Rule-based sentence segmentation
+
+/// Returns the byte offsets of the sentences of the given text.
+///
+/// A sentence ends with a line break, or with `.`, `!` or `?` followed by a whitespace (or the end of
+/// the text). This is a simple heuristic: abbreviations such as "Dr." end a sentence as well. Whitespaces
+/// surrounding the sentences are not part of them.
+pub fn sentences(text: &str) -> Vec<(usize, usize)> {
+    let mut result = Vec::new();
+    let mut start = None;
+    let mut push = |start: usize, end: usize| {
+        let end = start + text[start..end].trim_end().len();
+        if end > start {
+            result.push((start, end));
+        }
+    };
+    let mut chars = text.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        let Some(sentence_start) = start else {
+            if !c.is_whitespace() {
+                start = Some(index);
+            }
+            continue;
+        };
+        let end = match c {
+            '\n' => index,
+            '.' | '!' | '?' => match chars.peek() {
+                Some((_, next)) if !next.is_whitespace() => continue,
+                _ => index + c.len_utf8(),
+            },
+            _ => continue,
+        };
+        push(sentence_start, end);
+        start = None;
+    }
+    if let Some(sentence_start) = start {
+        push(sentence_start, text.len());
+    }
+    result
+}
+
+/// Index of the sentence containing the given byte offset, given the sentences of the text (see `sentences`),
+/// or `None` if the offset is beyond the last sentence
+pub fn sentence_index(sentences: &[(usize, usize)], offset: usize) -> Option<usize> {
+    let index = sentences.partition_point(|(_, end)| *end <= offset);
+    (index < sentences.len()).then_some(index)
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sentences() {
+        let text = "Bill Gates founded Microsoft. It is based in Redmond (v1.0)!\nSteve Jobs founded Apple ";
+        let offsets = sentences(text);
+        let sentences: Vec<&str> = offsets
+            .iter()
+            .map(|(start, end)| &text[*start..*end])
+            .collect();
+        assert_eq!(
+            sentences,
+            vec![
+                "Bill Gates founded Microsoft.",
+                "It is based in Redmond (v1.0)!",
+                "Steve Jobs founded Apple"
+            ]
+        );
+        assert_eq!(sentence_index(&offsets, 0), Some(0));
+        assert_eq!(sentence_index(&offsets, 29), Some(1));
+        assert_eq!(sentence_index(&offsets, 45), Some(1));
+        assert_eq!(sentence_index(&offsets, 80), Some(2));
+        assert_eq!(sentence_index(&offsets, text.len()), None);
+    }
+}
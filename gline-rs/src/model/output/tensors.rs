@@ -3,7 +3,10 @@
 use crate::model::pipeline::context::EntityContext;
 use crate::util::result::Result;
 use composable::Composable;
+use ndarray::{ArrayViewD, Axis, CowArray, IxDyn};
 use ort::session::SessionOutputs;
+use ort::tensor::TensorElementType;
+use ort::value::{DynValue, ValueType};
 
 /// Represents the raw tensor output of the inference step
 pub struct TensorOutput<'a> {
@@ -17,6 +20,55 @@ impl<'a> TensorOutput<'a> {
     }
 }
 
+/// Logits of a model output, borrowed whatever their floating point type.
+///
+/// Models are usually exported with `f32` logits. Half-precision outputs (`f16` or `bf16`, as produced by some
+/// quantized or mixed-precision exports) are converted to `f32` one sequence at a time (see `sequence`), so that
+/// the logits of the whole batch are never copied. Note that int8 quantization typically only applies to the
+/// weights of the encoder, the logits remaining floating point values.
+pub enum Logits<'a> {
+    F32(ArrayViewD<'a, f32>),
+    F16(ArrayViewD<'a, half::f16>),
+    BF16(ArrayViewD<'a, half::bf16>),
+}
+
+impl<'a> Logits<'a> {
+    pub fn extract(value: &'a DynValue) -> Result<Self> {
+        match value.dtype() {
+            ValueType::Tensor {
+                ty: TensorElementType::Float32,
+                ..
+            } => Ok(Self::F32(value.try_extract_tensor::<f32>()?)),
+            ValueType::Tensor {
+                ty: TensorElementType::Float16,
+                ..
+            } => Ok(Self::F16(value.try_extract_tensor::<half::f16>()?)),
+            ValueType::Tensor {
+                ty: TensorElementType::Bfloat16,
+                ..
+            } => Ok(Self::BF16(value.try_extract_tensor::<half::bf16>()?)),
+            other => Err(format!(
+                "unexpected logits type: expected floating point values, got {other}"
+            )
+            .into()),
+        }
+    }
+
+    /// Logits of a single sequence, i.e. the sub-array at `index` along the batch `axis`, as `f32` values
+    /// (borrowed for `f32` logits, converted otherwise)
+    pub fn sequence(&self, axis: usize, index: usize) -> CowArray<'_, f32, IxDyn> {
+        match self {
+            Self::F32(array) => CowArray::from(array.index_axis(Axis(axis), index)),
+            Self::F16(array) => {
+                CowArray::from(array.index_axis(Axis(axis), index).mapv(half::f16::to_f32))
+            }
+            Self::BF16(array) => {
+                CowArray::from(array.index_axis(Axis(axis), index).mapv(half::bf16::to_f32))
+            }
+        }
+    }
+}
+
 /// Composable: (SessionOutput, TensorMeta) => TensorOutput
 #[derive(Default)]
 pub struct SessionOutputToTensors {}
@@ -28,3 +80,28 @@ impl<'a> Composable<(SessionOutputs<'a, 'a>, EntityContext), TensorOutput<'a>>
         Ok(TensorOutput::from(input.0, input.1))
     }
 }
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sequence() -> Result<()> {
+        let values: Vec<f32> = (0..24).map(|i| i as f32 / 4.0).collect();
+        let logits = ndarray::Array3::from_shape_vec((4, 3, 2), values)?.into_dyn();
+        // f32 logits are borrowed
+        let f32_logits = Logits::F32(logits.view());
+        let sequence = f32_logits.sequence(0, 1);
+        assert!(sequence.is_view());
+        assert_eq!(sequence.shape(), &[3, 2]);
+        // half-precision logits are converted for the requested sequence only
+        let half_logits = logits.mapv(half::f16::from_f32);
+        let half_logits = Logits::F16(half_logits.view());
+        let sequence = half_logits.sequence(0, 1);
+        assert!(!sequence.is_view());
+        assert_eq!(sequence.len(), 6);
+        assert_eq!(sequence, logits.index_axis(Axis(0), 1));
+        Ok(())
+    }
+}
@@ -1,5 +1,19 @@
 //! Processing parameters
 
+use crate::model::config::ModelConfig;
+use crate::model::info::ModelInfo;
+use crate::model::input::casing::Casing;
+use crate::model::input::normalizer::Normalization;
+use crate::model::input::prompt::PromptConfig;
+use crate::model::input::tokenized::TruncationPolicy;
+use crate::model::output::decoded::greedy::GreedySearch;
+use crate::model::output::decoded::nms::{NonMaxSuppression, OverlapResolution};
+use crate::model::output::decoded::window::WindowMerge;
+use crate::util::result::Result;
+use crate::util::timing::Timings;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
 /// Represents the set of parameters for the whole pipeline
 ///
 /// * pre-processing
@@ -7,19 +21,103 @@
 ///
 /// The easiest way to instanciate sound parameters is to use the
 /// `default()` constructor and then use individual setters as needed.
+#[derive(Debug, Clone)]
 pub struct Parameters {
     /// Probability threshold (default: 0.5)
     pub threshold: f32,
+    /// Setting this parameter to `true` means that entities are decoded whatever their probability (`threshold` being
+    /// ignored by the decoders), with their scores left intact, for the caller to filter them (e.g. to sweep thresholds
+    /// from a single inference). Combine with `flat_ner=false`, `dup_label=true` and `multi_label=true` to get every
+    /// candidate. It is ignored by relation extraction (default: false)
+    pub keep_sub_threshold: bool,
+    /// Temperature `T` used to calibrate probabilities, computed as `sigmoid(logit / T)` (default: 1.0)
+    pub temperature: f32,
+    /// Whether the model is quantized, `None` meaning that it is detected when the model is loaded (see `ModelInfo::quantized`)
+    /// (default: None)
+    pub quantized: Option<bool>,
+    /// Temperature used instead of `temperature` when the model is quantized, for its logits typically come out on
+    /// another scale (default: None, i.e. `temperature` whatever the model)
+    pub quantized_temperature: Option<f32>,
+    /// Probability threshold for relations, which are also subject to `threshold` (default: None)
+    pub relation_threshold: Option<f32>,
+    /// Setting this parameter to `true` means that rejected relations are reported in the output, with the reason of their rejection (default: false)
+    pub keep_rejected_relations: bool,
+    /// Setting this parameter to `true` means that identical relations (same class, subject and object offsets) found in a sequence are reported once, keeping the most probable (default: true)
+    pub dedup_relations: bool,
+    /// Setting this parameter to `true` means that relations are extracted for all pairs of entity labels, the ones which are not
+    /// allowed by the schema being kept and tagged as such (see `Relation::is_schema_allowed`), e.g. for schema discovery (default: false)
+    pub all_relation_pairs: bool,
+    /// If `batch_size` is set, setting this parameter to `true` means that texts of similar lengths are grouped into the same sub-batches to reduce padding, the output keeping the original order (default: false)
+    pub bucket_by_length: bool,
+    /// If set, each span carries the distribution of the classes whose probability reaches this (secondary) threshold, and not only its own class (default: None)
+    pub class_scores_threshold: Option<f32>,
+    /// Setting this parameter to `true` means that the text of each entity is sliced again from the input text at its offsets
+    /// after post-processing (an error being raised if they do not fall on character boundaries), guaranteeing that
+    /// `text == input[start..end]` (default: false)
+    pub strict_offsets: bool,
+    /// Setting this parameter to `true` means that the encoding of the entity labels is cached by the pipeline and
+    /// reused across calls using the same labels, for the few label sets used last (see `LabelPromptCache`) (default: true)
+    pub label_prompt_cache: bool,
+    /// Special tokens used to build the prompts, typically set from the model configuration (default: the ones of GLiNER)
+    pub prompt: PromptConfig,
+    /// If set, the duration of each stage of the NER pipelines is recorded into these timings, for every call
+    /// using these parameters (default: None)
+    pub timings: Option<Arc<Timings>>,
+    /// Setting this parameter to `true` means that entity labels are trimmed and lowercased in the prompts (so that
+    /// "Person" and "person" give the same results), the spans still referring to the labels as given (default: false)
+    pub normalize_labels: bool,
+    /// If set, the entity labels having a description are replaced with it in the prompts (e.g. "stock ticker of a company"
+    /// for "ORG_TICKER"), since descriptive labels work better, the spans still referring to the labels as given (default: None)
+    pub label_descriptions: Option<HashMap<String, String>>,
+    /// A warning is logged when more entity labels than this are requested, since each of them widens the output
+    /// tensor (default: 100)
+    pub labels_warning: Option<usize>,
+    /// If set, requesting more entity labels than this is an error, which avoids running out of memory (default: None)
+    pub max_labels: Option<usize>,
+    /// Unicode normalization applied to the texts before splitting them into words, entity offsets still referring to the original texts (default: None)
+    pub normalization: Option<Normalization>,
+    /// Casing normalization of the words given to the model (e.g. for headlines written in capital letters), entities still
+    /// referring to the original texts (default: None)
+    pub casing: Option<Casing>,
     /// Setting this parameter to `true` means that no entity can overlap with another one (default: true)
     pub flat_ner: bool,
     /// If `flat_ner=false`, setting this parameter to `true` means that overlapping spans can belong to the *same* class (default: false)
     pub dup_label: bool,
     /// If `flat_ner=false`, setting this parameter to `true` means that overlapping spans can belong to *different* classes (default: false)
     pub multi_label: bool,
+    /// If `flat_ner=false`, setting this parameter means that up to `top_k` overlapping spans are kept for any region
+    /// of the text, whatever their classes (`multi_label` being then ignored). Useful for nested entities (default: None)
+    pub top_k: Option<usize>,
+    /// If set, overlapping spans are resolved by non-maximum suppression with this IoU threshold instead of greedy-search,
+    /// `flat_ner`, `dup_label`, `multi_label` and `top_k` being then ignored (default: None)
+    pub nms_iou_threshold: Option<f32>,
+    /// Spans having less characters than this (leading and trailing whitespace excluded) are dropped when
+    /// decoding, which removes short false positives (default: 0, i.e. disabled)
+    pub min_span_chars: usize,
     /// For span mode, maximum span width (default: 12)
     pub max_width: usize,
     /// Maximum sequence length (default: 512)
     pub max_length: Option<usize>,
+    /// What to do with sequences longer than `max_length` when `stride` is not set: silent truncation, truncation
+    /// with a warning, or error (default: silent)
+    pub truncation: TruncationPolicy,
+    /// If set, sequences longer than `max_length` are split into overlapping windows sharing `stride` words,
+    /// instead of being truncated (default: None)
+    pub stride: Option<usize>,
+    /// How an entity found in two overlapping windows (see `stride`) is merged: keeping the most probable span, the
+    /// longest one, or their union (default: only merging spans with the same offsets, see `WindowMerge`)
+    pub window_merge: WindowMerge,
+    /// Setting this parameter to `true` means that adjacent entities of the same class are merged into one (default: false)
+    pub merge_adjacent: bool,
+    /// Setting this parameter to `true` guarantees that no two entities overlap, whatever `flat_ner` and `multi_label`, any
+    /// residual conflict being resolved by score after the other post-processing steps (default: false)
+    pub force_flat: bool,
+    /// If set, input texts are processed by sub-batches of at most `batch_size` texts, which bounds memory usage
+    /// for large inputs (default: None, meaning all texts in one batch)
+    pub batch_size: Option<usize>,
+    /// If set, only the entities of these classes are kept (others being dropped before greedy-search),
+    /// which is useful to use a narrower set of classes than the one of the prompt (default: None)
+    pub allowed_labels: Option<HashSet<String>>,
 }
 
 impl Default for Parameters {
@@ -30,6 +128,11 @@ impl Default for Parameters {
 }
 
 impl Parameters {
+    /// Returns a builder starting from the default configuration, checking parameters consistency when built
+    pub fn builder() -> ParametersBuilder {
+        ParametersBuilder::default()
+    }
+
     /// New configuration specifying every parameter
     pub fn new(
         threshold: f32,
@@ -46,14 +149,277 @@ impl Parameters {
             flat_ner,
             dup_label,
             multi_label,
+            stride: None,
+            window_merge: WindowMerge::ExactOffsets,
+            truncation: TruncationPolicy::Silent,
+            merge_adjacent: false,
+            force_flat: false,
+            relation_threshold: None,
+            keep_rejected_relations: false,
+            dedup_relations: true,
+            all_relation_pairs: false,
+            bucket_by_length: false,
+            class_scores_threshold: None,
+            strict_offsets: false,
+            label_prompt_cache: true,
+            prompt: PromptConfig::default(),
+            timings: None,
+            normalize_labels: false,
+            label_descriptions: None,
+            labels_warning: Some(100),
+            max_labels: None,
+            normalization: None,
+            casing: None,
+            batch_size: None,
+            allowed_labels: None,
+            temperature: 1.0,
+            quantized: None,
+            quantized_temperature: None,
+            keep_sub_threshold: false,
+            top_k: None,
+            nms_iou_threshold: None,
+            min_span_chars: 0,
         }
     }
 
+    /// Parameters honoring the defaults recommended by a model configuration (contents of `gliner_config.json`),
+    /// see `ModelConfig::recommended_parameters`
+    pub fn from_config_json(json: &str) -> Result<Self> {
+        ModelConfig::from_bytes(json.as_bytes())?.recommended_parameters()
+    }
+
     pub fn with_threshold(mut self, threshold: f32) -> Self {
         self.threshold = threshold;
         self
     }
 
+    pub fn with_keep_sub_threshold(mut self, keep_sub_threshold: bool) -> Self {
+        self.keep_sub_threshold = keep_sub_threshold;
+        self
+    }
+
+    /// Threshold to be used by the decoders, i.e. `threshold` unless `keep_sub_threshold` is set
+    pub fn decoding_threshold(&self) -> f32 {
+        if self.keep_sub_threshold {
+            0.0
+        } else {
+            self.threshold
+        }
+    }
+
+    /// Returns an error if the parameters are inconsistent (e.g. a threshold out of [0, 1] or a blank label
+    /// description), see `ParametersBuilder::build`
+    pub fn validate(&self) -> Result<()> {
+        if !(0.0..=1.0).contains(&self.threshold) {
+            return Err(format!(
+                "invalid parameters: threshold must be within [0, 1], got {}",
+                self.threshold
+            )
+            .into());
+        }
+        if let Some(relation_threshold) = self.relation_threshold {
+            if !(0.0..=1.0).contains(&relation_threshold) {
+                return Err(format!(
+                    "invalid parameters: relation_threshold must be within [0, 1], got {relation_threshold}"
+                )
+                .into());
+            }
+        }
+        if let Some(class_scores_threshold) = self.class_scores_threshold {
+            if !(0.0..=1.0).contains(&class_scores_threshold) {
+                return Err(format!(
+                    "invalid parameters: class_scores_threshold must be within [0, 1], got {class_scores_threshold}"
+                )
+                .into());
+            }
+        }
+        if let Some(nms_iou_threshold) = self.nms_iou_threshold {
+            if !(0.0..=1.0).contains(&nms_iou_threshold) {
+                return Err(format!(
+                    "invalid parameters: nms_iou_threshold must be within [0, 1], got {nms_iou_threshold}"
+                )
+                .into());
+            }
+        }
+        if !(self.temperature > 0.0 && self.temperature.is_finite()) {
+            return Err(format!(
+                "invalid parameters: temperature must be a positive number, got {}",
+                self.temperature
+            )
+            .into());
+        }
+        if let Some(quantized_temperature) = self.quantized_temperature {
+            if !(quantized_temperature > 0.0 && quantized_temperature.is_finite()) {
+                return Err(format!(
+                    "invalid parameters: quantized_temperature must be a positive number, got {quantized_temperature}"
+                )
+                .into());
+            }
+        }
+        if self.max_width == 0 {
+            return Err("invalid parameters: max_width must be greater than 0".into());
+        }
+        if self.top_k == Some(0) {
+            return Err("invalid parameters: top_k must be greater than 0".into());
+        }
+        if self.max_labels == Some(0) {
+            return Err("invalid parameters: max_labels must be greater than 0".into());
+        }
+        if self.batch_size == Some(0) {
+            return Err("invalid parameters: batch_size must be greater than 0".into());
+        }
+        if let Some(label_descriptions) = &self.label_descriptions {
+            if let Some((label, _)) = label_descriptions
+                .iter()
+                .find(|(_, description)| description.trim().is_empty())
+            {
+                return Err(format!(
+                    "invalid parameters: the description of label '{label}' must not be blank"
+                )
+                .into());
+            }
+        }
+        if let Some(max_length) = self.max_length {
+            if self.max_width > max_length {
+                return Err(format!(
+                    "invalid parameters: max_width ({}) must not exceed max_length ({})",
+                    self.max_width, max_length
+                )
+                .into());
+            }
+            if let Some(stride) = self.stride {
+                if stride >= max_length {
+                    return Err(format!(
+                        "invalid parameters: stride ({stride}) must be lower than max_length ({max_length})"
+                    )
+                    .into());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Step resolving overlapping spans, i.e. non-maximum suppression if `nms_iou_threshold` is set, greedy-search otherwise
+    pub fn overlap_resolution(&self) -> OverlapResolution {
+        match self.nms_iou_threshold {
+            Some(iou_threshold) => OverlapResolution::Nms(NonMaxSuppression::new(iou_threshold)),
+            None => OverlapResolution::Greedy(
+                GreedySearch::new(self.flat_ner, self.dup_label, self.multi_label)
+                    .with_top_k(self.top_k),
+            ),
+        }
+    }
+
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = temperature;
+        self
+    }
+
+    pub fn with_quantized(mut self, quantized: Option<bool>) -> Self {
+        self.quantized = quantized;
+        self
+    }
+
+    pub fn with_quantized_temperature(mut self, quantized_temperature: Option<f32>) -> Self {
+        self.quantized_temperature = quantized_temperature;
+        self
+    }
+
+    /// Sets `quantized` from what was detected about the model, unless it was set explicitly
+    pub fn for_model(mut self, info: &ModelInfo) -> Self {
+        self.quantized.get_or_insert(info.quantized);
+        self
+    }
+
+    /// Temperature to be used by the decoders, i.e. `quantized_temperature` if set and the model is quantized,
+    /// `temperature` otherwise
+    pub fn decoding_temperature(&self) -> f32 {
+        match (self.quantized, self.quantized_temperature) {
+            (Some(true), Some(quantized_temperature)) => quantized_temperature,
+            _ => self.temperature,
+        }
+    }
+
+    pub fn with_relation_threshold(mut self, relation_threshold: Option<f32>) -> Self {
+        self.relation_threshold = relation_threshold;
+        self
+    }
+
+    pub fn with_keep_rejected_relations(mut self, keep_rejected_relations: bool) -> Self {
+        self.keep_rejected_relations = keep_rejected_relations;
+        self
+    }
+
+    pub fn with_dedup_relations(mut self, dedup_relations: bool) -> Self {
+        self.dedup_relations = dedup_relations;
+        self
+    }
+
+    pub fn with_all_relation_pairs(mut self, all_relation_pairs: bool) -> Self {
+        self.all_relation_pairs = all_relation_pairs;
+        self
+    }
+
+    pub fn with_bucket_by_length(mut self, bucket_by_length: bool) -> Self {
+        self.bucket_by_length = bucket_by_length;
+        self
+    }
+
+    pub fn with_class_scores_threshold(mut self, class_scores_threshold: Option<f32>) -> Self {
+        self.class_scores_threshold = class_scores_threshold;
+        self
+    }
+
+    pub fn with_strict_offsets(mut self, strict_offsets: bool) -> Self {
+        self.strict_offsets = strict_offsets;
+        self
+    }
+
+    pub fn with_label_prompt_cache(mut self, label_prompt_cache: bool) -> Self {
+        self.label_prompt_cache = label_prompt_cache;
+        self
+    }
+
+    pub fn with_prompt(mut self, prompt: PromptConfig) -> Self {
+        self.prompt = prompt;
+        self
+    }
+
+    pub fn with_timings(mut self, timings: Option<Arc<Timings>>) -> Self {
+        self.timings = timings;
+        self
+    }
+
+    pub fn with_normalize_labels(mut self, normalize_labels: bool) -> Self {
+        self.normalize_labels = normalize_labels;
+        self
+    }
+
+    pub fn with_labels_warning(mut self, labels_warning: Option<usize>) -> Self {
+        self.labels_warning = labels_warning;
+        self
+    }
+
+    pub fn with_max_labels(mut self, max_labels: Option<usize>) -> Self {
+        self.max_labels = max_labels;
+        self
+    }
+
+    pub fn with_truncation(mut self, truncation: TruncationPolicy) -> Self {
+        self.truncation = truncation;
+        self
+    }
+
+    pub fn with_normalization(mut self, normalization: Option<Normalization>) -> Self {
+        self.normalization = normalization;
+        self
+    }
+
+    pub fn with_casing(mut self, casing: Option<Casing>) -> Self {
+        self.casing = casing;
+        self
+    }
+
     pub fn with_max_width(mut self, max_width: usize) -> Self {
         self.max_width = max_width;
         self
@@ -64,6 +430,59 @@ impl Parameters {
         self
     }
 
+    pub fn with_stride(mut self, stride: Option<usize>) -> Self {
+        self.stride = stride;
+        self
+    }
+
+    pub fn with_window_merge(mut self, window_merge: WindowMerge) -> Self {
+        self.window_merge = window_merge;
+        self
+    }
+
+    pub fn with_merge_adjacent(mut self, merge_adjacent: bool) -> Self {
+        self.merge_adjacent = merge_adjacent;
+        self
+    }
+
+    pub fn with_force_flat(mut self, force_flat: bool) -> Self {
+        self.force_flat = force_flat;
+        self
+    }
+
+    pub fn with_batch_size(mut self, batch_size: Option<usize>) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    pub fn with_label_descriptions(
+        mut self,
+        label_descriptions: Option<HashMap<String, String>>,
+    ) -> Self {
+        self.label_descriptions = label_descriptions;
+        self
+    }
+
+    pub fn with_allowed_labels(mut self, allowed_labels: Option<HashSet<String>>) -> Self {
+        self.allowed_labels = allowed_labels;
+        self
+    }
+
+    pub fn with_top_k(mut self, top_k: Option<usize>) -> Self {
+        self.top_k = top_k;
+        self
+    }
+
+    pub fn with_min_span_chars(mut self, min_span_chars: usize) -> Self {
+        self.min_span_chars = min_span_chars;
+        self
+    }
+
+    pub fn with_nms_iou_threshold(mut self, nms_iou_threshold: Option<f32>) -> Self {
+        self.nms_iou_threshold = nms_iou_threshold;
+        self
+    }
+
     pub fn with_flat_ner(mut self, flat_ner: bool) -> Self {
         self.flat_ner = flat_ner;
         self
@@ -79,3 +498,247 @@ impl Parameters {
         self
     }
 }
+
+/// Builder for `Parameters` (see `Parameters::builder()`)
+#[derive(Debug, Clone, Default)]
+pub struct ParametersBuilder {
+    params: Parameters,
+}
+
+impl ParametersBuilder {
+    pub fn threshold(mut self, threshold: f32) -> Self {
+        self.params.threshold = threshold;
+        self
+    }
+
+    pub fn keep_sub_threshold(mut self, keep_sub_threshold: bool) -> Self {
+        self.params.keep_sub_threshold = keep_sub_threshold;
+        self
+    }
+
+    pub fn temperature(mut self, temperature: f32) -> Self {
+        self.params.temperature = temperature;
+        self
+    }
+
+    pub fn quantized(mut self, quantized: Option<bool>) -> Self {
+        self.params.quantized = quantized;
+        self
+    }
+
+    pub fn quantized_temperature(mut self, quantized_temperature: Option<f32>) -> Self {
+        self.params.quantized_temperature = quantized_temperature;
+        self
+    }
+
+    pub fn relation_threshold(mut self, relation_threshold: Option<f32>) -> Self {
+        self.params.relation_threshold = relation_threshold;
+        self
+    }
+
+    pub fn keep_rejected_relations(mut self, keep_rejected_relations: bool) -> Self {
+        self.params.keep_rejected_relations = keep_rejected_relations;
+        self
+    }
+
+    pub fn dedup_relations(mut self, dedup_relations: bool) -> Self {
+        self.params.dedup_relations = dedup_relations;
+        self
+    }
+
+    pub fn all_relation_pairs(mut self, all_relation_pairs: bool) -> Self {
+        self.params.all_relation_pairs = all_relation_pairs;
+        self
+    }
+
+    pub fn bucket_by_length(mut self, bucket_by_length: bool) -> Self {
+        self.params.bucket_by_length = bucket_by_length;
+        self
+    }
+
+    pub fn class_scores_threshold(mut self, class_scores_threshold: Option<f32>) -> Self {
+        self.params.class_scores_threshold = class_scores_threshold;
+        self
+    }
+
+    pub fn strict_offsets(mut self, strict_offsets: bool) -> Self {
+        self.params.strict_offsets = strict_offsets;
+        self
+    }
+
+    pub fn label_prompt_cache(mut self, label_prompt_cache: bool) -> Self {
+        self.params.label_prompt_cache = label_prompt_cache;
+        self
+    }
+
+    pub fn prompt(mut self, prompt: PromptConfig) -> Self {
+        self.params.prompt = prompt;
+        self
+    }
+
+    pub fn timings(mut self, timings: Option<Arc<Timings>>) -> Self {
+        self.params.timings = timings;
+        self
+    }
+
+    pub fn normalize_labels(mut self, normalize_labels: bool) -> Self {
+        self.params.normalize_labels = normalize_labels;
+        self
+    }
+
+    pub fn labels_warning(mut self, labels_warning: Option<usize>) -> Self {
+        self.params.labels_warning = labels_warning;
+        self
+    }
+
+    pub fn max_labels(mut self, max_labels: Option<usize>) -> Self {
+        self.params.max_labels = max_labels;
+        self
+    }
+
+    pub fn truncation(mut self, truncation: TruncationPolicy) -> Self {
+        self.params.truncation = truncation;
+        self
+    }
+
+    pub fn normalization(mut self, normalization: Option<Normalization>) -> Self {
+        self.params.normalization = normalization;
+        self
+    }
+
+    pub fn casing(mut self, casing: Option<Casing>) -> Self {
+        self.params.casing = casing;
+        self
+    }
+
+    pub fn max_width(mut self, max_width: usize) -> Self {
+        self.params.max_width = max_width;
+        self
+    }
+
+    pub fn max_length(mut self, max_length: Option<usize>) -> Self {
+        self.params.max_length = max_length;
+        self
+    }
+
+    pub fn stride(mut self, stride: Option<usize>) -> Self {
+        self.params.stride = stride;
+        self
+    }
+
+    pub fn window_merge(mut self, window_merge: WindowMerge) -> Self {
+        self.params.window_merge = window_merge;
+        self
+    }
+
+    pub fn batch_size(mut self, batch_size: Option<usize>) -> Self {
+        self.params.batch_size = batch_size;
+        self
+    }
+
+    pub fn label_descriptions(
+        mut self,
+        label_descriptions: Option<HashMap<String, String>>,
+    ) -> Self {
+        self.params.label_descriptions = label_descriptions;
+        self
+    }
+
+    pub fn allowed_labels(mut self, allowed_labels: Option<HashSet<String>>) -> Self {
+        self.params.allowed_labels = allowed_labels;
+        self
+    }
+
+    pub fn top_k(mut self, top_k: Option<usize>) -> Self {
+        self.params.top_k = top_k;
+        self
+    }
+
+    pub fn flat_ner(mut self, flat_ner: bool) -> Self {
+        self.params.flat_ner = flat_ner;
+        self
+    }
+
+    pub fn dup_label(mut self, dup_label: bool) -> Self {
+        self.params.dup_label = dup_label;
+        self
+    }
+
+    pub fn multi_label(mut self, multi_label: bool) -> Self {
+        self.params.multi_label = multi_label;
+        self
+    }
+
+    pub fn min_span_chars(mut self, min_span_chars: usize) -> Self {
+        self.params.min_span_chars = min_span_chars;
+        self
+    }
+
+    pub fn nms_iou_threshold(mut self, nms_iou_threshold: Option<f32>) -> Self {
+        self.params.nms_iou_threshold = nms_iou_threshold;
+        self
+    }
+
+    pub fn merge_adjacent(mut self, merge_adjacent: bool) -> Self {
+        self.params.merge_adjacent = merge_adjacent;
+        self
+    }
+
+    pub fn force_flat(mut self, force_flat: bool) -> Self {
+        self.params.force_flat = force_flat;
+        self
+    }
+
+    /// Returns the parameters, or an error if they are inconsistent (see `Parameters::validate`)
+    pub fn build(self) -> Result<Parameters> {
+        self.params.validate()?;
+        Ok(self.params)
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder() -> Result<()> {
+        let params = Parameters::builder()
+            .threshold(0.3)
+            .max_length(Some(256))
+            .flat_ner(false)
+            .multi_label(true)
+            .build()?;
+        assert_eq!(params.threshold, 0.3);
+        assert_eq!(params.max_length, Some(256));
+        assert_eq!(params.max_width, 12);
+        assert!(!params.flat_ner);
+        assert!(params.multi_label);
+        assert!(!params.dup_label);
+        Ok(())
+    }
+
+    #[test]
+    fn test_builder_invariants() {
+        assert!(Parameters::builder().threshold(1.5).build().is_err());
+        assert!(Parameters::builder()
+            .relation_threshold(Some(-0.1))
+            .build()
+            .is_err());
+        assert!(Parameters::builder().max_width(0).build().is_err());
+        assert!(Parameters::builder()
+            .max_length(Some(8))
+            .max_width(12)
+            .build()
+            .is_err());
+        assert!(Parameters::builder()
+            .max_length(Some(8))
+            .max_width(4)
+            .stride(Some(8))
+            .build()
+            .is_err());
+        assert!(Parameters::builder().batch_size(Some(0)).build().is_err());
+        assert!(Parameters::builder().temperature(0.0).build().is_err());
+        assert!(Parameters::builder().max_length(None).build().is_ok());
+    }
+}
@@ -1,5 +1,7 @@
 //! Data to be transmitted, beside the tensors themselves, from pre-processing to post-processing.
 
+use crate::model::output::decoded::window::WindowMerge;
+use crate::model::output::decoded::SpanOutput;
 use crate::text::span::Span;
 use crate::text::token::Token;
 use crate::util::{error::IndexError, result::Result};
@@ -8,8 +10,11 @@ use std::collections::{HashMap, HashSet};
 // Context for NER pipelines
 pub struct EntityContext {
     pub texts: Vec<String>,
+    /// Token sequences (one per window if sequences have been split, see `TokenizedInput::from_windows`)
     pub tokens: Vec<Vec<Token>>,
     pub entities: Vec<String>,
+    /// Original sequence (index in `texts`) of each token sequence
+    pub sequence_ids: Vec<usize>,
     pub num_words: usize,
 }
 
@@ -27,6 +32,10 @@ impl EntityContext {
             .tokens
             .get(sequence_id)
             .ok_or(IndexError::new("meta.tokens", sequence_id))?;
+        let sequence_id = *self
+            .sequence_ids
+            .get(sequence_id)
+            .ok_or(IndexError::new("meta.sequence_ids", sequence_id))?;
         let start_token = sequence
             .get(start_token)
             .ok_or(IndexError::new("meta.tokens[]", start_token))?;
@@ -54,10 +63,129 @@ impl EntityContext {
             probability,
         ))
     }
+
+    /// Builds the final output given the spans found in each token sequence.
+    ///
+    /// Spans found in the windows of a same sequence are gathered (their offsets are already
+    /// relative to the whole sequence). When the same span is found in two overlapping windows,
+    /// only the most probable one is kept (see `into_output_with` for other policies).
+    pub fn into_output(self, spans: Vec<Vec<Span>>) -> SpanOutput {
+        self.into_output_with(spans, WindowMerge::default())
+    }
+
+    /// Same as `into_output`, merging the spans found in overlapping windows according to the given policy
+    pub fn into_output_with(self, spans: Vec<Vec<Span>>, window_merge: WindowMerge) -> SpanOutput {
+        let mut token_counts = vec![0; self.texts.len()];
+        let mut word_offsets: Vec<Vec<(usize, usize)>> = vec![Vec::new(); self.texts.len()];
+        for (tokens, sequence_id) in self.tokens.iter().zip(&self.sequence_ids) {
+            if let Some(count) = token_counts.get_mut(*sequence_id) {
+                *count += tokens.len();
+            }
+            if let Some(words) = word_offsets.get_mut(*sequence_id) {
+                // words shared with the previous window are only kept once
+                let previous_end = words.last().map_or(0, |(_, end)| *end);
+                words.extend(
+                    tokens
+                        .iter()
+                        .map(|token| (token.start(), token.end()))
+                        .filter(|(start, _)| *start >= previous_end),
+                );
+            }
+        }
+
+        if self.sequence_ids.len() == self.texts.len() {
+            return SpanOutput::new(self.texts, self.entities, spans)
+                .with_token_counts(token_counts)
+                .with_word_offsets(word_offsets);
+        }
+
+        let mut result: Vec<Vec<Span>> = std::iter::repeat_with(Vec::new)
+            .take(self.texts.len())
+            .collect();
+        for (window, window_spans) in spans.into_iter().enumerate() {
+            if let Some(sequence_id) = self.sequence_ids.get(window) {
+                window_merge.merge(&mut result[*sequence_id], window_spans);
+            }
+        }
+
+        for sequence in &mut result {
+            sequence.sort_unstable_by(|a, b| {
+                a.offsets()
+                    .cmp(&b.offsets())
+                    .then_with(|| a.class().cmp(b.class()))
+            });
+        }
+
+        SpanOutput::new(self.texts, self.entities, result)
+            .with_token_counts(token_counts)
+            .with_word_offsets(word_offsets)
+    }
 }
 
 // Context for RE pipeline
 pub struct RelationContext {
     pub entity_labels: HashMap<String, HashSet<String>>,
     pub entity_offsets: HashMap<String, (usize, usize)>,
+    /// Probability of each entity, as detected by NER (for the same occurrence as `entity_offsets`)
+    pub entity_probabilities: HashMap<String, f32>,
+    /// Entities of each sequence, used to find the actual occurrences of the subject and object of a relation
+    /// (the maps above, keyed by surface text, being the fallback)
+    pub entity_spans: Vec<Vec<Span>>,
+    /// Offset of the text within the prompts, to be deducted from the offsets of the relation spans
+    pub text_offset: usize,
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_windows() -> Result<()> {
+        #![allow(clippy::unwrap_used)]
+        let text = "Bill met Steve in Paris";
+        let context = EntityContext {
+            texts: vec![text.to_string()],
+            tokens: vec![
+                vec![
+                    Token::new(0, 4, "Bill"),
+                    Token::new(5, 8, "met"),
+                    Token::new(9, 14, "Steve"),
+                ],
+                vec![
+                    Token::new(9, 14, "Steve"),
+                    Token::new(15, 17, "in"),
+                    Token::new(18, 23, "Paris"),
+                ],
+            ],
+            entities: vec!["person".to_string(), "city".to_string()],
+            sequence_ids: vec![0, 0],
+            num_words: 3,
+        };
+        let spans = vec![
+            vec![
+                context.create_span(0, 0, 0, 0, 0.9)?,
+                context.create_span(0, 2, 2, 0, 0.6)?,
+            ],
+            vec![
+                context.create_span(1, 0, 0, 0, 0.8)?,
+                context.create_span(1, 2, 2, 1, 0.7)?,
+            ],
+        ];
+        let output = context.into_output(spans);
+        assert_eq!(output.spans.len(), 1);
+        assert_eq!(output.token_counts, vec![6]);
+        assert_eq!(
+            output.word_offsets,
+            vec![vec![(0, 4), (5, 8), (9, 14), (15, 17), (18, 23)]]
+        );
+        let spans = output.spans.first().unwrap();
+        assert_eq!(spans.len(), 3);
+        let steve = spans.get(1).unwrap();
+        assert_eq!(steve.text(), "Steve");
+        assert_eq!(steve.offsets(), (9, 14));
+        assert_eq!(steve.probability(), 0.8);
+        assert_eq!(spans.get(2).unwrap().text(), "Paris");
+        Ok(())
+    }
 }
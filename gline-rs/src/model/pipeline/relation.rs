@@ -2,25 +2,38 @@
 
 use super::super::params::Parameters;
 use super::context::{EntityContext, RelationContext};
-use super::token::TokenPipeline;
+use super::token::{TokenMode, TokenPipeline};
 use crate::model::input::relation::schema::RelationSchema;
 use crate::model::input::relation::{RelationInputToTextInput, SpanOutputToRelationInput};
+use crate::model::input::text::TextInput;
 use crate::model::output::decoded::SpanOutput;
 use crate::model::output::relation::{RelationOutput, SpanOutputToRelationOutput};
-use crate::text::{splitter::Splitter, tokenizer::Tokenizer};
 use composable::*;
 use orp::pipeline::*;
+use std::borrow::Cow;
 use std::path::Path;
 
 /// Relation Extraction pipeline
 ///
-/// Re-uses the token-level pipeline (see `TokenPipline`)
-pub struct RelationPipeline<'a, S, T> {
-    token_pipeline: TokenPipeline<S, T>,
+/// Re-uses an entity-level pipeline (span or token mode, see `SpanPipeline` and `TokenPipeline`),
+/// which must match the mode of the underlying model. This pipeline can either own the entity
+/// pipeline, or borrow the one of an already loaded model (see `RelationPipeline::borrowed`).
+pub struct RelationPipeline<'a, P: Clone> {
+    entity_pipeline: Cow<'a, P>,
     relation_schema: &'a RelationSchema,
 }
 
-impl<'a, S: Splitter, T: Tokenizer> Pipeline<'a> for RelationPipeline<'a, S, T> {
+impl<'a, P> Pipeline<'a> for RelationPipeline<'a, P>
+where
+    P: Clone
+        + Pipeline<
+            'a,
+            Input = TextInput,
+            Output = SpanOutput,
+            Context = EntityContext,
+            Parameters = Parameters,
+        >,
+{
     type Input = SpanOutput;
     type Output = RelationOutput;
     type Context = (RelationContext, EntityContext);
@@ -32,10 +45,11 @@ impl<'a, S: Splitter, T: Tokenizer> Pipeline<'a> for RelationPipeline<'a, S, T>
     ) -> impl PreProcessor<'a, Self::Input, Self::Context> {
         composed_t![
             composed![
-                SpanOutputToRelationInput::new(self.relation_schema),
+                SpanOutputToRelationInput::new(self.relation_schema)
+                    .with_all_pairs(params.all_relation_pairs),
                 RelationInputToTextInput::default()
             ],
-            self.token_pipeline.pre_processor(params)
+            self.entity_pipeline.pre_processor(params)
         ]
     }
 
@@ -44,25 +58,43 @@ impl<'a, S: Splitter, T: Tokenizer> Pipeline<'a> for RelationPipeline<'a, S, T>
         params: &Parameters,
     ) -> impl PostProcessor<'a, Self::Output, Self::Context> {
         composed_rt![
-            self.token_pipeline.post_processor(params),
+            self.entity_pipeline.post_processor(params),
             SpanOutputToRelationOutput::new(self.relation_schema)
+                .with_threshold(params.relation_threshold)
+                .with_rejected(params.keep_rejected_relations)
+                .with_dedup(params.dedup_relations)
+                .with_all_pairs(params.all_relation_pairs)
         ]
     }
+
+    fn expected_inputs(&self) -> Option<&std::collections::HashSet<&str>> {
+        self.entity_pipeline.expected_inputs()
+    }
+
+    fn expected_outputs(&self) -> Option<&std::collections::HashSet<&str>> {
+        self.entity_pipeline.expected_outputs()
+    }
 }
 
-impl<'a, S, T> RelationPipeline<'a, S, T> {
-    pub fn new(token_pipeline: TokenPipeline<S, T>, relation_schema: &'a RelationSchema) -> Self {
+impl<'a, P: Clone> RelationPipeline<'a, P> {
+    pub fn new(entity_pipeline: P, relation_schema: &'a RelationSchema) -> Self {
         Self {
-            token_pipeline,
+            entity_pipeline: Cow::Owned(entity_pipeline),
+            relation_schema,
+        }
+    }
+
+    /// Same as `new`, but borrows the entity pipeline (typically the one of an already loaded model)
+    pub fn borrowed(entity_pipeline: &'a P, relation_schema: &'a RelationSchema) -> Self {
+        Self {
+            entity_pipeline: Cow::Borrowed(entity_pipeline),
             relation_schema,
         }
     }
 }
 
-/// Builds a default relation extraction pipeline
-impl<'a>
-    RelationPipeline<'a, crate::text::splitter::RegexSplitter, crate::text::tokenizer::HFTokenizer>
-{
+/// Builds a default relation extraction pipeline (token mode)
+impl<'a> RelationPipeline<'a, TokenMode> {
     pub fn default<P: AsRef<Path>>(
         tokenizer_path: P,
         relation_schema: &'a RelationSchema,
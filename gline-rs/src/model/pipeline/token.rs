@@ -1,19 +1,26 @@
 //! Pre-defined pipeline for NER (token mode)
 
 use super::super::super::text::{splitter::Splitter, tokenizer::Tokenizer};
+use super::super::session::SessionParameters;
 use super::super::{input, output, params};
 use super::context::EntityContext;
 use crate::util::result::Result;
+use crate::util::timing::{Stage, Timed};
 use composable::*;
-use orp::{params::RuntimeParameters, pipeline::*};
+use orp::pipeline::*;
 use std::collections::HashSet;
 use std::path::Path;
+use std::sync::Arc;
 
 /// Generic token-level pipeline
+#[derive(Clone)]
 pub struct TokenPipeline<S, T> {
     splitter: S,
     tokenizer: T,
-    expected_inputs: HashSet<&'static str>,
+    label_prompt_cache: Arc<input::encoded::LabelPromptCache>,
+    input_names: input::tensors::InputNames,
+    /// Expected inputs, if the input tensors have their usual names (see `with_input_names`)
+    expected_inputs: Option<HashSet<&'static str>>,
     expected_outputs: HashSet<&'static str>,
 }
 
@@ -27,12 +34,45 @@ impl<'a, S: Splitter, T: Tokenizer> Pipeline<'a> for TokenPipeline<S, T> {
         &self,
         params: &Self::Parameters,
     ) -> impl PreProcessor<'a, Self::Input, Self::Context> {
+        let timings = params.timings.clone();
         composed![
-            input::tokenized::RawToTokenized::new(&self.splitter, params.max_length),
-            input::prompt::TokenizedToPrompt::default(),
-            input::encoded::PromptsToEncoded::new(&self.tokenizer),
-            input::tensors::token::EncodedToTensors::default(),
-            input::tensors::token::TensorsToSessionInput::default()
+            Timed::new(
+                Stage::Splitting,
+                composed![
+                    input::tokenized::RawToTokenized::new(&self.splitter, params.max_length)
+                        .with_stride(params.stride)
+                        .with_normalization(params.normalization)
+                        .with_truncation(params.truncation),
+                    input::casing::TokenizedToCased::new(params.casing)
+                ],
+                timings.clone()
+            ),
+            Timed::new(
+                Stage::Prompting,
+                input::prompt::TokenizedToPrompt::new(params.prompt.clone())
+                    .with_label_normalization(params.normalize_labels)
+                    .with_label_descriptions(params.label_descriptions.clone())
+                    .with_label_limits(params.labels_warning, params.max_labels),
+                timings.clone()
+            ),
+            Timed::new(
+                Stage::Encoding,
+                input::encoded::PromptsToEncoded::new(&self.tokenizer).with_cache(
+                    params
+                        .label_prompt_cache
+                        .then_some(self.label_prompt_cache.as_ref())
+                ),
+                timings.clone()
+            ),
+            Timed::new(
+                Stage::Tensors,
+                composed![
+                    input::tensors::token::EncodedToTensors::default()
+                        .with_input_names(self.input_names.clone()),
+                    input::tensors::token::TensorsToSessionInput::default()
+                ],
+                timings
+            )
         ]
     }
 
@@ -40,20 +80,41 @@ impl<'a, S: Splitter, T: Tokenizer> Pipeline<'a> for TokenPipeline<S, T> {
         &self,
         params: &Self::Parameters,
     ) -> impl PostProcessor<'a, Self::Output, Self::Context> {
+        let timings = params.timings.clone();
         composed![
-            output::tensors::SessionOutputToTensors::default(),
-            output::decoded::token::TensorsToDecoded::new(params.threshold),
-            output::decoded::sort::SpanSort::default(),
-            output::decoded::greedy::GreedySearch::new(
-                params.flat_ner,
-                params.dup_label,
-                params.multi_label
+            Timed::new(
+                Stage::Decoding,
+                composed![
+                    output::tensors::SessionOutputToTensors::default(),
+                    output::decoded::token::TensorsToDecoded::new(params.decoding_threshold())
+                        .with_temperature(params.decoding_temperature())
+                        .with_class_scores(params.class_scores_threshold)
+                        .with_min_span_chars(params.min_span_chars)
+                        .with_window_merge(params.window_merge)
+                ],
+                timings.clone()
+            ),
+            Timed::new(
+                Stage::PostProcessing,
+                composed![
+                    params
+                        .allowed_labels
+                        .clone()
+                        .map(output::decoded::filter::LabelFilter::new)
+                        .unwrap_or_default(),
+                    output::decoded::sort::SpanSort::default(),
+                    params.overlap_resolution(),
+                    output::decoded::merge::MergeAdjacent::new(params.merge_adjacent),
+                    output::decoded::flat::ForceFlat::new(params.force_flat),
+                    output::decoded::slice::SliceText::new(params.strict_offsets)
+                ],
+                timings
             )
         ]
     }
 
     fn expected_inputs(&self) -> Option<&std::collections::HashSet<&str>> {
-        Some(&self.expected_inputs)
+        self.expected_inputs.as_ref()
     }
 
     fn expected_outputs(&self) -> Option<&std::collections::HashSet<&str>> {
@@ -61,19 +122,83 @@ impl<'a, S: Splitter, T: Tokenizer> Pipeline<'a> for TokenPipeline<S, T> {
     }
 }
 
-/// Specific implementation using HF tokenizer and default splitter
-impl TokenPipeline<crate::text::splitter::RegexSplitter, crate::text::tokenizer::HFTokenizer> {
-    pub fn new<P: AsRef<Path>>(tokenizer_path: P) -> Result<Self> {
-        Ok(Self {
-            splitter: crate::text::splitter::RegexSplitter::default(),
-            tokenizer: crate::text::tokenizer::HFTokenizer::from_file(tokenizer_path)?,
-            expected_inputs: input::tensors::token::TokenTensors::inputs()
-                .into_iter()
-                .collect(),
+impl<S, T> TokenPipeline<S, T> {
+    /// Sets the names of the input tensors, for models exported with non-standard ones (see `ModelConfig::input_names`).
+    ///
+    /// Since `Pipeline::expected_inputs` cannot borrow names owned by the pipeline, the inputs of the model are then
+    /// not checked by the pipeline itself (`GLiNER::from_dir` checks them against the configuration when loading).
+    pub fn with_input_names(mut self, names: input::tensors::InputNames) -> Self {
+        self.expected_inputs = names.is_default().then(Self::default_expected_inputs);
+        self.input_names = names;
+        self
+    }
+
+    fn default_expected_inputs() -> HashSet<&'static str> {
+        input::tensors::token::TokenTensors::default_inputs()
+            .into_iter()
+            .collect()
+    }
+
+    pub fn tokenizer(&self) -> &T {
+        &self.tokenizer
+    }
+
+    pub fn splitter(&self) -> &S {
+        &self.splitter
+    }
+}
+
+impl<S: Splitter, T: Tokenizer> TokenPipeline<S, T> {
+    /// Builds the pipeline given the splitter and any tokenizer, for example one which is not a Hugging Face
+    /// tokenizer (a `Box<dyn Tokenizer + Send + Sync>` can also be given, see `BoxedTokenizer`)
+    pub fn with_tokenizer(splitter: S, tokenizer: T) -> Self {
+        Self {
+            splitter,
+            tokenizer,
+            label_prompt_cache: Default::default(),
+            input_names: Default::default(),
+            expected_inputs: Some(Self::default_expected_inputs()),
             expected_outputs: output::decoded::token::TensorsToDecoded::outputs()
                 .into_iter()
                 .collect(),
-        })
+        }
+    }
+}
+
+/// Specific implementation using HF tokenizer and default splitter
+impl TokenPipeline<crate::text::splitter::RegexSplitter, crate::text::tokenizer::HFTokenizer> {
+    pub fn new<P: AsRef<Path>>(tokenizer_path: P) -> Result<Self> {
+        Self::new_with_splitter(
+            tokenizer_path,
+            crate::text::splitter::RegexSplitter::default(),
+        )
+    }
+
+    pub fn new_from_bytes(tokenizer_bytes: &[u8]) -> Result<Self> {
+        Self::new_from_bytes_with_splitter(
+            tokenizer_bytes,
+            crate::text::splitter::RegexSplitter::default(),
+        )
+    }
+}
+
+/// Implementation using HF tokenizer and a custom splitter
+///
+/// The splitter defines the word boundaries: entity offsets are computed from the
+/// words it produces, so an entity can never start or end within a word.
+impl<S: Splitter> TokenPipeline<S, crate::text::tokenizer::HFTokenizer> {
+    pub fn new_with_splitter<P: AsRef<Path>>(tokenizer_path: P, splitter: S) -> Result<Self> {
+        Ok(Self::with_tokenizer(
+            splitter,
+            crate::text::tokenizer::HFTokenizer::from_path(tokenizer_path)?,
+        ))
+    }
+
+    pub fn new_from_bytes_with_splitter(tokenizer_bytes: &[u8], splitter: S) -> Result<Self> {
+        Ok(Self::with_tokenizer(
+            splitter,
+            crate::text::tokenizer::HFTokenizer::from_bytes(tokenizer_bytes)?,
+        ))
     }
 }
 
@@ -85,14 +210,96 @@ pub type TokenMode =
 impl super::super::GLiNER<TokenMode> {
     pub fn new<P: AsRef<Path>>(
         params: params::Parameters,
-        runtime_params: RuntimeParameters,
+        runtime_params: impl Into<SessionParameters>,
+        tokenizer_path: P,
+        model_path: P,
+    ) -> Result<Self> {
+        Self::load(
+            params,
+            runtime_params,
+            model_path,
+            TokenPipeline::new(tokenizer_path)?,
+            None,
+        )
+    }
+
+    pub fn new_from_bytes(
+        params: params::Parameters,
+        runtime_params: impl Into<SessionParameters>,
+        tokenizer_bytes: &[u8],
+        model_bytes: &[u8],
+    ) -> Result<Self> {
+        Self::load_from_bytes(
+            params,
+            runtime_params,
+            model_bytes,
+            TokenPipeline::new_from_bytes(tokenizer_bytes)?,
+            None,
+        )
+    }
+}
+
+/// GLiNER implementation using a token-mode pipeline with a custom splitter (see `TokenPipeline::new_with_splitter`)
+impl<S: Splitter> super::super::GLiNER<TokenPipeline<S, crate::text::tokenizer::HFTokenizer>> {
+    pub fn new_with_splitter<P: AsRef<Path>>(
+        params: params::Parameters,
+        runtime_params: impl Into<SessionParameters>,
         tokenizer_path: P,
         model_path: P,
+        splitter: S,
     ) -> Result<Self> {
-        Ok(Self {
+        Self::load(
             params,
-            model: super::super::Model::new(model_path, runtime_params)?,
-            pipeline: TokenPipeline::new(tokenizer_path)?,
-        })
+            runtime_params,
+            model_path,
+            TokenPipeline::new_with_splitter(tokenizer_path, splitter)?,
+            None,
+        )
+    }
+}
+
+/// GLiNER implementation using a token-mode pipeline with any tokenizer (see `TokenPipeline::with_tokenizer`)
+impl<S: Splitter, T: Tokenizer> super::super::GLiNER<TokenPipeline<S, T>> {
+    pub fn new_with_tokenizer<P: AsRef<Path>>(
+        params: params::Parameters,
+        runtime_params: impl Into<SessionParameters>,
+        tokenizer: T,
+        model_path: P,
+        splitter: S,
+    ) -> Result<Self> {
+        Self::load(
+            params,
+            runtime_params,
+            model_path,
+            TokenPipeline::with_tokenizer(splitter, tokenizer),
+            None,
+        )
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Minimal word-level tokenizer, standing for the `tokenizer.json` of a model
+    const TOKENIZER_JSON: &str = r#"{
+        "version": "1.0",
+        "truncation": null,
+        "padding": null,
+        "added_tokens": [],
+        "normalizer": null,
+        "pre_tokenizer": {"type": "Whitespace"},
+        "post_processor": null,
+        "decoder": null,
+        "model": {"type": "WordLevel", "vocab": {"[UNK]": 0, "james": 1, "bond": 2}, "unk_token": "[UNK]"}
+    }"#;
+
+    #[test]
+    fn test_new_from_bytes() -> Result<()> {
+        let pipeline = TokenPipeline::new_from_bytes(TOKENIZER_JSON.as_bytes())?;
+        assert_eq!(pipeline.tokenizer.encode("james bond 007")?, vec![1, 2, 0]);
+        assert!(TokenPipeline::new_from_bytes(b"not a tokenizer").is_err());
+        Ok(())
     }
 }
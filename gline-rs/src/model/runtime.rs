@@ -1,7 +1,10 @@
-use orp::model::Model;
-
-use crate::model::{input, output, pipeline, GLiNER};
+use crate::model::info::ModelInfo;
+use crate::model::input::relation::schema::RelationSchema;
+use crate::model::session::Model;
+use crate::model::{input, output, params::Parameters, pipeline, GLiNER};
+use crate::text::span::Span;
 use crate::util::result::Result;
+use crate::util::timing::StageTimings;
 
 /// Runtime-selected GLiNER model (span or token mode).
 ///
@@ -19,13 +22,195 @@ impl InferenceMode {
         }
     }
 
-    pub fn inference<'a>(
-        &'a self,
+    /// Description of the actual inputs and outputs of the ONNX model (see `GLiNER::model_info`)
+    pub fn model_info(&self) -> &ModelInfo {
+        match self {
+            Self::Span(model) => model.model_info(),
+            Self::Token(model) => model.model_info(),
+        }
+    }
+
+    /// Names of the actual inputs of the ONNX model (see `GLiNER::input_names`)
+    pub fn input_names(&self) -> Vec<&str> {
+        match self {
+            Self::Span(model) => model.input_names(),
+            Self::Token(model) => model.input_names(),
+        }
+    }
+
+    /// Names of the actual outputs of the ONNX model (see `GLiNER::output_names`)
+    pub fn output_names(&self) -> Vec<&str> {
+        match self {
+            Self::Span(model) => model.output_names(),
+            Self::Token(model) => model.output_names(),
+        }
+    }
+
+    pub fn get_parameters(&self) -> &Parameters {
+        match self {
+            Self::Span(model) => model.get_parameters(),
+            Self::Token(model) => model.get_parameters(),
+        }
+    }
+
+    /// Performs NER on the given texts, looking for entities of the given classes (see `GLiNER::predict`)
+    pub fn predict(&self, texts: &[&str], labels: &[&str]) -> Result<output::decoded::SpanOutput> {
+        match self {
+            Self::Span(model) => model.predict(texts, labels),
+            Self::Token(model) => model.predict(texts, labels),
+        }
+    }
+
+    /// Performs NER, also returning the time spent in each phase of the pipeline (see `GLiNER::inference_timed`)
+    pub fn inference_timed(
+        &self,
         input: input::text::TextInput,
+    ) -> Result<(output::decoded::SpanOutput, StageTimings)> {
+        match self {
+            Self::Span(model) => model.inference_timed(input),
+            Self::Token(model) => model.inference_timed(input),
+        }
+    }
+
+    /// Performs NER on a single text, returning its spans (see `GLiNER::predict_entity`)
+    pub fn predict_entity(&self, text: &str, labels: &[&str]) -> Result<Vec<Span>> {
+        match self {
+            Self::Span(model) => model.predict_entity(text, labels),
+            Self::Token(model) => model.predict_entity(text, labels),
+        }
+    }
+
+    /// Performs NER on a document read incrementally, using the splitter of the model (see `GLiNER::inference_stream`)
+    pub fn inference_stream<R: std::io::BufRead>(
+        &self,
+        reader: R,
+        labels: &[&str],
+    ) -> Result<Vec<Span>> {
+        match self {
+            Self::Span(model) => model.inference_stream(
+                reader,
+                model.get_pipeline().splitter(),
+                labels,
+                model.get_parameters(),
+            ),
+            Self::Token(model) => model.inference_stream(
+                reader,
+                model.get_pipeline().splitter(),
+                labels,
+                model.get_parameters(),
+            ),
+        }
+    }
+
+    pub fn inference(&self, input: input::text::TextInput) -> Result<output::decoded::SpanOutput> {
+        match self {
+            Self::Span(model) => model.inference_in_batches(input, model.get_parameters()),
+            Self::Token(model) => model.inference_in_batches(input, model.get_parameters()),
+        }
+    }
+
+    pub fn inference_with_params(
+        &self,
+        input: input::text::TextInput,
+        params: &Parameters,
+    ) -> Result<output::decoded::SpanOutput> {
+        match self {
+            Self::Span(model) => model.inference_in_batches(input, params),
+            Self::Token(model) => model.inference_in_batches(input, params),
+        }
+    }
+
+    /// Same as `inference_with_params`, notifying the progress after each sub-batch (see
+    /// `GLiNER::inference_in_batches_with_progress`)
+    pub fn inference_with_progress(
+        &self,
+        input: input::text::TextInput,
+        params: &Parameters,
+        progress: &mut (dyn FnMut(usize, usize) -> Result<()> + Send),
+    ) -> Result<output::decoded::SpanOutput> {
+        match self {
+            Self::Span(model) => model.inference_in_batches_with_progress(input, params, progress),
+            Self::Token(model) => model.inference_in_batches_with_progress(input, params, progress),
+        }
+    }
+
+    /// Performs NER on texts having each their own set of entity classes (see `GLiNER::inference_with_entities`)
+    pub fn inference_with_entities(
+        &self,
+        texts: Vec<String>,
+        entities: Vec<Vec<String>>,
+        params: &Parameters,
+    ) -> Result<output::decoded::SpanOutput> {
+        match self {
+            Self::Span(model) => model.inference_with_entities(texts, entities, params),
+            Self::Token(model) => model.inference_with_entities(texts, entities, params),
+        }
+    }
+
+    /// Same as `inference_with_entities`, notifying the progress after each sub-batch (see
+    /// `GLiNER::inference_with_entities_with_progress`)
+    pub fn inference_with_entities_with_progress(
+        &self,
+        texts: Vec<String>,
+        entities: Vec<Vec<String>>,
+        params: &Parameters,
+        progress: &mut (dyn FnMut(usize, usize) -> Result<()> + Send),
     ) -> Result<output::decoded::SpanOutput> {
         match self {
-            Self::Span(model) => model.inference(input),
-            Self::Token(model) => model.inference(input),
+            Self::Span(model) => {
+                model.inference_with_entities_with_progress(texts, entities, params, progress)
+            }
+            Self::Token(model) => {
+                model.inference_with_entities_with_progress(texts, entities, params, progress)
+            }
+        }
+    }
+
+    /// Entity labels declared in the model configuration, if any
+    pub fn known_labels(&self) -> Option<&[String]> {
+        match self {
+            Self::Span(model) => model.known_labels(),
+            Self::Token(model) => model.known_labels(),
+        }
+    }
+
+    /// Returns the words of the given text (with their offsets in characters) as seen by the splitter of the
+    /// pipeline, see `input::tokenized::preview`
+    pub fn tokenize_preview(&self, text: &str) -> Result<Vec<(String, usize, usize)>> {
+        match self {
+            Self::Span(model) => input::tokenized::preview(model.get_pipeline().splitter(), text),
+            Self::Token(model) => input::tokenized::preview(model.get_pipeline().splitter(), text),
+        }
+    }
+
+    /// Performs NER and then relation extraction, both stages using the pipeline matching the mode
+    /// of the loaded model.
+    pub fn extract_relations(
+        &self,
+        input: input::text::TextInput,
+        relation_schema: &RelationSchema,
+        params: &Parameters,
+    ) -> Result<output::relation::RelationOutput> {
+        match self {
+            Self::Span(model) => model.extract_relations(input, relation_schema, params),
+            Self::Token(model) => model.extract_relations(input, relation_schema, params),
+        }
+    }
+
+    /// Performs relation extraction only, given already known entities (see `GLiNER::extract_relations_from_entities`)
+    pub fn extract_relations_from_entities(
+        &self,
+        entities: output::decoded::SpanOutput,
+        relation_schema: &RelationSchema,
+        params: &Parameters,
+    ) -> Result<output::relation::RelationOutput> {
+        match self {
+            Self::Span(model) => {
+                model.extract_relations_from_entities(entities, relation_schema, params)
+            }
+            Self::Token(model) => {
+                model.extract_relations_from_entities(entities, relation_schema, params)
+            }
         }
     }
 }
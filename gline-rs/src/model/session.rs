@@ -0,0 +1,354 @@
+//! ONNX Runtime session running the pipelines
+
+use std::path::Path;
+
+use composable::Composable;
+use orp::params::RuntimeParameters;
+use orp::pipeline::Pipeline;
+use ort::execution_providers::CPUExecutionProvider;
+use ort::session::builder::{GraphOptimizationLevel, SessionBuilder};
+use ort::session::{Session, SessionInputs, SessionOutputs};
+
+use super::info::ModelInfo;
+use crate::util::result::Result;
+
+/// Graph optimization level of the ONNX Runtime session (see `GraphOptimizationLevel`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OptimizationLevel {
+    /// No graph optimization
+    Disable,
+    /// Semantics-preserving rewrites only (e.g. constant folding, redundant node elimination)
+    Level1,
+    /// Level 1, plus complex node fusions
+    Level2,
+    /// All optimizations, including layout ones
+    #[default]
+    Level3,
+}
+
+impl From<OptimizationLevel> for GraphOptimizationLevel {
+    fn from(level: OptimizationLevel) -> Self {
+        match level {
+            OptimizationLevel::Disable => GraphOptimizationLevel::Disable,
+            OptimizationLevel::Level1 => GraphOptimizationLevel::Level1,
+            OptimizationLevel::Level2 => GraphOptimizationLevel::Level2,
+            OptimizationLevel::Level3 => GraphOptimizationLevel::Level3,
+        }
+    }
+}
+
+/// Runtime parameters (threads and execution providers), along with options of the ONNX Runtime session.
+///
+/// Any `RuntimeParameters` converts into session parameters with the default options, so that they can be
+/// given as is to the model constructors.
+#[derive(Default)]
+pub struct SessionParameters {
+    runtime: RuntimeParameters,
+    optimization_level: OptimizationLevel,
+    cpu_arena: Option<bool>,
+    memory_pattern: Option<bool>,
+    inter_threads: Option<usize>,
+    cpu_fallback: bool,
+}
+
+impl SessionParameters {
+    pub fn new(runtime: RuntimeParameters) -> Self {
+        Self {
+            runtime,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the graph optimization level (default: `Level3`)
+    pub fn with_optimization_level(mut self, optimization_level: OptimizationLevel) -> Self {
+        self.optimization_level = optimization_level;
+        self
+    }
+
+    /// Enables or disables the memory arena of the CPU (default: ONNX Runtime default, i.e. enabled).
+    /// Disabling it lowers the memory footprint, at the cost of more allocations.
+    pub fn with_cpu_arena(mut self, enable: bool) -> Self {
+        self.cpu_arena = Some(enable);
+        self
+    }
+
+    /// Enables or disables memory pattern optimization (default: ONNX Runtime default, i.e. enabled), which
+    /// pre-allocates memory according to the shapes of the previous inferences
+    pub fn with_memory_pattern(mut self, enable: bool) -> Self {
+        self.memory_pattern = Some(enable);
+        self
+    }
+
+    /// Runs independent nodes of the graph in parallel, on the given number of threads (default: sequential
+    /// execution, `0` letting ONNX Runtime decide). Unlike the threads of `RuntimeParameters`, which are used
+    /// within each node, these only help models with parallel branches, at the cost of more memory.
+    pub fn with_inter_threads(mut self, threads: usize) -> Self {
+        self.inter_threads = Some(threads);
+        self
+    }
+
+    /// Loads the model on the CPU, with a warning, if the session cannot be created with the execution providers
+    /// (default: the error is returned), whether they fail to register or to load the model. Since `ort` ignores
+    /// registration failures by default, those only apply to the providers built with `error_on_failure` (see
+    /// `Model::cpu_fallback`).
+    pub fn with_cpu_fallback(mut self, enable: bool) -> Self {
+        self.cpu_fallback = enable;
+        self
+    }
+
+    pub fn runtime(&self) -> &RuntimeParameters {
+        &self.runtime
+    }
+
+    pub fn optimization_level(&self) -> OptimizationLevel {
+        self.optimization_level
+    }
+
+    pub fn cpu_arena(&self) -> Option<bool> {
+        self.cpu_arena
+    }
+
+    pub fn memory_pattern(&self) -> Option<bool> {
+        self.memory_pattern
+    }
+
+    pub fn inter_threads(&self) -> Option<usize> {
+        self.inter_threads
+    }
+
+    pub fn cpu_fallback(&self) -> bool {
+        self.cpu_fallback
+    }
+
+    /// CPU execution provider setting the CPU arena, if set: `ort` enables the arena when registering a provider
+    /// built `with_arena_allocator`, and disables it explicitly otherwise
+    fn cpu_provider(&self) -> Option<CPUExecutionProvider> {
+        self.cpu_arena.map(|enable| {
+            let cpu = CPUExecutionProvider::default();
+            match enable {
+                true => cpu.with_arena_allocator(),
+                false => cpu,
+            }
+        })
+    }
+}
+
+impl From<RuntimeParameters> for SessionParameters {
+    fn from(runtime: RuntimeParameters) -> Self {
+        Self::new(runtime)
+    }
+}
+
+/// A `Model` loads an ONNX model, and runs it using the provided pipeline.
+///
+/// This works like `orp::model::Model` (with any `orp` pipeline), but also keeps the description of
+/// the model inputs and outputs, read once from the session when loading it (see `info`).
+pub struct Model {
+    session: Session,
+    info: ModelInfo,
+    fallback_error: Option<String>,
+}
+
+impl Model {
+    pub fn new<P: AsRef<Path>>(
+        model_path: P,
+        params: impl Into<SessionParameters>,
+    ) -> Result<Self> {
+        let (session, fallback_error) = Self::session(params.into(), |builder| {
+            builder.commit_from_file(&model_path)
+        })?;
+        let info = ModelInfo::from_session(&session).with_file_name(model_path.as_ref());
+        Ok(Self {
+            session,
+            info,
+            fallback_error,
+        })
+    }
+
+    pub fn new_from_bytes(
+        model_bytes: &[u8],
+        params: impl Into<SessionParameters>,
+    ) -> Result<Self> {
+        let (session, fallback_error) = Self::session(params.into(), |builder| {
+            builder.commit_from_memory(model_bytes)
+        })?;
+        let info = ModelInfo::from_session(&session).with_graph(model_bytes);
+        Ok(Self {
+            session,
+            info,
+            fallback_error,
+        })
+    }
+
+    /// Creates the session with the given commit (from a file or from memory), along with the error of the
+    /// execution providers if it fell back to the CPU
+    fn session(
+        params: SessionParameters,
+        commit: impl Fn(SessionBuilder) -> ort::Result<Session>,
+    ) -> Result<(Session, Option<String>)> {
+        let with_providers = Self::builder(&params, true).and_then(|builder| Ok(commit(builder)?));
+        match with_providers {
+            Ok(session) => Ok((session, None)),
+            // without any execution provider, the CPU-only session would fail the same way
+            Err(error)
+                if params.cpu_fallback && !params.runtime.execution_providers().is_empty() =>
+            {
+                log::warn!("failed to create the session with the execution providers, falling back to the CPU: {error}");
+                let session = commit(Self::builder(&params, false)?)?;
+                Ok((session, Some(error.to_string())))
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Builds the session, with the execution providers or with the CPU only
+    fn builder(params: &SessionParameters, with_providers: bool) -> Result<SessionBuilder> {
+        // the CPU provider, registered after the other ones, sets the CPU arena
+        let cpu = params.cpu_provider().map(CPUExecutionProvider::build);
+        let providers = params
+            .runtime
+            .execution_providers()
+            .iter()
+            .filter(|_| with_providers)
+            .cloned()
+            .chain(cpu);
+        let mut builder = Session::builder()?
+            .with_intra_threads(params.runtime.threads())?
+            .with_execution_providers(providers)?
+            .with_optimization_level(params.optimization_level.into())?;
+        if let Some(enable) = params.memory_pattern {
+            builder = builder.with_memory_pattern(enable)?;
+        }
+        if let Some(threads) = params.inter_threads {
+            builder = builder
+                .with_parallel_execution(true)?
+                .with_inter_threads(threads)?;
+        }
+        Ok(builder)
+    }
+
+    /// Names, element types and shapes of the actual inputs and outputs of the model
+    pub fn info(&self) -> &ModelInfo {
+        &self.info
+    }
+
+    /// Error of the execution providers, if the model was loaded on the CPU instead (see
+    /// `SessionParameters::with_cpu_fallback`)
+    pub fn cpu_fallback(&self) -> Option<&str> {
+        self.fallback_error.as_deref()
+    }
+
+    /// Performs inferences using the provided pipeline and parameters
+    pub fn inference<'a, P: Pipeline<'a>>(
+        &'a self,
+        input: P::Input,
+        pipeline: &P,
+        params: &P::Parameters,
+    ) -> Result<P::Output> {
+        self.check_schema(pipeline)?;
+        let (input, context) = pipeline.pre_processor(params).apply(input)?;
+        let output = self.run(input)?;
+        pipeline.post_processor(params).apply((output, context))
+    }
+
+    pub fn to_composable<'a, P: Pipeline<'a>>(
+        &'a self,
+        pipeline: &'a P,
+        params: &'a P::Parameters,
+    ) -> impl Composable<P::Input, P::Output> + 'a {
+        ComposableModel {
+            model: self,
+            pipeline,
+            params,
+        }
+    }
+
+    /// Checks the model tensors against the pipeline expectations (see `ModelInfo::check`)
+    pub fn check_schema<'a, P: Pipeline<'a>>(&self, pipeline: &P) -> Result<()> {
+        self.info
+            .check(pipeline.expected_inputs(), pipeline.expected_outputs())
+    }
+
+    fn run(&self, input: SessionInputs<'_, '_>) -> Result<SessionOutputs<'_, '_>> {
+        Ok(self.session.run(input)?)
+    }
+}
+
+/// References a model, a pipeline and some parameters to implement `Composable`
+struct ComposableModel<'a, P: Pipeline<'a>> {
+    model: &'a Model,
+    pipeline: &'a P,
+    params: &'a P::Parameters,
+}
+
+impl<'a, P: Pipeline<'a>> Composable<P::Input, P::Output> for ComposableModel<'a, P> {
+    fn apply(&self, input: P::Input) -> Result<P::Output> {
+        self.model.inference(input, self.pipeline, self.params)
+    }
+}
+
+/// Test fixtures shared by the unit tests of the crate
+#[cfg(test)]
+pub(crate) mod testing {
+    /// Minimal ONNX model, with a single `Identity` node from the `x` input to the `y` output
+    pub fn identity_model() -> Vec<u8> {
+        // see https://github.com/onnx/onnx/blob/main/onnx/onnx.proto
+        let field = |tag: u8, bytes: &[u8]| [&[tag, bytes.len() as u8], bytes].concat();
+        // float tensor
+        let tensor_type = field(0x0a, &[0x08, 0x01]);
+        let value_info = |name: &[u8]| [field(0x0a, name), field(0x12, &tensor_type)].concat();
+        let node = [
+            field(0x0a, b"x"),
+            field(0x12, b"y"),
+            field(0x22, b"Identity"),
+        ]
+        .concat();
+        let graph = [
+            field(0x0a, &node),
+            field(0x12, b"g"),
+            field(0x5a, &value_info(b"x")),
+            field(0x62, &value_info(b"y")),
+        ]
+        .concat();
+        let opset = [field(0x0a, b""), vec![0x10, 13]].concat();
+        [vec![0x08, 0x08], field(0x42, &opset), field(0x3a, &graph)].concat()
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::testing::identity_model;
+    use super::*;
+    use ort::execution_providers::ROCmExecutionProvider;
+
+    #[test]
+    fn test_cpu_arena() {
+        let provider = |params: SessionParameters| format!("{:?}", params.cpu_provider());
+        assert_eq!(provider(SessionParameters::default()), "None");
+        assert_eq!(
+            provider(SessionParameters::default().with_cpu_arena(false)),
+            "Some(CPUExecutionProvider { use_arena: false })"
+        );
+        assert_eq!(
+            provider(SessionParameters::default().with_cpu_arena(true)),
+            "Some(CPUExecutionProvider { use_arena: true })"
+        );
+    }
+
+    #[test]
+    fn test_cpu_fallback() -> Result<()> {
+        // an execution provider which is not available in the CPU builds of ONNX Runtime
+        let params = || {
+            SessionParameters::new(RuntimeParameters::default().with_execution_providers([
+                ROCmExecutionProvider::default().build().error_on_failure(),
+            ]))
+        };
+        assert!(Model::new_from_bytes(&identity_model(), params()).is_err());
+
+        let model = Model::new_from_bytes(&identity_model(), params().with_cpu_fallback(true))?;
+        assert!(model.cpu_fallback().is_some());
+        assert_eq!(model.info().input_names().collect::<Vec<_>>(), vec!["x"]);
+        Ok(())
+    }
+}
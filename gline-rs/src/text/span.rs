@@ -1,4 +1,12 @@
-#[derive(Debug, Clone)]
+use serde::{Deserialize, Serialize};
+
+/// A span of text detected as an entity.
+///
+/// Spans are equal (and hashed) according to their sequence, offsets and class only: the probability is
+/// deliberately ignored, so that the same entity found in several windows or runs (with slightly different
+/// probabilities) is deduplicated by a `HashSet`. For the same reason, they are ordered by sequence, offsets
+/// and class, which is consistent with `SpanSort` except that the latter also orders by probability.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Span {
     /// Input index in the batch
     sequence: usize,
@@ -12,6 +20,10 @@ pub struct Span {
     class: String,
     /// Probability
     probability: f32,
+    /// Most probable classes with their probabilities, in decreasing order (only if requested, see the
+    /// `class_scores_threshold` parameter)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    class_scores: Option<Vec<(String, f32)>>,
 }
 
 impl Span {
@@ -31,6 +43,7 @@ impl Span {
             text,
             class,
             probability,
+            class_scores: None,
         }
     }
 
@@ -38,14 +51,44 @@ impl Span {
         self.sequence
     }
 
+    /// Returns the same span, referring to another sequence of the batch
+    pub fn with_sequence(mut self, sequence: usize) -> Self {
+        self.sequence = sequence;
+        self
+    }
+
+    /// Returns the same span, its offsets being shifted by the given number of bytes (e.g. to make them
+    /// relative to a whole document instead of a window of it)
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        self.start += offset;
+        self.end += offset;
+        self
+    }
+
     pub fn offsets(&self) -> (usize, usize) {
         (self.start, self.end)
     }
 
+    /// Returns the offsets in characters (instead of bytes) within the given text, which must be the sequence
+    /// this span was extracted from. Returns `None` if the offsets do not match the text.
+    pub fn char_offsets(&self, text: &str) -> Option<(usize, usize)> {
+        let start = text.get(..self.start)?.chars().count();
+        let end = start + text.get(self.start..self.end)?.chars().count();
+        Some((start, end))
+    }
+
+    /// Returns the same span, carrying the given text
+    pub fn with_text(mut self, text: String) -> Self {
+        self.text = text;
+        self
+    }
+
     pub fn text(&self) -> &str {
         &self.text
     }
 
+    /// Entity class, which is always one of the labels given as input, exactly as given (even if the labels
+    /// are normalized in the prompts, see the `normalize_labels` parameter)
     pub fn class(&self) -> &str {
         &self.class
     }
@@ -54,16 +97,39 @@ impl Span {
         self.probability
     }
 
+    /// Returns the same span, carrying the given class distribution
+    pub fn with_class_scores(mut self, class_scores: Option<Vec<(String, f32)>>) -> Self {
+        self.class_scores = class_scores;
+        self
+    }
+
+    /// Most probable classes for this span with their probabilities, in decreasing order (if requested)
+    pub fn class_scores(&self) -> Option<&[(String, f32)]> {
+        self.class_scores.as_deref()
+    }
+
     /// returns `true` iif this span is nested inside (or equals) the given span
     pub fn is_nested_in(&self, other: &Span) -> bool {
         self.start >= other.start && self.end <= other.end
     }
 
-    /// returns `true` iif this span overlaps with the given one (symetric)
+    /// returns `true` iif the given span is nested inside (or equals) this span
+    pub fn contains(&self, other: &Span) -> bool {
+        other.is_nested_in(self)
+    }
+
+    /// returns `true` iif this span overlaps with the given one (symetric).
+    /// Note that touching spans (the end of one being the start of the other) are considered as
+    /// overlapping, see `strictly_overlaps` otherwise.
     pub fn overlaps(&self, other: &Span) -> bool {
         !(other.start > self.end || other.end < self.start)
     }
 
+    /// returns `true` iif this span shares at least one byte with the given one (symetric)
+    pub fn strictly_overlaps(&self, other: &Span) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+
     /// returns `true` iif the spans do not overlap
     pub fn is_disjoint(&self, other: &Span) -> bool {
         !self.overlaps(other)
@@ -73,4 +139,93 @@ impl Span {
     pub fn same_offsets(&self, other: &Span) -> bool {
         self.start == other.start && self.end == other.end
     }
+
+    /// Fields defining the identity of a span (see the documentation of `Span`)
+    fn key(&self) -> (usize, usize, usize, &str) {
+        (self.sequence, self.start, self.end, &self.class)
+    }
+}
+
+impl PartialEq for Span {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Span {}
+
+impl std::hash::Hash for Span {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+impl PartialOrd for Span {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Span {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(start: usize, end: usize) -> Span {
+        Span::new(0, start, end, String::new(), "class".to_string(), 0.5)
+    }
+
+    #[test]
+    fn test_overlaps() {
+        // strictly overlapping
+        assert!(span(0, 5).overlaps(&span(3, 8)));
+        assert!(span(0, 5).strictly_overlaps(&span(3, 8)));
+        assert!(span(3, 8).strictly_overlaps(&span(0, 5)));
+        // touching
+        assert!(span(0, 5).overlaps(&span(5, 8)));
+        assert!(!span(0, 5).strictly_overlaps(&span(5, 8)));
+        // disjoint
+        assert!(!span(0, 5).overlaps(&span(6, 8)));
+        assert!(!span(0, 5).strictly_overlaps(&span(6, 8)));
+    }
+
+    #[test]
+    fn test_contains() {
+        assert!(span(0, 10).contains(&span(2, 5)));
+        assert!(span(0, 10).contains(&span(0, 10)));
+        assert!(!span(2, 5).contains(&span(0, 10)));
+        assert!(!span(0, 5).contains(&span(3, 8)));
+    }
+
+    #[test]
+    fn test_dedup() {
+        let span = |start: usize, class: &str, probability: f32| {
+            Span::new(
+                0,
+                start,
+                start + 5,
+                String::new(),
+                class.to_string(),
+                probability,
+            )
+        };
+        let spans: std::collections::HashSet<Span> = [
+            span(0, "person", 0.9),
+            span(0, "person", 0.8),
+            span(0, "company", 0.8),
+            span(6, "person", 0.7),
+        ]
+        .into();
+        assert_eq!(spans.len(), 3);
+        let mut spans: Vec<Span> = spans.into_iter().collect();
+        spans.sort();
+        let keys: Vec<(usize, &str)> = spans.iter().map(|s| (s.start, s.class())).collect();
+        assert_eq!(keys, vec![(0, "company"), (0, "person"), (6, "person")]);
+    }
 }
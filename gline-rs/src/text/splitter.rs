@@ -8,11 +8,17 @@ pub trait Splitter {
 }
 
 /// Word-level tokenization implemented using regular expressions
+#[derive(Clone)]
 pub struct RegexSplitter {
     regex: Regex,
 }
 
 impl RegexSplitter {
+    /// Creates a splitter where each match of `regex` is a word.
+    ///
+    /// Since entities are made of whole words, the pattern also determines the
+    /// possible entity offsets. For non-whitespace-delimited languages, a pattern
+    /// like `\p{Han}|\w+|\S` makes each ideogram a word of its own.
     pub fn new(regex: &str) -> Result<Self> {
         Ok(Self {
             regex: Regex::new(regex)?,
@@ -75,4 +81,17 @@ mod tests {
         assert_eq!(tokens.get(4).unwrap().text(), "w5");
         Ok(())
     }
+
+    #[test]
+    fn test_custom_regex() -> Result<()> {
+        let splitter = RegexSplitter::new("\\p{Han}|\\w+|\\S")?;
+        let tokens = splitter.split("我住在北京 (Beijing)", None)?;
+        assert_eq!(tokens.len(), 8);
+        let token = tokens.get(3).unwrap();
+        assert_eq!(token.start(), 9);
+        assert_eq!(token.end(), 12);
+        assert_eq!(token.text(), "北");
+        assert_eq!(tokens.get(6).unwrap().text(), "Beijing");
+        Ok(())
+    }
 }
@@ -1,5 +1,5 @@
 /// A token with text and start/end offsets
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Token {
     start: usize,
     end: usize,
@@ -1,12 +1,27 @@
 use crate::util::result::Result;
+use serde_json::Value;
 use std::path::Path;
+use tokenizers::normalizers::BertNormalizer;
+use tokenizers::pre_tokenizers::bert::BertPreTokenizer;
+use tokenizers::{decoders, models::wordpiece::WordPiece, AddedToken};
 
 /// Sub-word tokenization (aka encoding)
 pub trait Tokenizer {
     fn encode(&self, input: &str) -> Result<Vec<u32>>;
 }
 
+/// Tokenizer chosen at runtime, for example a custom implementation (e.g. for SentencePiece-based checkpoints)
+/// given to `SpanPipeline::with_tokenizer` or `GLiNER::new_with_tokenizer`
+pub type BoxedTokenizer = Box<dyn Tokenizer + Send + Sync>;
+
+impl Tokenizer for BoxedTokenizer {
+    fn encode(&self, input: &str) -> Result<Vec<u32>> {
+        self.as_ref().encode(input)
+    }
+}
+
 /// Implement `Tokenizer` as a wrapper around Hugging Face tokenizers
+#[derive(Clone)]
 pub struct HFTokenizer {
     inner: tokenizers::Tokenizer,
 }
@@ -18,6 +33,88 @@ impl HFTokenizer {
         })
     }
 
+    /// Loads the tokenizer from a directory of Hugging Face files, i.e. from `tokenizer.json` if present, or
+    /// otherwise assembles a WordPiece (BERT-like) tokenizer from `vocab.txt`, `tokenizer_config.json` and
+    /// `added_tokens.json` (the last two being optional).
+    pub fn from_pretrained_dir<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref();
+        let tokenizer_path = dir.join("tokenizer.json");
+        if tokenizer_path.is_file() {
+            return Self::from_file(tokenizer_path);
+        }
+        let vocab_path = dir.join("vocab.txt");
+        if !vocab_path.is_file() {
+            return Err(format!(
+                "no tokenizer found in {} (expected tokenizer.json or vocab.txt)",
+                dir.display()
+            )
+            .into());
+        }
+        let config = read_json(&dir.join("tokenizer_config.json"))?;
+        let flag = |key: &str| config.get(key).and_then(Value::as_bool);
+        let unk_token = config
+            .get("unk_token")
+            .and_then(|token| token.get("content").unwrap_or(token).as_str())
+            .unwrap_or("[UNK]");
+
+        let model = WordPiece::from_file(&vocab_path.to_string_lossy())
+            .unk_token(unk_token.to_string())
+            .build()?;
+        let mut inner = tokenizers::Tokenizer::new(model);
+        inner
+            .with_normalizer(Some(BertNormalizer::new(
+                true,
+                flag("tokenize_chinese_chars").unwrap_or(true),
+                flag("strip_accents"),
+                flag("do_lower_case").unwrap_or(true),
+            )))
+            .with_pre_tokenizer(Some(BertPreTokenizer))
+            .with_decoder(Some(decoders::wordpiece::WordPiece::default()));
+
+        // added tokens (e.g. `<<ENT>>` and `<<SEP>>`), in the order of their ids
+        let mut added_tokens: Vec<(u64, AddedToken)> = Vec::new();
+        if let Some(Value::Object(decoder)) = config.get("added_tokens_decoder") {
+            for (id, token) in decoder {
+                if let (Ok(id), Some(content)) = (id.parse(), token["content"].as_str()) {
+                    let special = token["special"].as_bool().unwrap_or(false);
+                    added_tokens.push((id, AddedToken::from(content.to_string(), special)));
+                }
+            }
+        }
+        if let Value::Object(tokens) = read_json(&dir.join("added_tokens.json"))? {
+            for (content, id) in tokens {
+                if let Some(id) = id.as_u64() {
+                    if !added_tokens.iter().any(|(_, t)| t.content == content) {
+                        added_tokens.push((id, AddedToken::from(content, true)));
+                    }
+                }
+            }
+        }
+        added_tokens.sort_by_key(|(id, _)| *id);
+        for (_, token) in added_tokens {
+            if token.special {
+                inner.add_special_tokens(&[token]);
+            } else {
+                inner.add_tokens(&[token]);
+            }
+        }
+
+        Ok(Self { inner })
+    }
+
+    /// Loads the tokenizer from a `tokenizer.json` file, or from a directory (see `from_pretrained_dir`). If
+    /// the file does not exist, the tokenizer is assembled from the other files of its directory as a fallback.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        if path.is_dir() {
+            return Self::from_pretrained_dir(path);
+        }
+        match path.parent() {
+            Some(dir) if !path.exists() && dir.is_dir() => Self::from_pretrained_dir(dir),
+            _ => Self::from_file(path),
+        }
+    }
+
     pub fn from_pretrained(identifier: &str) -> Result<Self> {
         Ok(Self {
             inner: tokenizers::Tokenizer::from_pretrained(identifier, None)?,
@@ -29,6 +126,19 @@ impl HFTokenizer {
             inner: tokenizers::Tokenizer::from_bytes(bytes)?,
         })
     }
+
+    /// Size of the vocabulary, including added tokens
+    pub fn vocab_size(&self) -> usize {
+        self.inner.get_vocab_size(true)
+    }
+}
+
+/// Reads an optional JSON file (`Value::Null` if it does not exist)
+fn read_json(path: &Path) -> Result<Value> {
+    if !path.is_file() {
+        return Ok(Value::Null);
+    }
+    Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
 }
 
 impl Tokenizer for HFTokenizer {
@@ -37,3 +147,59 @@ impl Tokenizer for HFTokenizer {
         Ok(encoding.get_ids().to_vec())
     }
 }
+
+/// Test fixtures shared by the unit tests of the crate
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::Tokenizer;
+    use crate::util::result::Result;
+
+    /// Tokenizer encoding each character as its code point, for tests which do not need an actual vocabulary
+    #[derive(Clone)]
+    pub struct CharTokenizer;
+
+    impl Tokenizer for CharTokenizer {
+        fn encode(&self, input: &str) -> Result<Vec<u32>> {
+            Ok(input.chars().map(u32::from).collect())
+        }
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::testing::CharTokenizer;
+    use super::*;
+
+    #[test]
+    fn test_boxed_tokenizer() -> Result<()> {
+        let tokenizer: BoxedTokenizer = Box::new(CharTokenizer);
+        let pipeline = crate::model::pipeline::span::SpanPipeline::with_tokenizer(
+            crate::text::splitter::RegexSplitter::default(),
+            tokenizer,
+        );
+        assert_eq!(pipeline.tokenizer().encode("ab")?, vec![97, 98]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_pretrained_dir() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("gliner-tokenizer-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(
+            dir.join("vocab.txt"),
+            "[PAD]\n[UNK]\n[CLS]\n[SEP]\nhello\nworld\n##s\n",
+        )?;
+        std::fs::write(
+            dir.join("tokenizer_config.json"),
+            r#"{"do_lower_case": true, "unk_token": "[UNK]", "added_tokens_decoder": {"7": {"content": "<<ENT>>", "special": true}}}"#,
+        )?;
+        // `tokenizer.json` is missing, so the tokenizer is assembled from the other files
+        let tokenizer = HFTokenizer::from_path(dir.join("tokenizer.json"))?;
+        let result = tokenizer.encode("Hello worlds <<ENT>> foo");
+        std::fs::remove_dir_all(&dir)?;
+        assert_eq!(result?, vec![4, 5, 6, 7, 1]);
+        assert_eq!(tokenizer.vocab_size(), 8);
+        Ok(())
+    }
+}
@@ -0,0 +1,201 @@
+//! Optional timing of the pipeline stages (see `Parameters::timings`)
+
+use crate::util::result::Result;
+use composable::Composable;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Stages of the NER pipelines
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Stage {
+    /// Splitting the texts into words
+    Splitting,
+    /// Building the prompts
+    Prompting,
+    /// Sub-word tokenization of the prompts
+    Encoding,
+    /// Building the input tensors
+    Tensors,
+    /// Running the ONNX model (measured as the remainder of the whole inference)
+    Session,
+    /// Decoding the output tensors into spans
+    Decoding,
+    /// Filtering, greedy search and other steps applied to the decoded spans
+    PostProcessing,
+}
+
+impl std::fmt::Display for Stage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Splitting => "splitting",
+            Self::Prompting => "prompting",
+            Self::Encoding => "encoding",
+            Self::Tensors => "tensors",
+            Self::Session => "session",
+            Self::Decoding => "decoding",
+            Self::PostProcessing => "post-processing",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Records the duration of each stage of every inference it is given to (one record per stage and
+/// per batch). It can be shared between calls, but records of concurrent calls would be mixed up.
+#[derive(Debug, Default)]
+pub struct Timings {
+    records: Mutex<Vec<(Stage, Duration)>>,
+}
+
+impl Timings {
+    pub fn record(&self, stage: Stage, duration: Duration) {
+        if let Ok(mut records) = self.records.lock() {
+            records.push((stage, duration));
+        }
+    }
+
+    /// Records, for the given stage, the given duration minus the ones recorded since the `from`-th record
+    /// (which is how the duration of the session is measured, see `Stage::Session`)
+    pub fn record_remainder(&self, stage: Stage, duration: Duration, from: usize) {
+        if let Ok(mut records) = self.records.lock() {
+            let recorded: Duration = records.iter().skip(from).map(|(_, d)| *d).sum();
+            records.push((stage, duration.saturating_sub(recorded)));
+        }
+    }
+
+    /// Number of records so far
+    pub fn len(&self) -> usize {
+        self.records.lock().map_or(0, |records| records.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns all the records so far, in chronological order
+    pub fn records(&self) -> Vec<(Stage, Duration)> {
+        self.records
+            .lock()
+            .map_or_else(|_| Vec::new(), |records| records.clone())
+    }
+
+    /// Returns the total duration of each stage recorded so far, in pipeline order
+    pub fn totals(&self) -> Vec<(Stage, Duration)> {
+        let mut totals: Vec<(Stage, Duration)> = Vec::new();
+        for (stage, duration) in self.records() {
+            match totals.iter_mut().find(|(s, _)| *s == stage) {
+                Some((_, total)) => *total += duration,
+                None => totals.push((stage, duration)),
+            }
+        }
+        totals.sort_by_key(|(stage, _)| *stage);
+        totals
+    }
+
+    /// Drops all the records so far
+    pub fn clear(&self) {
+        if let Ok(mut records) = self.records.lock() {
+            records.clear();
+        }
+    }
+}
+
+/// Summary of timings in three phases: pre-processing (up to the input tensors), session run, and
+/// post-processing (from the output tensors), e.g. for benchmarking (see `GLiNER::inference_timed`)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StageTimings {
+    pub pre_processing: Duration,
+    pub session: Duration,
+    pub post_processing: Duration,
+}
+
+impl StageTimings {
+    /// Sums up the durations recorded by the given timings
+    pub fn from_timings(timings: &Timings) -> Self {
+        let mut result = Self::default();
+        for (stage, duration) in timings.records() {
+            match stage {
+                Stage::Splitting | Stage::Prompting | Stage::Encoding | Stage::Tensors => {
+                    result.pre_processing += duration
+                }
+                Stage::Session => result.session += duration,
+                Stage::Decoding | Stage::PostProcessing => result.post_processing += duration,
+            }
+        }
+        result
+    }
+
+    pub fn total(&self) -> Duration {
+        self.pre_processing + self.session + self.post_processing
+    }
+}
+
+/// Composable wrapper recording the duration of the inner step as the given stage (if timings are set)
+pub struct Timed<C> {
+    stage: Stage,
+    inner: C,
+    timings: Option<Arc<Timings>>,
+}
+
+impl<C> Timed<C> {
+    pub fn new(stage: Stage, inner: C, timings: Option<Arc<Timings>>) -> Self {
+        Self {
+            stage,
+            inner,
+            timings,
+        }
+    }
+}
+
+impl<I, O, C: Composable<I, O>> Composable<I, O> for Timed<C> {
+    fn apply(&self, input: I) -> Result<O> {
+        match &self.timings {
+            None => self.inner.apply(input),
+            Some(timings) => {
+                let start = Instant::now();
+                let output = self.inner.apply(input);
+                timings.record(self.stage, start.elapsed());
+                output
+            }
+        }
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_totals() {
+        let timings = Timings::default();
+        timings.record(Stage::Decoding, Duration::from_millis(2));
+        timings.record(Stage::Splitting, Duration::from_millis(1));
+        timings.record(Stage::Decoding, Duration::from_millis(3));
+        timings.record_remainder(Stage::Session, Duration::from_millis(10), 1);
+        assert_eq!(
+            timings.totals(),
+            vec![
+                (Stage::Splitting, Duration::from_millis(1)),
+                (Stage::Session, Duration::from_millis(6)),
+                (Stage::Decoding, Duration::from_millis(5)),
+            ]
+        );
+        timings.clear();
+        assert!(timings.is_empty());
+    }
+
+    #[test]
+    fn test_stage_timings() {
+        let timings = Timings::default();
+        timings.record(Stage::Splitting, Duration::from_millis(1));
+        timings.record(Stage::Tensors, Duration::from_millis(2));
+        timings.record(Stage::Session, Duration::from_millis(10));
+        timings.record(Stage::Decoding, Duration::from_millis(3));
+        timings.record(Stage::PostProcessing, Duration::from_millis(1));
+        let stage_timings = StageTimings::from_timings(&timings);
+        assert_eq!(stage_timings.pre_processing, Duration::from_millis(3));
+        assert_eq!(stage_timings.session, Duration::from_millis(10));
+        assert_eq!(stage_timings.post_processing, Duration::from_millis(4));
+        assert_eq!(stage_timings.total(), Duration::from_millis(17));
+    }
+}